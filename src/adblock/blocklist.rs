@@ -0,0 +1,294 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Built-in categorized blocklist — ads, analytics, social, telemetry and
+//! fingerprinting domains
+//!
+//! Entries match a domain and its subdomains, optionally narrowed to a path
+//! prefix that must end at a segment boundary (`facebook.com/tr` matches
+//! `/tr?id=1` but not `/trending`).
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::filter::host_matches;
+
+/// What kind of third party an entry is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockCategory {
+    Ads,
+    Analytics,
+    Social,
+    Telemetry,
+    Fingerprinting,
+}
+
+impl BlockCategory {
+    pub const ALL: [BlockCategory; 5] = [
+        BlockCategory::Ads,
+        BlockCategory::Analytics,
+        BlockCategory::Social,
+        BlockCategory::Telemetry,
+        BlockCategory::Fingerprinting,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlockCategory::Ads => "ads",
+            BlockCategory::Analytics => "analytics",
+            BlockCategory::Social => "social",
+            BlockCategory::Telemetry => "telemetry",
+            BlockCategory::Fingerprinting => "fingerprinting",
+        }
+    }
+}
+
+/// A blocked domain, optionally limited to a path prefix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockEntry {
+    pub domain: String,
+    pub path: Option<String>,
+    pub category: BlockCategory,
+}
+
+impl BlockEntry {
+    /// Parse `domain` or `domain/path`
+    pub fn parse(entry: &str, category: BlockCategory) -> Option<Self> {
+        let entry = entry.trim().trim_start_matches("||").trim_end_matches('^');
+        let (domain, path) = match entry.find('/') {
+            Some(pos) => (&entry[..pos], Some(entry[pos..].trim_end_matches('/').to_string())),
+            None => (entry, None),
+        };
+        if domain.is_empty() || !domain.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'-') {
+            return None;
+        }
+        Some(Self {
+            domain: domain.to_ascii_lowercase(),
+            path: path.filter(|p| !p.is_empty()),
+            category,
+        })
+    }
+
+    fn matches(&self, host: &str, path: &str) -> bool {
+        if !host_matches(host, &self.domain) {
+            return false;
+        }
+        match &self.path {
+            Some(prefix) => {
+                path == prefix
+                    || (path.starts_with(prefix.as_str()) && path.as_bytes()[prefix.len()] == b'/')
+            }
+            None => true,
+        }
+    }
+
+    /// The entry as an ABP network rule
+    pub fn rule(&self) -> String {
+        match &self.path {
+            Some(path) => format!("||{}{}^", self.domain, path),
+            None => format!("||{}^", self.domain),
+        }
+    }
+}
+
+/// Built-in entries, grouped by category
+const BUILTIN: &[(BlockCategory, &[&str])] = &[
+    (BlockCategory::Ads, &[
+        // Google
+        "doubleclick.net",
+        "googlesyndication.com",
+        "googleadservices.com",
+        "googletagservices.com",
+        "adservice.google.com",
+        // Social network ad platforms
+        "an.facebook.com",
+        "ads.twitter.com",
+        "ads-api.twitter.com",
+        "static.ads-twitter.com",
+        "t.co/i/adsct",
+        "ads.linkedin.com",
+        "bat.bing.com",
+        // Amazon
+        "amazon-adsystem.com",
+        // Ad exchanges and networks
+        "pubmatic.com",
+        "rubiconproject.com",
+        "openx.net",
+        "indexexchange.com",
+        "casalemedia.com",
+        "outbrain.com",
+        "taboola.com",
+        "criteo.com",
+        "criteo.net",
+        "adnxs.com",
+        "adsrvr.org",
+        "moatads.com",
+        "yieldmanager.com",
+        "turn.com",
+        "adform.net",
+        "mediamath.com",
+        "bidswitch.net",
+        "mathtag.com",
+        "serving-sys.com",
+        "sizmek.com",
+    ]),
+    (BlockCategory::Analytics, &[
+        "google-analytics.com",
+        "googletagmanager.com",
+        "analytics.twitter.com",
+        "analytics.pointdrive.linkedin.com",
+        "scorecardresearch.com",
+        "quantserve.com",
+        "hotjar.com",
+        "fullstory.com",
+        "mouseflow.com",
+        "crazyegg.com",
+        "luckyorange.com",
+        "clarity.ms",
+        "mixpanel.com",
+        "mxpnl.com",
+        "segment.io",
+        "segment.com",
+        "amplitude.com",
+        "heapanalytics.com",
+        "kissmetrics.com",
+        "optimizely.com",
+        // Data management platforms
+        "demdex.net",
+        "bluekai.com",
+        "krxd.net",
+        "exelator.com",
+    ]),
+    (BlockCategory::Social, &[
+        "facebook.com/tr",
+        "connect.facebook.net",
+        "pixel.facebook.com",
+        "platform.twitter.com/widgets",
+        "snap.licdn.com",
+        "ct.pinterest.com",
+    ]),
+    (BlockCategory::Telemetry, &[
+        "newrelic.com",
+        "nr-data.net",
+        "browser-intake-datadoghq.com",
+        "sentry.io",
+        "bugsnag.com",
+        "raygun.com",
+        "rollbar.com",
+        "crashlytics.com",
+        "fls-na.amazon.com",
+    ]),
+    (BlockCategory::Fingerprinting, &[
+        "fpjs.io",
+        "fpcdn.io",
+        "openfpcdn.io",
+        "online-metrix.net",
+        "iesnare.com",
+    ]),
+];
+
+/// The built-in entries plus user additions, filtered by enabled category
+#[derive(Debug, Clone)]
+pub struct Blocklist {
+    entries: Vec<BlockEntry>,
+    enabled: Vec<BlockCategory>,
+}
+
+impl Blocklist {
+    /// Built-in entries with the given categories enabled
+    pub fn new(enabled: &[BlockCategory]) -> Self {
+        let entries = BUILTIN
+            .iter()
+            .flat_map(|(category, domains)| {
+                domains.iter().filter_map(move |d| BlockEntry::parse(d, *category))
+            })
+            .collect();
+        Self {
+            entries,
+            enabled: enabled.to_vec(),
+        }
+    }
+
+    /// Add an entry; returns false if it is not a valid `domain[/path]`
+    pub fn add(&mut self, entry: &str, category: BlockCategory) -> bool {
+        match BlockEntry::parse(entry, category) {
+            Some(entry) => {
+                if !self.entries.contains(&entry) {
+                    self.entries.push(entry);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_enabled(&self, category: BlockCategory) -> bool {
+        self.enabled.contains(&category)
+    }
+
+    pub fn set_enabled(&mut self, category: BlockCategory, enabled: bool) {
+        self.enabled.retain(|c| *c != category);
+        if enabled {
+            self.enabled.push(category);
+        }
+    }
+
+    /// Category of the first enabled entry matching `url`
+    pub fn matches(&self, url: &str) -> Option<BlockCategory> {
+        let url = Url::parse(url).ok()?;
+        let host = url.host_str()?.to_ascii_lowercase();
+        let path = url.path();
+        self.active()
+            .find(|entry| entry.matches(&host, path))
+            .map(|entry| entry.category)
+    }
+
+    /// Enabled entries as ABP network rules
+    pub fn rules(&self) -> Vec<String> {
+        self.active().map(BlockEntry::rule).collect()
+    }
+
+    /// Number of entries in `category`
+    pub fn count(&self, category: BlockCategory) -> usize {
+        self.entries.iter().filter(|e| e.category == category).count()
+    }
+
+    fn active(&self) -> impl Iterator<Item = &BlockEntry> {
+        self.entries.iter().filter(|e| self.enabled.contains(&e.category))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_entries_stop_at_segment_boundary() {
+        let list = Blocklist::new(&BlockCategory::ALL);
+        assert_eq!(list.matches("https://www.facebook.com/tr?id=1&ev=PageView"), Some(BlockCategory::Social));
+        assert_eq!(list.matches("https://www.facebook.com/trending"), None);
+        assert_eq!(list.matches("https://t.co/i/adsct?p_id=1"), Some(BlockCategory::Ads));
+        assert_eq!(list.matches("https://t.co/abc123"), None);
+    }
+
+    #[test]
+    fn domains_match_on_label_boundaries() {
+        let list = Blocklist::new(&BlockCategory::ALL);
+        assert_eq!(list.matches("https://ssl.google-analytics.com/ga.js"), Some(BlockCategory::Analytics));
+        assert_eq!(list.matches("https://notdoubleclick.net/"), None);
+        // Substring matches in the path or query no longer count
+        assert_eq!(list.matches("https://example.com/?ref=hotjar.com"), None);
+    }
+
+    #[test]
+    fn categories_toggle_independently() {
+        let mut list = Blocklist::new(&[BlockCategory::Ads]);
+        assert!(list.matches("https://doubleclick.net/x").is_some());
+        assert!(list.matches("https://sentry.io/api").is_none());
+        list.set_enabled(BlockCategory::Telemetry, true);
+        assert!(list.matches("https://sentry.io/api").is_some());
+        assert!(list.rules().contains(&"||sentry.io^".to_string()));
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Cosmetic (element-hiding) filters — `##selector` and `#@#selector` rules
+
+use std::collections::{HashMap, HashSet};
+
+use super::filter::host_matches;
+
+/// A parsed element-hiding rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct CosmeticFilter {
+    pub selector: String,
+    pub exception: bool,
+    pub include_domains: Vec<String>,
+    pub exclude_domains: Vec<String>,
+}
+
+impl CosmeticFilter {
+    /// Parse `example.com,~sub.example.com##.ad` or `#@#.ad`. Procedural
+    /// (`#?#`) and scriptlet (`#$#`) rules are not supported.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.starts_with('!') {
+            return None;
+        }
+
+        let (domains, selector, exception) = if let Some((d, s)) = line.split_once("#@#") {
+            (d, s, true)
+        } else if let Some((d, s)) = line.split_once("##") {
+            (d, s, false)
+        } else {
+            return None;
+        };
+
+        let selector = selector.trim();
+        if selector.is_empty() || !is_plain_selector(selector) {
+            return None;
+        }
+
+        let mut include_domains = Vec::new();
+        let mut exclude_domains = Vec::new();
+        for domain in domains.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match domain.strip_prefix('~') {
+                Some(d) => exclude_domains.push(d.to_ascii_lowercase()),
+                None => include_domains.push(domain.to_ascii_lowercase()),
+            }
+        }
+
+        Some(Self {
+            selector: selector.to_string(),
+            exception,
+            include_domains,
+            exclude_domains,
+        })
+    }
+
+    fn excluded_on(&self, host: &str) -> bool {
+        self.exclude_domains.iter().any(|d| host_matches(host, d))
+    }
+}
+
+/// Selectors a browser can apply as plain CSS (no ABP/uBO extended syntax)
+fn is_plain_selector(selector: &str) -> bool {
+    const EXTENDED: &[&str] = &[
+        ":-abp-", ":has-text(", ":contains(", ":matches-css", ":xpath(", ":upward(",
+        ":remove(", ":style(", ":min-text-length(", ":watch-attr(", ":matches-path(",
+        ":others(", "+js(",
+    ];
+    !selector.contains('{') && !EXTENDED.iter().any(|e| selector.contains(e))
+}
+
+/// Element-hiding rules indexed by domain
+#[derive(Default)]
+pub struct CosmeticIndex {
+    /// Selectors that apply everywhere
+    generic: Vec<CosmeticFilter>,
+    /// Site-specific hiding rules keyed by each of their include domains
+    by_domain: HashMap<String, Vec<String>>,
+    /// `#@#` exceptions keyed by domain; the empty key holds generic exceptions
+    exceptions: HashMap<String, HashSet<String>>,
+}
+
+impl CosmeticIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, filter: CosmeticFilter) {
+        if filter.exception {
+            if filter.include_domains.is_empty() {
+                self.exceptions.entry(String::new()).or_default().insert(filter.selector);
+            } else {
+                for domain in &filter.include_domains {
+                    self.exceptions.entry(domain.clone()).or_default().insert(filter.selector.clone());
+                }
+            }
+        } else if filter.include_domains.is_empty() {
+            self.generic.push(filter);
+        } else {
+            for domain in &filter.include_domains {
+                self.by_domain.entry(domain.clone()).or_default().push(filter.selector.clone());
+            }
+        }
+    }
+
+    /// Number of cosmetic rules indexed
+    pub fn len(&self) -> usize {
+        self.generic.len()
+            + self.by_domain.values().map(Vec::len).sum::<usize>()
+            + self.exceptions.values().map(HashSet::len).sum::<usize>()
+    }
+
+    /// Selectors to hide on `host`, generic and site-specific, minus exceptions
+    pub fn selectors_for_host(&self, host: &str) -> Vec<&str> {
+        let host = host.to_ascii_lowercase();
+        let parents = parent_domains(&host);
+
+        let excepted: HashSet<&str> = parents
+            .iter()
+            .copied()
+            .chain(std::iter::once(""))
+            .filter_map(|d| self.exceptions.get(d))
+            .flat_map(|set| set.iter().map(String::as_str))
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut selectors = Vec::new();
+
+        for filter in &self.generic {
+            if !filter.excluded_on(&host) && !excepted.contains(filter.selector.as_str())
+                && seen.insert(filter.selector.as_str())
+            {
+                selectors.push(filter.selector.as_str());
+            }
+        }
+
+        for domain in &parents {
+            if let Some(list) = self.by_domain.get(*domain) {
+                for selector in list {
+                    if !excepted.contains(selector.as_str()) && seen.insert(selector.as_str()) {
+                        selectors.push(selector.as_str());
+                    }
+                }
+            }
+        }
+
+        selectors
+    }
+
+    /// Build the element-hiding stylesheet for `host`
+    pub fn stylesheet_for_host(&self, host: &str) -> String {
+        // One rule per selector so a selector the engine rejects only drops itself
+        self.selectors_for_host(host)
+            .iter()
+            .map(|s| format!("{} {{ display: none !important; }}\n", s))
+            .collect()
+    }
+}
+
+/// `a.b.example.com` → `["a.b.example.com", "b.example.com", "example.com", "com"]`
+fn parent_domains(host: &str) -> Vec<&str> {
+    let mut domains = vec![host];
+    let mut rest = host;
+    while let Some(pos) = rest.find('.') {
+        rest = &rest[pos + 1..];
+        domains.push(rest);
+    }
+    domains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(rules: &str) -> CosmeticIndex {
+        let mut index = CosmeticIndex::new();
+        for filter in rules.lines().filter_map(CosmeticFilter::parse) {
+            index.insert(filter);
+        }
+        index
+    }
+
+    #[test]
+    fn generic_and_site_specific() {
+        let idx = index("##.ad-banner\nnews.com##.sponsored\n~shop.com##.promo");
+        assert_eq!(idx.selectors_for_host("www.news.com"), vec![".ad-banner", ".promo", ".sponsored"]);
+        assert_eq!(idx.selectors_for_host("shop.com"), vec![".ad-banner"]);
+    }
+
+    #[test]
+    fn exceptions_remove_selectors() {
+        let idx = index("##.ad-banner\n##.sidebar-ad\nblog.org#@#.ad-banner");
+        assert_eq!(idx.selectors_for_host("blog.org"), vec![".sidebar-ad"]);
+        assert_eq!(idx.selectors_for_host("other.org").len(), 2);
+    }
+
+    #[test]
+    fn extended_syntax_is_skipped() {
+        assert!(CosmeticFilter::parse("example.com##div:has-text(Sponsored)").is_none());
+        assert!(CosmeticFilter::parse("example.com#?#div:-abp-has(.ad)").is_none());
+    }
+}
@@ -0,0 +1,315 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Indexed filter engine — matches requests against tens of thousands of rules
+//!
+//! Filters are bucketed so a request only checks a handful of candidates:
+//! `||host^` filters by hostname (walked up label by label), everything else
+//! by the rarest token of its pattern that must appear in a matching URL.
+
+use std::collections::{HashMap, HashSet};
+
+use super::cosmetic::{CosmeticFilter, CosmeticIndex};
+use super::filter::{is_cosmetic, is_token_byte, parse_hosts_line, NetworkFilter, Request};
+
+/// One bucketed set of filters (blocking or exceptions)
+#[derive(Default)]
+struct FilterIndex {
+    by_host: HashMap<String, Vec<NetworkFilter>>,
+    by_token: HashMap<String, Vec<NetworkFilter>>,
+    fallback: Vec<NetworkFilter>,
+}
+
+impl FilterIndex {
+    fn insert(&mut self, filter: NetworkFilter) {
+        if let Some(host) = filter.hostname() {
+            self.by_host.entry(host.to_string()).or_default().push(filter);
+            return;
+        }
+
+        // Pick the token with the fewest filters already behind it
+        let token = filter
+            .tokens()
+            .into_iter()
+            .min_by_key(|t| self.by_token.get(t).map_or(0, |v| v.len()));
+
+        match token {
+            Some(token) => self.by_token.entry(token).or_default().push(filter),
+            None => self.fallback.push(filter),
+        }
+    }
+
+    fn find<'a>(&'a self, request: &Request, tokens: &[&str]) -> Option<&'a NetworkFilter> {
+        let mut host = request.host.as_str();
+        loop {
+            if let Some(found) = self.by_host.get(host)
+                .and_then(|bucket| bucket.iter().find(|f| f.matches(request)))
+            {
+                return Some(found);
+            }
+            match host.find('.') {
+                Some(pos) => host = &host[pos + 1..],
+                None => break,
+            }
+        }
+
+        for token in tokens {
+            if let Some(found) = self.by_token.get(*token)
+                .and_then(|bucket| bucket.iter().find(|f| f.matches(request)))
+            {
+                return Some(found);
+            }
+        }
+
+        self.fallback.iter().find(|f| f.matches(request))
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &NetworkFilter> {
+        self.by_host.values()
+            .chain(self.by_token.values())
+            .flatten()
+            .chain(self.fallback.iter())
+    }
+
+    fn len(&self) -> usize {
+        self.by_host.values().map(Vec::len).sum::<usize>()
+            + self.by_token.values().map(Vec::len).sum::<usize>()
+            + self.fallback.len()
+    }
+}
+
+/// Filter engine built from ABP filter lists and hosts files
+#[derive(Default)]
+pub struct FilterEngine {
+    blocking: FilterIndex,
+    important: FilterIndex,
+    exceptions: FilterIndex,
+    cosmetic: CosmeticIndex,
+}
+
+impl FilterEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a single ABP filter; returns false if the line is not a usable network filter
+    pub fn add_filter(&mut self, line: &str) -> bool {
+        match NetworkFilter::parse(line) {
+            Some(filter) => {
+                if filter.exception {
+                    self.exceptions.insert(filter);
+                } else if filter.important {
+                    self.important.insert(filter);
+                } else {
+                    self.blocking.insert(filter);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Block a hostname and all of its subdomains
+    pub fn add_hostname(&mut self, host: &str) -> bool {
+        self.add_filter(&format!("||{}^", host))
+    }
+
+    /// Add a single `##` / `#@#` element-hiding rule
+    pub fn add_cosmetic(&mut self, line: &str) -> bool {
+        match CosmeticFilter::parse(line) {
+            Some(filter) => {
+                self.cosmetic.insert(filter);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Load a filter list or hosts file; the format is detected per line.
+    /// Returns the number of network and cosmetic rules added.
+    pub fn load_list(&mut self, content: &str) -> usize {
+        let mut added = 0;
+        for line in content.lines() {
+            if let Some(hosts) = parse_hosts_line(line) {
+                added += hosts.iter().filter(|h| self.add_hostname(h)).count();
+            } else if is_cosmetic(line) {
+                if self.add_cosmetic(line) {
+                    added += 1;
+                }
+            } else if self.add_filter(line) {
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Find the blocking filter for a request, honouring `@@` exceptions
+    /// and `$important` overrides
+    pub fn check(&self, request: &Request) -> Option<&NetworkFilter> {
+        let tokens = url_tokens(&request.url);
+
+        if let Some(filter) = self.important.find(request, &tokens) {
+            return Some(filter);
+        }
+
+        let filter = self.blocking.find(request, &tokens)?;
+        if self.exceptions.find(request, &tokens).is_some() {
+            return None;
+        }
+        Some(filter)
+    }
+
+    /// Compile the network rules into WebKit content-blocker rules. Order
+    /// matters to WebKit: exceptions must follow the rules they undo, and
+    /// `$important` rules come last so exceptions cannot undo them.
+    pub fn content_rules(&self) -> Vec<serde_json::Value> {
+        self.blocking.iter()
+            .chain(self.exceptions.iter())
+            .chain(self.important.iter())
+            .flat_map(NetworkFilter::to_content_rules)
+            .collect()
+    }
+
+    /// Element-hiding stylesheet for pages on `host`
+    pub fn cosmetic_stylesheet(&self, host: &str) -> String {
+        self.cosmetic.stylesheet_for_host(host)
+    }
+
+    /// Total number of network rules loaded
+    pub fn rule_count(&self) -> usize {
+        self.blocking.len() + self.important.len() + self.exceptions.len()
+    }
+
+    /// Total number of element-hiding rules loaded
+    pub fn cosmetic_rule_count(&self) -> usize {
+        self.cosmetic.len()
+    }
+}
+
+/// Split a URL into unique index tokens
+fn url_tokens(url: &str) -> Vec<&str> {
+    let bytes = url.as_bytes();
+    let mut seen = HashSet::new();
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for pos in 0..=bytes.len() {
+        let is_tok = pos < bytes.len() && is_token_byte(bytes[pos]);
+        match (start, is_tok) {
+            (None, true) => start = Some(pos),
+            (Some(s), false) => {
+                let token = &url[s..pos];
+                if token.len() > 1 && seen.insert(token) {
+                    tokens.push(token);
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine(rules: &str) -> FilterEngine {
+        let mut engine = FilterEngine::new();
+        engine.load_list(rules);
+        engine
+    }
+
+    fn blocked(engine: &FilterEngine, url: &str, source: &str, ty: &str) -> bool {
+        let request = Request::new(url, source, ty).unwrap();
+        engine.check(&request).is_some()
+    }
+
+    #[test]
+    fn hostname_anchor_matches_subdomains_only() {
+        let e = engine("||ads.example.com^");
+        assert!(blocked(&e, "https://ads.example.com/banner.png", "https://news.com/", ""));
+        assert!(blocked(&e, "https://cdn.ads.example.com/x.js", "https://news.com/", ""));
+        assert!(!blocked(&e, "https://badads.example.com/x.js", "https://news.com/", ""));
+        assert!(!blocked(&e, "https://example.com/ads.example.com", "https://news.com/", ""));
+    }
+
+    #[test]
+    fn exception_overrides_block() {
+        let e = engine("||tracker.net^\n@@||tracker.net/allowed.js");
+        assert!(blocked(&e, "https://tracker.net/t.js", "https://site.com/", "script"));
+        assert!(!blocked(&e, "https://tracker.net/allowed.js", "https://site.com/", "script"));
+    }
+
+    #[test]
+    fn third_party_and_type_options() {
+        let e = engine("||widgets.com^$third-party,script");
+        assert!(blocked(&e, "https://widgets.com/w.js", "https://blog.org/", "script"));
+        assert!(!blocked(&e, "https://widgets.com/w.js", "https://www.widgets.com/", "script"));
+        assert!(!blocked(&e, "https://widgets.com/w.png", "https://blog.org/", "image"));
+    }
+
+    #[test]
+    fn domain_option() {
+        let e = engine("/adframe/*$domain=news.com|~sports.news.com");
+        assert!(blocked(&e, "https://cdn.net/adframe/1.html", "https://www.news.com/", ""));
+        assert!(!blocked(&e, "https://cdn.net/adframe/1.html", "https://sports.news.com/", ""));
+        assert!(!blocked(&e, "https://cdn.net/adframe/1.html", "https://other.com/", ""));
+    }
+
+    #[test]
+    fn wildcard_and_separator() {
+        let e = engine("/banner/*/img^");
+        assert!(blocked(&e, "https://a.com/banner/foo/img?x=1", "", ""));
+        assert!(blocked(&e, "https://a.com/banner/foo/img", "", ""));
+        assert!(!blocked(&e, "https://a.com/banner/foo/imgs", "", ""));
+    }
+
+    #[test]
+    fn hosts_file_format() {
+        let e = engine("# comment\n0.0.0.0 tracker.io\n127.0.0.1 localhost\n0.0.0.0 a.io b.io");
+        assert_eq!(e.rule_count(), 3);
+        assert!(blocked(&e, "https://tracker.io/p", "", ""));
+        assert!(blocked(&e, "https://x.b.io/p", "", ""));
+    }
+
+    #[test]
+    fn content_rules_translate_hostname_anchor() {
+        let e = engine("||ads.example.com^$third-party");
+        let rules = e.content_rules();
+        assert_eq!(rules.len(), 2);
+        let trigger = &rules[0]["trigger"];
+        assert_eq!(trigger["url-filter"], "^[^:]+://+([^/]*\\.)?ads\\.example\\.com([^a-z0-9_.%-].*)?$");
+        assert_eq!(trigger["load-type"][0], "third-party");
+        assert_eq!(rules[0]["action"]["type"], "block");
+        // Third-party frames are blocked too
+        assert_eq!(rules[1]["trigger"]["resource-type"], serde_json::json!(["document"]));
+        assert_eq!(rules[1]["trigger"]["load-context"], serde_json::json!(["child-frame"]));
+        assert_eq!(rules[1]["trigger"]["load-type"][0], "third-party");
+    }
+
+    #[test]
+    fn content_rules_leave_top_level_documents_alone() {
+        let rules = engine("||sentry.io^").content_rules();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(
+            rules[0]["trigger"]["resource-type"],
+            serde_json::json!(["script", "style-sheet", "image", "svg-document", "media", "font", "raw", "ping"])
+        );
+        assert_eq!(rules[1]["trigger"]["resource-type"], serde_json::json!(["document"]));
+        assert_eq!(rules[1]["trigger"]["load-context"], serde_json::json!(["child-frame"]));
+        assert!(!blocked(&engine("||sentry.io^"), "https://sentry.io/", "", "document"));
+
+        // Only $document blocks navigation to the site itself
+        let rules = engine("||phish.example^$document").content_rules();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0]["trigger"]["resource-type"], serde_json::json!(["document"]));
+        assert!(rules[0]["trigger"].get("load-context").is_none());
+    }
+
+    #[test]
+    fn important_beats_exception() {
+        let e = engine("||bad.com^$important\n@@||bad.com^");
+        assert!(blocked(&e, "https://bad.com/", "https://x.com/", ""));
+    }
+}
@@ -0,0 +1,587 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Network filter parsing — Adblock Plus / uBlock syntax and hosts files
+
+use std::net::IpAddr;
+use regex::Regex;
+
+use crate::utils::domain;
+
+/// Resource type of a request, as used by `$script`, `$image`, ... options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestType {
+    Document,
+    Subdocument,
+    Script,
+    Stylesheet,
+    Image,
+    Media,
+    Font,
+    Xhr,
+    Websocket,
+    Ping,
+    Other,
+}
+
+impl RequestType {
+    /// Parse the request type names used by WebKit, Chromium and filter options
+    pub fn parse(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "document" | "doc" | "main_frame" | "mainframe" => RequestType::Document,
+            "subdocument" | "sub_frame" | "frame" | "iframe" => RequestType::Subdocument,
+            "script" => RequestType::Script,
+            "stylesheet" | "css" => RequestType::Stylesheet,
+            "image" | "img" | "imageset" => RequestType::Image,
+            "media" | "video" | "audio" => RequestType::Media,
+            "font" => RequestType::Font,
+            "xmlhttprequest" | "xhr" | "fetch" => RequestType::Xhr,
+            "websocket" => RequestType::Websocket,
+            "ping" | "beacon" => RequestType::Ping,
+            _ => RequestType::Other,
+        }
+    }
+
+    /// Guess the request type from the URL path when the engine gives us none
+    pub fn from_url(url: &str) -> Self {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        let ext = path.rsplit('/').next()
+            .and_then(|file| file.rsplit_once('.'))
+            .map(|(_, ext)| ext.to_ascii_lowercase())
+            .unwrap_or_default();
+        match ext.as_str() {
+            "js" | "mjs" => RequestType::Script,
+            "css" => RequestType::Stylesheet,
+            "png" | "jpg" | "jpeg" | "gif" | "webp" | "svg" | "ico" | "avif" | "bmp" => RequestType::Image,
+            "woff" | "woff2" | "ttf" | "otf" | "eot" => RequestType::Font,
+            "mp4" | "webm" | "mp3" | "ogg" | "m3u8" | "wav" => RequestType::Media,
+            "html" | "htm" => RequestType::Subdocument,
+            _ => RequestType::Other,
+        }
+    }
+
+    fn bit(self) -> u16 {
+        1 << (self as u16)
+    }
+}
+
+/// All request types set
+const ALL_TYPES: u16 = (1 << 11) - 1;
+/// Types a filter without type options applies to: everything but the
+/// top-level document, which only `$document` blocks
+const DEFAULT_TYPES: u16 = ALL_TYPES & !(1 << RequestType::Document as u16);
+
+/// A request as seen by the filter engine
+#[derive(Debug, Clone)]
+pub struct Request {
+    /// Lower-cased full URL
+    pub url: String,
+    /// Hostname of the requested resource
+    pub host: String,
+    /// Hostname of the page that issued the request (empty if unknown)
+    pub source_host: String,
+    pub request_type: RequestType,
+    pub third_party: bool,
+}
+
+impl Request {
+    /// Build a request from raw URLs; returns None for URLs without a host
+    pub fn new(url: &str, source_url: &str, request_type: &str) -> Option<Self> {
+        let parsed = url::Url::parse(url).ok()?;
+        let host = parsed.host_str()?.trim_end_matches('.').to_ascii_lowercase();
+        let source_host = url::Url::parse(source_url)
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.trim_end_matches('.').to_ascii_lowercase()))
+            .unwrap_or_default();
+
+        let request_type = if request_type.is_empty() {
+            RequestType::from_url(url)
+        } else {
+            RequestType::parse(request_type)
+        };
+
+        let third_party = !source_host.is_empty() && !domain::same_site(&host, &source_host);
+
+        Some(Self {
+            url: parsed.as_str().to_ascii_lowercase(),
+            host,
+            source_host,
+            request_type,
+            third_party,
+        })
+    }
+}
+
+/// Whether `host` equals `domain` or is one of its subdomains
+pub fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.len() > domain.len()
+            && host.ends_with(domain)
+            && host.as_bytes()[host.len() - domain.len() - 1] == b'.')
+}
+
+/// How the start of a pattern is tied to the URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Anchor {
+    None,
+    /// `|pattern` — start of the URL
+    Left,
+    /// `||pattern` — start of the hostname or one of its labels
+    Hostname,
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    /// Glob pattern split on `*`; `^` is kept as a separator placeholder
+    Glob(Vec<String>),
+    /// `/regex/` filters
+    Regex(Regex),
+}
+
+/// A single parsed network filter
+#[derive(Debug, Clone)]
+pub struct NetworkFilter {
+    pub raw: String,
+    pub exception: bool,
+    pub important: bool,
+    anchor: Anchor,
+    right_anchor: bool,
+    pattern: Pattern,
+    /// None = any party, Some(true) = third-party only, Some(false) = first-party only
+    third_party: Option<bool>,
+    types: u16,
+    include_domains: Vec<String>,
+    exclude_domains: Vec<String>,
+}
+
+impl NetworkFilter {
+    /// Parse one network filter line. Returns None for comments, cosmetic
+    /// rules and filters using options we do not support.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty()
+            || line.starts_with('!')
+            || line.starts_with('[')
+            || line.starts_with('#')
+            || line.contains(char::is_whitespace)
+            || is_cosmetic(line)
+        {
+            return None;
+        }
+
+        let raw = line.to_string();
+        let (exception, rest) = match line.strip_prefix("@@") {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+
+        // Split off options; a `$` inside a regex filter belongs to the regex
+        let (body, options) = match rest.rfind('$') {
+            Some(pos) if !(rest.starts_with('/') && rest[pos..].contains('/')) => {
+                (&rest[..pos], Some(&rest[pos + 1..]))
+            }
+            _ => (rest, None),
+        };
+
+        let mut filter = NetworkFilter {
+            raw,
+            exception,
+            important: false,
+            anchor: Anchor::None,
+            right_anchor: false,
+            pattern: Pattern::Glob(Vec::new()),
+            third_party: None,
+            types: DEFAULT_TYPES,
+            include_domains: Vec::new(),
+            exclude_domains: Vec::new(),
+        };
+
+        if let Some(options) = options {
+            filter.parse_options(options)?;
+        }
+
+        if body.len() > 2 && body.starts_with('/') && body.ends_with('/') {
+            let source = &body[1..body.len() - 1];
+            filter.pattern = Pattern::Regex(Regex::new(&format!("(?i){}", source)).ok()?);
+            return Some(filter);
+        }
+
+        let mut body = body;
+        if let Some(rest) = body.strip_prefix("||") {
+            filter.anchor = Anchor::Hostname;
+            body = rest;
+        } else if let Some(rest) = body.strip_prefix('|') {
+            filter.anchor = Anchor::Left;
+            body = rest;
+        }
+        if let Some(rest) = body.strip_suffix('|') {
+            filter.right_anchor = true;
+            body = rest;
+        }
+
+        // Requests are matched lower-cased, so `$match-case` is accepted but ignored
+        let body = body.to_ascii_lowercase();
+        let parts: Vec<String> = body
+            .split('*')
+            .filter(|p| !p.is_empty())
+            .map(|p| p.to_string())
+            .collect();
+
+        // A filter that matches everything is almost certainly a broken line
+        if parts.is_empty() && filter.anchor == Anchor::None && filter.include_domains.is_empty() {
+            return None;
+        }
+
+        filter.pattern = Pattern::Glob(parts);
+        Some(filter)
+    }
+
+    fn parse_options(&mut self, options: &str) -> Option<()> {
+        let mut include_types = 0u16;
+        let mut exclude_types = 0u16;
+
+        for option in options.split(',') {
+            let option = option.trim();
+            let (negated, name) = match option.strip_prefix('~') {
+                Some(name) => (true, name),
+                None => (false, option),
+            };
+
+            if let Some(domains) = name.strip_prefix("domain=") {
+                for domain in domains.split('|').filter(|d| !d.is_empty()) {
+                    match domain.strip_prefix('~') {
+                        Some(d) => self.exclude_domains.push(d.to_ascii_lowercase()),
+                        None => self.include_domains.push(domain.to_ascii_lowercase()),
+                    }
+                }
+                continue;
+            }
+
+            match name {
+                "third-party" | "3p" => self.third_party = Some(!negated),
+                "first-party" | "1p" => self.third_party = Some(negated),
+                "important" => self.important = true,
+                "match-case" | "all" => {}
+                _ => {
+                    let ty = match name {
+                        "script" => RequestType::Script,
+                        "image" => RequestType::Image,
+                        "stylesheet" | "css" => RequestType::Stylesheet,
+                        "xmlhttprequest" | "xhr" => RequestType::Xhr,
+                        "subdocument" | "frame" => RequestType::Subdocument,
+                        "document" | "doc" => RequestType::Document,
+                        "font" => RequestType::Font,
+                        "media" => RequestType::Media,
+                        "websocket" => RequestType::Websocket,
+                        "ping" => RequestType::Ping,
+                        "other" | "object" | "object-subrequest" => RequestType::Other,
+                        // Unsupported options ($csp, $redirect, $popup, ...) — skip the
+                        // whole filter rather than apply it more broadly than intended
+                        _ => return None,
+                    };
+                    if negated {
+                        exclude_types |= ty.bit();
+                    } else {
+                        include_types |= ty.bit();
+                    }
+                }
+            }
+        }
+
+        let types = if include_types != 0 { include_types } else { DEFAULT_TYPES };
+        self.types = types & !exclude_types;
+        if self.types == 0 {
+            return None;
+        }
+        Some(())
+    }
+
+    /// Hostname this filter is pinned to, for `||host^` style filters
+    pub fn hostname(&self) -> Option<&str> {
+        if self.anchor != Anchor::Hostname {
+            return None;
+        }
+        let parts = match &self.pattern {
+            Pattern::Glob(parts) if parts.len() == 1 => parts,
+            _ => return None,
+        };
+        let host = parts[0].strip_suffix('^')
+            .or_else(|| if self.right_anchor { Some(parts[0].as_str()) } else { None })?;
+        if !host.is_empty() && host.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'-') {
+            Some(host)
+        } else {
+            None
+        }
+    }
+
+    /// Tokens of the pattern that must appear as whole tokens in any matching URL
+    pub fn tokens(&self) -> Vec<String> {
+        let parts = match &self.pattern {
+            Pattern::Glob(parts) => parts,
+            Pattern::Regex(_) => return Vec::new(),
+        };
+        let mut tokens = Vec::new();
+        let last = parts.len().saturating_sub(1);
+        for (i, part) in parts.iter().enumerate() {
+            // A `*` (or an unanchored end) next to a token means it may be partial
+            let left_bounded = i == 0 && self.anchor != Anchor::None;
+            let right_bounded = i == last && self.right_anchor;
+
+            let bytes = part.as_bytes();
+            let mut start = None;
+            for pos in 0..=bytes.len() {
+                let is_tok = pos < bytes.len() && is_token_byte(bytes[pos]);
+                match (start, is_tok) {
+                    (None, true) => start = Some(pos),
+                    (Some(s), false) => {
+                        let bounded_left = s > 0 || left_bounded;
+                        let bounded_right = pos < bytes.len() || right_bounded;
+                        if bounded_left && bounded_right && pos - s > 1 {
+                            tokens.push(part[s..pos].to_ascii_lowercase());
+                        }
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        tokens
+    }
+
+    /// Translate the filter into WebKit content-blocker rules: one for
+    /// subresources, and one for documents, limited to child frames unless
+    /// the filter has `$document`. Regex filters and filters mixing
+    /// included and excluded domains have no equivalent.
+    pub fn to_content_rules(&self) -> Vec<serde_json::Value> {
+        let parts = match &self.pattern {
+            Pattern::Glob(parts) => parts,
+            Pattern::Regex(_) => return Vec::new(),
+        };
+
+        let mut url_filter = match self.anchor {
+            Anchor::Hostname => String::from("^[^:]+://+([^/]*\\.)?"),
+            Anchor::Left => String::from("^"),
+            Anchor::None => String::new(),
+        };
+        for (i, part) in parts.iter().enumerate() {
+            if i > 0 {
+                url_filter.push_str(".*");
+            }
+            let bytes = part.as_bytes();
+            for (j, &b) in bytes.iter().enumerate() {
+                match b {
+                    // `^` at the very end may also match the end of the URL
+                    b'^' if i == parts.len() - 1 && j == bytes.len() - 1 && !self.right_anchor => {
+                        url_filter.push_str("([^a-z0-9_.%-].*)?$")
+                    }
+                    b'^' => url_filter.push_str("[^a-z0-9_.%-]"),
+                    b'.' | b'?' | b'+' | b'(' | b')' | b'[' | b']' | b'{' | b'}' | b'\\' | b'|' | b'$' => {
+                        url_filter.push('\\');
+                        url_filter.push(b as char);
+                    }
+                    _ => url_filter.push(b as char),
+                }
+            }
+        }
+        if self.right_anchor {
+            url_filter.push('$');
+        }
+        if url_filter.is_empty() {
+            url_filter.push_str(".*");
+        }
+
+        let mut trigger = serde_json::json!({ "url-filter": url_filter });
+        if let Some(third_party) = self.third_party {
+            trigger["load-type"] = serde_json::json!([if third_party { "third-party" } else { "first-party" }]);
+        }
+
+        let wildcard = |domains: &[String]| -> Vec<String> {
+            domains.iter().map(|d| format!("*{}", d)).collect()
+        };
+        match (self.include_domains.is_empty(), self.exclude_domains.is_empty()) {
+            (true, true) => {}
+            (false, true) => trigger["if-domain"] = serde_json::json!(wildcard(&self.include_domains)),
+            (true, false) => trigger["unless-domain"] = serde_json::json!(wildcard(&self.exclude_domains)),
+            (false, false) => return Vec::new(),
+        }
+
+        let mut resources: Vec<&str> = Vec::new();
+        for (ty, name) in [
+            (RequestType::Script, "script"),
+            (RequestType::Stylesheet, "style-sheet"),
+            (RequestType::Image, "image"),
+            (RequestType::Image, "svg-document"),
+            (RequestType::Media, "media"),
+            (RequestType::Font, "font"),
+            (RequestType::Xhr, "raw"),
+            (RequestType::Websocket, "raw"),
+            (RequestType::Ping, "ping"),
+            (RequestType::Other, "raw"),
+        ] {
+            if self.types & ty.bit() != 0 && !resources.contains(&name) {
+                resources.push(name);
+            }
+        }
+
+        let action = if self.exception { "ignore-previous-rules" } else { "block" };
+        let rule = |trigger: serde_json::Value| serde_json::json!({
+            "trigger": trigger,
+            "action": { "type": action },
+        });
+        let mut rules = Vec::new();
+        if !resources.is_empty() {
+            let mut trigger = trigger.clone();
+            trigger["resource-type"] = serde_json::json!(resources);
+            rules.push(rule(trigger));
+        }
+        if self.types & RequestType::Document.bit() != 0 {
+            trigger["resource-type"] = serde_json::json!(["document"]);
+            rules.push(rule(trigger));
+        } else if self.types & RequestType::Subdocument.bit() != 0 {
+            trigger["resource-type"] = serde_json::json!(["document"]);
+            trigger["load-context"] = serde_json::json!(["child-frame"]);
+            rules.push(rule(trigger));
+        }
+        rules
+    }
+
+    /// Check whether this filter applies to the request
+    pub fn matches(&self, request: &Request) -> bool {
+        if self.types & request.request_type.bit() == 0 {
+            return false;
+        }
+        if let Some(third_party) = self.third_party {
+            if third_party != request.third_party {
+                return false;
+            }
+        }
+        if !self.include_domains.is_empty()
+            && !self.include_domains.iter().any(|d| host_matches(&request.source_host, d))
+        {
+            return false;
+        }
+        if self.exclude_domains.iter().any(|d| host_matches(&request.source_host, d)) {
+            return false;
+        }
+        self.matches_url(request)
+    }
+
+    fn matches_url(&self, request: &Request) -> bool {
+        let parts = match &self.pattern {
+            Pattern::Regex(re) => return re.is_match(&request.url),
+            Pattern::Glob(parts) => parts,
+        };
+        let url = request.url.as_bytes();
+
+        if parts.is_empty() {
+            return true;
+        }
+
+        match self.anchor {
+            Anchor::Left => self.match_parts_from(url, parts, 0, true),
+            Anchor::None => self.match_parts_from(url, parts, 0, false),
+            Anchor::Hostname => {
+                let host_start = match request.url.find("://").and_then(|scheme_end| {
+                    request.url[scheme_end + 3..]
+                        .find(request.host.as_str())
+                        .map(|pos| scheme_end + 3 + pos)
+                }) {
+                    Some(pos) => pos,
+                    None => return false,
+                };
+                let host_end = host_start + request.host.len();
+                let mut candidates = vec![host_start];
+                candidates.extend(
+                    url[host_start..host_end.min(url.len())]
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, b)| **b == b'.')
+                        .map(|(i, _)| host_start + i + 1),
+                );
+                candidates
+                    .into_iter()
+                    .any(|start| self.match_parts_from(url, parts, start, true))
+            }
+        }
+    }
+
+    /// Match glob parts left to right; the first part is pinned to `start` when `pinned`
+    fn match_parts_from(&self, url: &[u8], parts: &[String], start: usize, pinned: bool) -> bool {
+        let mut pos = start;
+        let last = parts.len() - 1;
+        for (i, part) in parts.iter().enumerate() {
+            let part = part.as_bytes();
+            let must_end = i == last && self.right_anchor;
+            let found = if i == 0 && pinned {
+                match_at(url, pos, part).filter(|end| !must_end || *end == url.len())
+            } else if must_end {
+                // Right-anchored tail: try the only position that can end at the URL end
+                (pos..=url.len())
+                    .rev()
+                    .find_map(|p| match_at(url, p, part).filter(|end| *end == url.len()))
+            } else {
+                (pos..=url.len()).find_map(|p| match_at(url, p, part))
+            };
+            match found {
+                Some(end) => pos = end,
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Try to match a glob part (no `*`) at `pos`, returning the end offset
+fn match_at(url: &[u8], pos: usize, part: &[u8]) -> Option<usize> {
+    let mut u = pos;
+    for (i, &p) in part.iter().enumerate() {
+        if p == b'^' {
+            if u == url.len() {
+                // `^` may match the end of the URL, but only as the final char
+                return if i == part.len() - 1 { Some(u) } else { None };
+            }
+            if !is_separator(url[u]) {
+                return None;
+            }
+        } else if u >= url.len() || url[u] != p {
+            return None;
+        }
+        u += 1;
+    }
+    Some(u)
+}
+
+/// ABP separator: anything but a letter, digit, or one of `_-.%`
+fn is_separator(b: u8) -> bool {
+    !(b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.' | b'%'))
+}
+
+pub(crate) fn is_token_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'%'
+}
+
+/// Cosmetic rules (`##`, `#@#`, `#?#`, `#$#`) are not network filters
+pub fn is_cosmetic(line: &str) -> bool {
+    line.contains("##") || line.contains("#@#") || line.contains("#?#") || line.contains("#$#")
+}
+
+/// Parse a hosts-file line (`0.0.0.0 ads.example.com`) into the blocked hostnames
+pub fn parse_hosts_line(line: &str) -> Option<Vec<String>> {
+    let line = line.split('#').next().unwrap_or("").trim();
+    let mut fields = line.split_whitespace();
+    fields.next()?.parse::<IpAddr>().ok()?;
+
+    let hosts: Vec<String> = fields
+        .map(|h| h.trim_end_matches('.').to_ascii_lowercase())
+        .filter(|h| {
+            !matches!(
+                h.as_str(),
+                "localhost" | "localhost.localdomain" | "local" | "broadcasthost"
+                    | "ip6-localhost" | "ip6-loopback" | "0.0.0.0"
+            )
+        })
+        .collect();
+
+    if hosts.is_empty() { None } else { Some(hosts) }
+}
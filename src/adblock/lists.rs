@@ -0,0 +1,432 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Filter-list cache — downloads remote lists and keeps the last good copy on disk
+//!
+//! Each list is stored next to an index recording its ETag, Last-Modified,
+//! `! Expires:` interval and SHA-256. Refreshes are conditional requests, and a
+//! failed or empty download never replaces a copy that already works.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use super::FilterEngine;
+use crate::config::{AdblockConfig, Config};
+use crate::network;
+
+const INDEX_FILE: &str = "lists.json";
+/// Refresh interval for lists without an `! Expires:` header
+const DEFAULT_EXPIRY_HOURS: i64 = 24;
+const MIN_EXPIRY_HOURS: i64 = 1;
+const MAX_EXPIRY_HOURS: i64 = 14 * 24;
+
+/// Cache state for one remote filter list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListInfo {
+    pub url: String,
+    /// File name of the cached copy inside the cache directory
+    pub file: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub expires_hours: i64,
+    pub sha256: Option<String>,
+    pub rule_count: usize,
+    /// When the cached content last changed
+    pub last_updated: Option<DateTime<Utc>>,
+    /// When the server last answered with a usable copy or a 304. Failed
+    /// checks leave it alone, so the list stays due until one succeeds.
+    pub last_checked: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+impl ListInfo {
+    fn new(url: &str) -> Self {
+        let digest = sha256_hex(url.as_bytes());
+        Self {
+            url: url.to_string(),
+            file: format!("{}.txt", &digest[..16]),
+            etag: None,
+            last_modified: None,
+            expires_hours: DEFAULT_EXPIRY_HOURS,
+            sha256: None,
+            rule_count: 0,
+            last_updated: None,
+            last_checked: None,
+            last_error: None,
+        }
+    }
+
+    /// Whether the list should be checked for updates at `now`
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        match self.last_checked {
+            Some(checked) => now - checked >= chrono::Duration::hours(self.expires_hours),
+            None => true,
+        }
+    }
+}
+
+/// Result of refreshing one list
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefreshOutcome {
+    Updated(usize),
+    NotModified,
+    Failed(String),
+}
+
+/// Downloads and caches the remote lists named in `AdblockConfig::filter_lists`
+pub struct FilterListManager {
+    dir: PathBuf,
+    lists: Vec<ListInfo>,
+}
+
+impl FilterListManager {
+    /// Open the cache in `dir` for the given list URLs. Local paths are ignored;
+    /// `AdBlocker::from_config` reads those directly.
+    pub fn new(dir: PathBuf, urls: &[String]) -> Self {
+        let known: Vec<ListInfo> = fs::read_to_string(dir.join(INDEX_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        let lists = urls
+            .iter()
+            .filter(|url| is_remote(url))
+            .map(|url| {
+                known.iter()
+                    .find(|info| &info.url == url)
+                    .cloned()
+                    .unwrap_or_else(|| ListInfo::new(url))
+            })
+            .collect();
+
+        Self { dir, lists }
+    }
+
+    /// Open the cache under `Config::cache_dir()`
+    pub fn open(config: &AdblockConfig) -> Result<Self, String> {
+        let dir = Config::cache_dir()
+            .map_err(|e| format!("No cache directory: {}", e))?
+            .join("filter-lists");
+        Ok(Self::new(dir, &config.filter_lists))
+    }
+
+    pub fn lists(&self) -> &[ListInfo] {
+        &self.lists
+    }
+
+    /// Whether any list is due for a refresh
+    pub fn any_due(&self) -> bool {
+        let now = Utc::now();
+        self.lists.iter().any(|info| info.is_due(now))
+    }
+
+    /// Refresh lists that are due (or all of them when `force` is set) and
+    /// persist the index. Returns one outcome per list checked.
+    pub async fn refresh(&mut self, force: bool) -> Vec<(String, RefreshOutcome)> {
+        let now = Utc::now();
+        let mut outcomes = Vec::new();
+
+        for i in 0..self.lists.len() {
+            if !force && !self.lists[i].is_due(now) {
+                continue;
+            }
+            let outcome = self.refresh_list(i).await;
+            match &outcome {
+                RefreshOutcome::Updated(rules) => info!("Updated filter list {} ({} rules)", self.lists[i].url, rules),
+                RefreshOutcome::NotModified => info!("Filter list {} not modified", self.lists[i].url),
+                RefreshOutcome::Failed(e) => warn!("Failed to refresh {}: {}", self.lists[i].url, e),
+            }
+            outcomes.push((self.lists[i].url.clone(), outcome));
+        }
+
+        if !outcomes.is_empty() {
+            if let Err(e) = self.save_index() {
+                warn!("Failed to save filter list index: {}", e);
+            }
+        }
+        outcomes
+    }
+
+    async fn refresh_list(&mut self, index: usize) -> RefreshOutcome {
+        let has_copy = self.read_cached(&self.lists[index]).is_some();
+        let info = &mut self.lists[index];
+
+        let client = match network::client() {
+            Ok(client) => client,
+            Err(e) => return Self::fail(info, e),
+        };
+        let mut request = client.get(&info.url).timeout(Duration::from_secs(60));
+        // Validators only make sense while the copy they describe is intact
+        if has_copy {
+            if let Some(etag) = &info.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(modified) = &info.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, modified);
+            }
+        }
+
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => return Self::fail(info, format!("Request failed: {}", e)),
+        };
+
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            info.last_checked = Some(Utc::now());
+            info.last_error = None;
+            return RefreshOutcome::NotModified;
+        }
+        if !response.status().is_success() {
+            return Self::fail(info, format!("HTTP {}", response.status()));
+        }
+
+        let header = |name: reqwest::header::HeaderName| {
+            response.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+        };
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+
+        let content = match response.text().await {
+            Ok(content) => content,
+            Err(e) => return Self::fail(info, format!("Failed to read body: {}", e)),
+        };
+
+        let rule_count = FilterEngine::new().load_list(&content);
+        if rule_count == 0 {
+            return Self::fail(info, "Download contained no usable rules".to_string());
+        }
+
+        if let Err(e) = write_atomic(&self.dir, &info.file, content.as_bytes()) {
+            return Self::fail(info, e);
+        }
+
+        info.etag = etag;
+        info.last_modified = last_modified;
+        info.expires_hours = parse_expires(&content).unwrap_or(DEFAULT_EXPIRY_HOURS);
+        info.sha256 = Some(sha256_hex(content.as_bytes()));
+        info.rule_count = rule_count;
+        info.last_updated = Some(Utc::now());
+        info.last_checked = info.last_updated;
+        info.last_error = None;
+        RefreshOutcome::Updated(rule_count)
+    }
+
+    fn fail(info: &mut ListInfo, error: String) -> RefreshOutcome {
+        info.last_error = Some(error.clone());
+        RefreshOutcome::Failed(error)
+    }
+
+    /// Cached content for a list, if present and matching its recorded checksum
+    fn read_cached(&self, info: &ListInfo) -> Option<String> {
+        let expected = info.sha256.as_ref()?;
+        let content = fs::read_to_string(self.dir.join(&info.file)).ok()?;
+        if &sha256_hex(content.as_bytes()) != expected {
+            warn!("Cached filter list {} failed its checksum", info.url);
+            return None;
+        }
+        Some(content)
+    }
+
+    /// Content of every intact cached list
+    pub fn cached_lists(&self) -> Vec<(String, String)> {
+        self.lists
+            .iter()
+            .filter_map(|info| self.read_cached(info).map(|content| (info.url.clone(), content)))
+            .collect()
+    }
+
+    fn save_index(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.lists)
+            .map_err(|e| format!("Failed to serialize index: {}", e))?;
+        write_atomic(&self.dir, INDEX_FILE, json.as_bytes())
+    }
+
+    /// Refresh due lists on a background thread, checking every `interval`.
+    /// `on_update` is called from that thread whenever a list changed.
+    pub fn spawn_scheduler<F>(mut self, interval: Duration, on_update: F)
+    where
+        F: Fn() + Send + 'static,
+    {
+        std::thread::spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    warn!("Filter list updater could not start: {}", e);
+                    return;
+                }
+            };
+
+            loop {
+                if self.any_due() {
+                    let outcomes = runtime.block_on(self.refresh(false));
+                    if outcomes.iter().any(|(_, o)| matches!(o, RefreshOutcome::Updated(_))) {
+                        on_update();
+                    }
+                }
+                std::thread::sleep(interval);
+            }
+        });
+    }
+}
+
+fn is_remote(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+/// Parse `! Expires: 4 days` / `! Expires: 12 hours` from a list header
+fn parse_expires(content: &str) -> Option<i64> {
+    for line in content.lines().take(50) {
+        let line = line.trim();
+        if !line.starts_with('!') && !line.starts_with('#') && !line.is_empty() && !line.starts_with('[') {
+            break;
+        }
+        let value = match line.trim_start_matches(['!', '#']).trim().strip_prefix("Expires:") {
+            Some(value) => value.trim(),
+            None => continue,
+        };
+
+        let mut parts = value.split_whitespace();
+        let amount: i64 = parts.next()?.parse().ok()?;
+        let hours = match parts.next().unwrap_or("days") {
+            unit if unit.starts_with("hour") => amount,
+            unit if unit.starts_with("day") => amount * 24,
+            _ => return None,
+        };
+        return Some(hours.clamp(MIN_EXPIRY_HOURS, MAX_EXPIRY_HOURS));
+    }
+    None
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Write via a temporary file so a crash never leaves a half-written list
+fn write_atomic(dir: &Path, name: &str, bytes: &[u8]) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let tmp = dir.join(format!("{}.tmp", name));
+    fs::write(&tmp, bytes).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    fs::rename(&tmp, dir.join(name)).map_err(|e| format!("Failed to replace {}: {}", name, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    const LIST: &str = "[Adblock Plus 2.0]\n! Title: Test\n! Expires: 2 days\n||ads.example^\n##.banner\n";
+
+    /// Minimal HTTP server: serves `body` with an ETag, answers 304 when the
+    /// client presents it, and 500 once `body` is cleared.
+    fn serve(body: Arc<Mutex<Option<String>>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut if_none_match = false;
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+                    if line.to_ascii_lowercase().starts_with("if-none-match: \"v1\"") {
+                        if_none_match = true;
+                    }
+                    line.clear();
+                }
+
+                let mut stream = stream;
+                let response = match body.lock().unwrap().clone() {
+                    _ if if_none_match => "HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\n\r\n".to_string(),
+                    Some(body) => format!(
+                        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\n\r\n{}",
+                        body.len(), body
+                    ),
+                    None => "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\n\r\n".to_string(),
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{}/list.txt", addr)
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("marshall-lists-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn downloads_caches_and_revalidates() {
+        let body = Arc::new(Mutex::new(Some(LIST.to_string())));
+        let url = serve(body.clone());
+        let dir = temp_dir();
+
+        let mut manager = FilterListManager::new(dir.clone(), std::slice::from_ref(&url));
+        let outcomes = manager.refresh(false).await;
+        assert_eq!(outcomes[0].1, RefreshOutcome::Updated(2));
+        assert_eq!(manager.lists()[0].expires_hours, 48);
+        assert_eq!(manager.lists()[0].etag.as_deref(), Some("\"v1\""));
+
+        // A fresh manager picks up the index and sends the ETag back
+        let mut manager = FilterListManager::new(dir.clone(), &[url]);
+        assert!(!manager.any_due());
+        assert_eq!(manager.refresh(true).await[0].1, RefreshOutcome::NotModified);
+        assert_eq!(manager.cached_lists()[0].1, LIST);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn failure_keeps_last_good_copy() {
+        let body = Arc::new(Mutex::new(Some(LIST.to_string())));
+        let url = serve(body.clone());
+        let dir = temp_dir();
+
+        let mut manager = FilterListManager::new(dir.clone(), &[url]);
+        manager.refresh(false).await;
+        manager.lists[0].etag = None;
+        *body.lock().unwrap() = None;
+
+        assert!(matches!(manager.refresh(true).await[0].1, RefreshOutcome::Failed(_)));
+        assert_eq!(manager.cached_lists().len(), 1);
+        assert!(manager.lists()[0].last_error.is_some());
+        assert!(manager.lists()[0].last_checked.is_some());
+
+        // A corrupted copy is rejected by its checksum
+        fs::write(dir.join(&manager.lists()[0].file), "tampered").unwrap();
+        assert!(manager.cached_lists().is_empty());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn failed_fetch_is_retried_next_tick() {
+        let body = Arc::new(Mutex::new(None));
+        let url = serve(body.clone());
+        let dir = temp_dir();
+
+        let mut manager = FilterListManager::new(dir.clone(), &[url]);
+        assert!(matches!(manager.refresh(false).await[0].1, RefreshOutcome::Failed(_)));
+        assert!(manager.lists()[0].last_checked.is_none());
+        assert!(manager.any_due());
+
+        *body.lock().unwrap() = Some(LIST.to_string());
+        assert_eq!(manager.refresh(false).await[0].1, RefreshOutcome::Updated(2));
+        assert!(!manager.any_due());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn expires_header() {
+        assert_eq!(parse_expires("! Title: x\n! Expires: 4 days (update frequency)\n||a^"), Some(96));
+        assert_eq!(parse_expires("! Expires: 12 hours\n"), Some(12));
+        assert_eq!(parse_expires("||a^\n! Expires: 1 day"), None);
+    }
+}
@@ -2,133 +2,152 @@
 // Licensed under the MIT License. See LICENSE file in the project root.
 // https://github.com/bad-antics/marshall
 
-//! Ad blocking engine (stub when adblock feature disabled)
+//! Ad blocking engine — Adblock Plus / uBlock filter lists and hosts files
 
-use std::collections::HashSet;
+mod filter;
+mod cosmetic;
+mod blocklist;
+mod engine;
+mod lists;
+
+pub use filter::{host_matches, NetworkFilter, Request, RequestType};
+pub use cosmetic::CosmeticFilter;
+pub use blocklist::{BlockCategory, BlockEntry, Blocklist};
+pub use engine::FilterEngine;
+pub use lists::{FilterListManager, ListInfo, RefreshOutcome};
+
+use std::path::Path;
 use std::sync::Arc;
 use parking_lot::RwLock;
-use tracing::info;
+use tracing::{info, warn};
+
+use crate::config::{AdblockConfig, BlockCategories};
 
-/// Ad blocker using custom rules
+/// Ad blocker backed by the filter engine. Its built-in rules are the `Ads`
+/// entries of the categorized `Blocklist`; the other categories belong to
+/// the tracker blocker.
 pub struct AdBlocker {
     enabled: bool,
     blocked_count: Arc<RwLock<u64>>,
-    blocklist: HashSet<String>,
+    engine: FilterEngine,
 }
 
 impl AdBlocker {
+    /// Ad blocker with the built-in ad domains
     pub fn new(enabled: bool) -> Self {
-        let mut blocklist = HashSet::new();
-        // ── Google Ads & Analytics ──
-        blocklist.insert("doubleclick.net".to_string());
-        blocklist.insert("googlesyndication.com".to_string());
-        blocklist.insert("googleadservices.com".to_string());
-        blocklist.insert("google-analytics.com".to_string());
-        blocklist.insert("googletagmanager.com".to_string());
-        blocklist.insert("googletagservices.com".to_string());
-        blocklist.insert("pagead2.googlesyndication.com".to_string());
-        blocklist.insert("adservice.google.com".to_string());
-        blocklist.insert("ssl.google-analytics.com".to_string());
-        // ── Meta / Facebook ──
-        blocklist.insert("facebook.com/tr".to_string());
-        blocklist.insert("connect.facebook.net".to_string());
-        blocklist.insert("pixel.facebook.com".to_string());
-        blocklist.insert("an.facebook.com".to_string());
-        // ── Twitter / X ──
-        blocklist.insert("ads.twitter.com".to_string());
-        blocklist.insert("analytics.twitter.com".to_string());
-        blocklist.insert("ads-api.twitter.com".to_string());
-        // ── Microsoft / LinkedIn ──
-        blocklist.insert("bat.bing.com".to_string());
-        blocklist.insert("ads.linkedin.com".to_string());
-        blocklist.insert("px.ads.linkedin.com".to_string());
-        blocklist.insert("analytics.pointdrive.linkedin.com".to_string());
-        // ── Amazon Ads ──
-        blocklist.insert("aax.amazon-adsystem.com".to_string());
-        blocklist.insert("z-na.amazon-adsystem.com".to_string());
-        blocklist.insert("fls-na.amazon.com".to_string());
-        // ── Common Trackers ──
-        blocklist.insert("scorecardresearch.com".to_string());
-        blocklist.insert("quantserve.com".to_string());
-        blocklist.insert("hotjar.com".to_string());
-        blocklist.insert("fullstory.com".to_string());
-        blocklist.insert("mouseflow.com".to_string());
-        blocklist.insert("crazyegg.com".to_string());
-        blocklist.insert("luckyorange.com".to_string());
-        blocklist.insert("clarity.ms".to_string());
-        blocklist.insert("newrelic.com".to_string());
-        blocklist.insert("nr-data.net".to_string());
-        blocklist.insert("mixpanel.com".to_string());
-        blocklist.insert("segment.io".to_string());
-        blocklist.insert("segment.com".to_string());
-        blocklist.insert("amplitude.com".to_string());
-        blocklist.insert("heapanalytics.com".to_string());
-        blocklist.insert("kissmetrics.com".to_string());
-        blocklist.insert("optimizely.com".to_string());
-        // ── Ad Networks ──
-        blocklist.insert("pubmatic.com".to_string());
-        blocklist.insert("rubiconproject.com".to_string());
-        blocklist.insert("openx.net".to_string());
-        blocklist.insert("indexexchange.com".to_string());
-        blocklist.insert("casalemedia.com".to_string());
-        blocklist.insert("outbrain.com".to_string());
-        blocklist.insert("taboola.com".to_string());
-        blocklist.insert("criteo.com".to_string());
-        blocklist.insert("criteo.net".to_string());
-        blocklist.insert("adnxs.com".to_string());
-        blocklist.insert("adsrvr.org".to_string());
-        blocklist.insert("moatads.com".to_string());
-        blocklist.insert("yieldmanager.com".to_string());
-        blocklist.insert("turn.com".to_string());
-        blocklist.insert("demdex.net".to_string());
-        blocklist.insert("bluekai.com".to_string());
-        blocklist.insert("krxd.net".to_string());
-        blocklist.insert("exelator.com".to_string());
-        blocklist.insert("adform.net".to_string());
-        blocklist.insert("mediamath.com".to_string());
-        blocklist.insert("bidswitch.net".to_string());
-        blocklist.insert("mathtag.com".to_string());
-        blocklist.insert("serving-sys.com".to_string());
-        blocklist.insert("sizmek.com".to_string());
-        // ── Social Widgets / Beacons ──
-        blocklist.insert("platform.twitter.com/widgets".to_string());
-        blocklist.insert("snap.licdn.com".to_string());
-        blocklist.insert("static.ads-twitter.com".to_string());
-        blocklist.insert("ct.pinterest.com".to_string());
-        blocklist.insert("t.co/i/adsct".to_string());
-        // ── Fingerprinting / Telemetry ──
-        blocklist.insert("cdn.mxpnl.com".to_string());
-        blocklist.insert("browser-intake-datadoghq.com".to_string());
-        blocklist.insert("sentry.io".to_string());
-        blocklist.insert("bugsnag.com".to_string());
-        blocklist.insert("raygun.com".to_string());
-        blocklist.insert("rollbar.com".to_string());
-        
-        info!("AdBlocker loaded {} rules", blocklist.len());
-        
+        Self::with_builtin_ads(enabled, true)
+    }
+
+    fn with_builtin_ads(enabled: bool, ads: bool) -> Self {
+        let mut engine = FilterEngine::new();
+        if ads {
+            for rule in Blocklist::new(&[BlockCategory::Ads]).rules() {
+                engine.add_filter(&rule);
+            }
+        }
         Self {
             enabled,
             blocked_count: Arc::new(RwLock::new(0)),
-            blocklist,
+            engine,
+        }
+    }
+
+    /// Create an ad blocker from local filter list files, cached copies of
+    /// remote lists and custom rules from the config. The built-in ad
+    /// domains are included while the `ads` category is enabled.
+    pub fn from_config(config: &AdblockConfig, categories: &BlockCategories) -> Self {
+        let lists = FilterListManager::open(config)
+            .map_err(|e| warn!("Filter list cache unavailable: {}", e))
+            .ok();
+        Self::with_lists(config, categories, lists.as_ref())
+    }
+
+    /// Like `from_config`, with the cached remote lists taken from `lists`
+    pub fn with_lists(config: &AdblockConfig, categories: &BlockCategories, lists: Option<&FilterListManager>) -> Self {
+        let mut blocker = Self::with_builtin_ads(config.enabled, categories.ads);
+
+        for list in &config.filter_lists {
+            let path = Path::new(list);
+            if path.is_file() {
+                if let Err(e) = blocker.load_filter_file(path) {
+                    warn!("Failed to load filter list {}: {}", list, e);
+                }
+            }
+        }
+
+        for (url, content) in lists.map(FilterListManager::cached_lists).unwrap_or_default() {
+            let added = blocker.load_filter_list(&content);
+            info!("Loaded {} rules from cached {}", added, url);
         }
+
+        for rule in &config.custom_rules {
+            blocker.add_rule(rule.clone());
+        }
+
+        info!("AdBlocker loaded {} rules", blocker.rule_count());
+        blocker
+    }
+
+    /// Load an ABP filter list or hosts file from disk, returning the number of rules added
+    pub fn load_filter_file(&mut self, path: &Path) -> Result<usize, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let added = self.load_filter_list(&content);
+        info!("Loaded {} rules from {}", added, path.display());
+        Ok(added)
+    }
+
+    /// Load filter list content, returning the number of rules added
+    pub fn load_filter_list(&mut self, content: &str) -> usize {
+        self.engine.load_list(content)
     }
 
     /// Check if a request should be blocked
-    pub fn should_block(&self, url: &str, _source_url: &str, _request_type: &str) -> bool {
+    pub fn should_block(&self, url: &str, source_url: &str, request_type: &str) -> bool {
         if !self.enabled {
             return false;
         }
 
-        for domain in &self.blocklist {
-            if url.contains(domain) {
-                *self.blocked_count.write() += 1;
-                return true;
-            }
+        let request = match Request::new(url, source_url, request_type) {
+            Some(request) => request,
+            None => return false,
+        };
+
+        if self.engine.check(&request).is_some() {
+            *self.blocked_count.write() += 1;
+            return true;
         }
         
         false
     }
 
+    /// WebKit content-blocker rules for everything the engine would block
+    pub fn content_rules(&self) -> Vec<serde_json::Value> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        self.engine.content_rules()
+    }
+
+    /// Element-hiding stylesheet for pages on `host`, if there is anything to hide
+    pub fn cosmetic_stylesheet(&self, host: &str) -> Option<String> {
+        if !self.enabled || host.is_empty() {
+            return None;
+        }
+        let css = self.engine.cosmetic_stylesheet(host);
+        if css.is_empty() { None } else { Some(css) }
+    }
+
+    /// Number of network rules loaded
+    pub fn rule_count(&self) -> usize {
+        self.engine.rule_count()
+    }
+
+    /// Number of element-hiding rules loaded
+    pub fn cosmetic_rule_count(&self) -> usize {
+        self.engine.cosmetic_rule_count()
+    }
+
     /// Get count of blocked requests
     pub fn blocked_count(&self) -> u64 {
         *self.blocked_count.read()
@@ -143,8 +162,26 @@ impl AdBlocker {
         self.enabled
     }
     
-    pub fn add_rule(&mut self, domain: String) {
-        self.blocklist.insert(domain);
+    /// Add a rule in ABP syntax; a bare domain blocks that domain and its
+    /// subdomains, and `##` / `#@#` rules hide elements
+    pub fn add_rule(&mut self, rule: String) {
+        if filter::is_cosmetic(&rule) {
+            self.engine.add_cosmetic(&rule);
+        } else {
+            self.engine.add_filter(&Self::domain_rule(&rule));
+        }
+    }
+
+    /// Turn a bare `domain` or `domain/path` into an ABP hostname rule
+    fn domain_rule(rule: &str) -> String {
+        let is_bare = rule.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'-' | b'/' | b'_'));
+        if !is_bare {
+            rule.to_string()
+        } else if rule.contains('/') {
+            format!("||{}", rule)
+        } else {
+            format!("||{}^", rule)
+        }
     }
 }
 
@@ -153,3 +190,26 @@ impl Default for AdBlocker {
         Self::new(true)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_cosmetic_rules_hide_elements() {
+        let mut config = AdblockConfig {
+            enabled: true,
+            filter_lists: Vec::new(),
+            custom_rules: vec!["news.example##.promo".to_string(), "tracker.example".to_string()],
+            whitelist: Vec::new(),
+        };
+        let blocker = AdBlocker::with_lists(&config, &BlockCategories::default(), None);
+        assert!(blocker.cosmetic_stylesheet("news.example").unwrap().contains(".promo"));
+        assert!(blocker.cosmetic_stylesheet("other.example").is_none());
+        assert!(blocker.should_block("https://tracker.example/t.js", "https://news.example/", "script"));
+
+        config.custom_rules.push("news.example#@#.promo".to_string());
+        let blocker = AdBlocker::with_lists(&config, &BlockCategories::default(), None);
+        assert!(blocker.cosmetic_stylesheet("news.example").is_none());
+    }
+}
@@ -10,9 +10,15 @@ use std::sync::Arc;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use tokio::runtime::Runtime;
 
+use crate::network;
+
+/// How long a provider may take to answer; generations outlast the
+/// network timeout
+const RESPONSE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
 /// Supported AI providers
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum AIProvider {
@@ -281,7 +287,6 @@ pub struct AIEngine {
     pub config: Arc<RwLock<AIModelConfig>>,
     pub messages: Arc<RwLock<Vec<ChatMessage>>>,
     pub tools: Vec<ToolDefinition>,
-    client: Client,
     runtime: Runtime,
 }
 
@@ -291,7 +296,6 @@ impl AIEngine {
             config: Arc::new(RwLock::new(config)),
             messages: Arc::new(RwLock::new(Vec::new())),
             tools: Vec::new(),
-            client: Client::new(),
             runtime: Runtime::new().expect("Failed to create Tokio runtime"),
         };
         
@@ -430,6 +434,16 @@ impl AIEngine {
         Ok(response)
     }
 
+    /// The shared client, so provider traffic follows the network settings
+    fn client(&self) -> Result<Client, AIError> {
+        network::client().map_err(AIError::NetworkError)
+    }
+
+    /// A request to a provider, which may take a while to answer
+    fn post(&self, endpoint: &str) -> Result<RequestBuilder, AIError> {
+        Ok(self.client()?.post(endpoint).timeout(RESPONSE_TIMEOUT))
+    }
+
     /// Send request to AI provider
     async fn send_request(&self) -> Result<String, AIError> {
         let config = self.config.read().clone();
@@ -467,7 +481,7 @@ impl AIEngine {
             request["tools"] = serde_json::to_value(&self.tools).unwrap_or_default();
         }
 
-        let mut req = self.client.post(endpoint)
+        let mut req = self.post(endpoint)?
             .header("Content-Type", "application/json")
             .json(&request);
 
@@ -536,7 +550,7 @@ impl AIEngine {
         let api_key = config.api_key.as_ref()
             .ok_or_else(|| AIError::ConfigError("Anthropic API key required".to_string()))?;
 
-        let response = self.client.post(endpoint)
+        let response = self.post(endpoint)?
             .header("Content-Type", "application/json")
             .header("x-api-key", api_key)
             .header("anthropic-version", "2023-06-01")
@@ -592,7 +606,7 @@ impl AIEngine {
             }
         });
 
-        let response = self.client.post(endpoint)
+        let response = self.post(endpoint)?
             .header("Content-Type", "application/json")
             .json(&request)
             .send()
@@ -655,7 +669,7 @@ impl AIEngine {
         let api_key = config.api_key.as_ref()
             .ok_or_else(|| AIError::ConfigError("Cohere API key required".to_string()))?;
 
-        let response = self.client.post(endpoint)
+        let response = self.post(endpoint)?
             .header("Content-Type", "application/json")
             .header("Authorization", format!("Bearer {}", api_key))
             .json(&request)
@@ -708,7 +722,11 @@ impl AIEngine {
     /// Check if local AI (Ollama) is available
     pub fn check_local_ai(&self) -> bool {
         self.runtime.block_on(async {
-            self.client.get("http://localhost:11434/api/tags")
+            let client = match self.client() {
+                Ok(client) => client,
+                Err(_) => return false,
+            };
+            client.get("http://localhost:11434/api/tags")
                 .send()
                 .await
                 .map(|r| r.status().is_success())
@@ -719,7 +737,11 @@ impl AIEngine {
     /// List available Ollama models
     pub fn list_ollama_models(&self) -> Vec<String> {
         self.runtime.block_on(async {
-            let response = self.client.get("http://localhost:11434/api/tags")
+            let client = match self.client() {
+                Ok(client) => client,
+                Err(_) => return Vec::new(),
+            };
+            let response = client.get("http://localhost:11434/api/tags")
                 .send()
                 .await;
 
@@ -0,0 +1,123 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Bookmark export to Netscape bookmark HTML, which every browser imports
+
+use std::fs;
+use std::path::Path;
+
+use super::{Bookmark, BookmarkFolder, BookmarkManager, BookmarkNode, OTHER_FOLDER, TOOLBAR_FOLDER};
+
+const HEADER: &str = "<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<!-- This is an automatically generated file.
+     It will be read and overwritten.
+     DO NOT EDIT! -->
+<META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+";
+
+impl BookmarkManager {
+    /// All bookmarks as Netscape bookmark HTML, in folder order. The
+    /// toolbar and "other" folders are flagged so other browsers file them
+    /// in their own.
+    pub fn export_html(&self) -> String {
+        let mut html = String::from(HEADER);
+        html.push_str("<DL><p>\n");
+        self.write_children(&mut html, None, 1);
+        html.push_str("</DL><p>\n");
+        html
+    }
+
+    pub fn export_file(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.export_html())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    fn write_children(&self, html: &mut String, folder_id: Option<&str>, depth: usize) {
+        for node in self.children(folder_id) {
+            match node {
+                BookmarkNode::Folder(folder) => self.write_folder(html, folder, depth),
+                BookmarkNode::Bookmark(bookmark) => write_bookmark(html, bookmark, depth),
+            }
+        }
+    }
+
+    fn write_folder(&self, html: &mut String, folder: &BookmarkFolder, depth: usize) {
+        let indent = "    ".repeat(depth);
+        let flag = match folder.id.as_str() {
+            TOOLBAR_FOLDER => " PERSONAL_TOOLBAR_FOLDER=\"true\"",
+            OTHER_FOLDER => " UNFILED_BOOKMARKS_FOLDER=\"true\"",
+            _ => "",
+        };
+        html.push_str(&format!(
+            "{}<DT><H3 ADD_DATE=\"{}\"{}>{}</H3>\n{}<DL><p>\n",
+            indent,
+            folder.created_at.timestamp(),
+            flag,
+            escape(&folder.name),
+            indent
+        ));
+        self.write_children(html, Some(&folder.id), depth + 1);
+        html.push_str(&format!("{}</DL><p>\n", indent));
+    }
+}
+
+fn write_bookmark(html: &mut String, bookmark: &Bookmark, depth: usize) {
+    let tags = if bookmark.tags.is_empty() {
+        String::new()
+    } else {
+        format!(" TAGS=\"{}\"", escape(&bookmark.tags.join(",")))
+    };
+    html.push_str(&format!(
+        "{}<DT><A HREF=\"{}\" ADD_DATE=\"{}\"{}>{}</A>\n",
+        "    ".repeat(depth),
+        escape(&bookmark.url),
+        bookmark.created_at.timestamp(),
+        tags,
+        escape(&bookmark.title)
+    ));
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{import, BookmarkStore};
+    use super::*;
+
+    #[test]
+    fn export_round_trips_and_skips_duplicates() {
+        let mut source = BookmarkManager { store: BookmarkStore::default(), file_path: None };
+        let tree = import::parse_netscape(
+            r#"<DL><p>
+                <DT><H3 PERSONAL_TOOLBAR_FOLDER="true">Toolbar</H3>
+                <DL><p><DT><A HREF="https://news.example/" TAGS="news">News &lt;daily&gt;</A></DL><p>
+                <DT><H3>Research</H3>
+                <DL><p><DT><A HREF="https://arxiv.org/">arXiv</A></DL><p>
+            </DL>"#,
+        );
+        let summary = source.import_tree(&tree);
+        assert_eq!((summary.bookmarks, summary.folders), (2, 1));
+
+        let html = source.export_html();
+        let mut target = BookmarkManager { store: BookmarkStore::default(), file_path: None };
+        let summary = target.import_tree(&import::parse(&html).unwrap());
+        assert_eq!((summary.bookmarks, summary.folders, summary.duplicates), (2, 1, 0));
+
+        let news = target.get_bookmarks_in_folder("toolbar");
+        assert_eq!(news.len(), 1);
+        assert_eq!(news[0].title, "News <daily>");
+        assert_eq!(news[0].tags, ["news"]);
+
+        // Same pages under other spellings are duplicates
+        let again = import::parse_netscape(r#"<DL><p><DT><A HREF="https://www.NEWS.example">News</A><DT><A HREF="https://arxiv.org">arXiv</A></DL>"#);
+        assert_eq!(target.import_tree(&again).duplicates, 2);
+    }
+}
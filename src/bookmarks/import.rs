@@ -0,0 +1,523 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Bookmark import — Netscape bookmark HTML, Firefox JSON backups and
+//! Chromium `Bookmarks` files
+//!
+//! Each format is parsed into an [`ImportedFolder`] tree first. The browsers'
+//! own toolbar folders land in our `toolbar` folder, everything else under
+//! `other`; folders are matched by name so importing twice doesn't nest a
+//! second copy, and bookmarks whose normalized URL is already stored are
+//! skipped, keeping the union of their tags.
+
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::{normalize_url, Bookmark, BookmarkManager};
+
+/// Seconds between 1601-01-01, Chromium's epoch, and 1970-01-01
+const CHROMIUM_EPOCH_OFFSET: i64 = 11_644_473_600;
+
+/// Bookmark file formats we can read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookmarkFormat {
+    NetscapeHtml,
+    FirefoxJson,
+    Chromium,
+}
+
+impl BookmarkFormat {
+    /// Guess the format from a file's content
+    pub fn detect(content: &str) -> Option<Self> {
+        let start = content.trim_start();
+        if start.starts_with('{') {
+            let value: Value = serde_json::from_str(start).ok()?;
+            if value.get("roots").is_some() {
+                Some(BookmarkFormat::Chromium)
+            } else if value.get("type").and_then(Value::as_str) == Some("text/x-moz-place-container") {
+                Some(BookmarkFormat::FirefoxJson)
+            } else {
+                None
+            }
+        } else if start.to_ascii_uppercase().contains("NETSCAPE-BOOKMARK-FILE") || start.contains("<DL") || start.contains("<dl") {
+            Some(BookmarkFormat::NetscapeHtml)
+        } else {
+            None
+        }
+    }
+}
+
+/// A bookmark read from another browser's file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedBookmark {
+    pub title: String,
+    pub url: String,
+    pub tags: Vec<String>,
+    pub added: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportedItem {
+    Folder(ImportedFolder),
+    Bookmark(ImportedBookmark),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedFolder {
+    pub title: String,
+    pub children: Vec<ImportedItem>,
+    pub added: Option<DateTime<Utc>>,
+    /// Set on a browser's own toolbar or "other" folder: the id of the
+    /// folder of ours its contents belong in
+    pub root: Option<&'static str>,
+}
+
+/// What an import did
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub bookmarks: usize,
+    pub folders: usize,
+    pub duplicates: usize,
+}
+
+/// Parse a bookmark file in any supported format
+pub fn parse(content: &str) -> Result<ImportedFolder, String> {
+    match BookmarkFormat::detect(content) {
+        Some(BookmarkFormat::NetscapeHtml) => Ok(parse_netscape(content)),
+        Some(BookmarkFormat::FirefoxJson) => parse_firefox(content),
+        Some(BookmarkFormat::Chromium) => parse_chromium(content),
+        None => Err("Not a bookmark file Marshall can read".to_string()),
+    }
+}
+
+/// Netscape bookmark HTML, as every browser exports it. Folders are
+/// `<DT><H3>` headings followed by a `<DL>` list; bookmarks are `<DT><A>`.
+pub fn parse_netscape(html: &str) -> ImportedFolder {
+    let mut stack = vec![ImportedFolder::default()];
+    // A heading waiting for the list that holds its contents
+    let mut heading: Option<ImportedFolder> = None;
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let end = match rest.find('>') {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+        let name = tag.split_whitespace().next().unwrap_or("").to_ascii_uppercase();
+
+        match name.as_str() {
+            "H3" => {
+                let (text, after) = element_text(rest, "</H3");
+                rest = after;
+                heading = Some(ImportedFolder {
+                    title: text,
+                    added: attribute(tag, "ADD_DATE").and_then(|s| unix_seconds(&s)),
+                    root: if attribute(tag, "PERSONAL_TOOLBAR_FOLDER").is_some() {
+                        Some("toolbar")
+                    } else if attribute(tag, "UNFILED_BOOKMARKS_FOLDER").is_some() {
+                        Some("other")
+                    } else {
+                        None
+                    },
+                    ..Default::default()
+                });
+            }
+            "DL" => stack.push(heading.take().unwrap_or_default()),
+            "/DL" if stack.len() > 1 => {
+                let folder = stack.pop().unwrap_or_default();
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(ImportedItem::Folder(folder));
+                }
+            }
+            "A" => {
+                let (text, after) = element_text(rest, "</A");
+                rest = after;
+                let url = match attribute(tag, "HREF") {
+                    Some(url) => url,
+                    None => continue,
+                };
+                let bookmark = ImportedBookmark {
+                    title: if text.is_empty() { url.clone() } else { text },
+                    tags: attribute(tag, "TAGS").map(|t| split_tags(&t)).unwrap_or_default(),
+                    added: attribute(tag, "ADD_DATE").and_then(|s| unix_seconds(&s)),
+                    url,
+                };
+                if let Some(folder) = stack.last_mut() {
+                    folder.children.push(ImportedItem::Bookmark(bookmark));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Unclosed lists still count
+    while stack.len() > 1 {
+        let folder = stack.pop().unwrap_or_default();
+        if let Some(parent) = stack.last_mut() {
+            parent.children.push(ImportedItem::Folder(folder));
+        }
+    }
+    let mut root = stack.pop().unwrap_or_default();
+    // The document's own top-level list is the root, not a folder in it
+    if matches!(root.children.as_slice(), [ImportedItem::Folder(only)] if only.title.is_empty() && only.root.is_none()) {
+        if let Some(ImportedItem::Folder(only)) = root.children.pop() {
+            root.children = only.children;
+        }
+    }
+    root
+}
+
+/// A Firefox `bookmarks-*.json` backup
+pub fn parse_firefox(json: &str) -> Result<ImportedFolder, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| format!("Invalid Firefox backup: {}", e))?;
+
+    fn item(node: &Value) -> Option<ImportedItem> {
+        let added = node.get("dateAdded").and_then(Value::as_i64).and_then(|us| Utc.timestamp_opt(us / 1_000_000, 0).single());
+        let title = node.get("title").and_then(Value::as_str).unwrap_or("").to_string();
+        match node.get("type").and_then(Value::as_str)? {
+            "text/x-moz-place" => {
+                let url = node.get("uri").and_then(Value::as_str)?.to_string();
+                // Smart bookmarks and bookmarklets aren't pages
+                if url.starts_with("place:") || url.starts_with("javascript:") {
+                    return None;
+                }
+                Some(ImportedItem::Bookmark(ImportedBookmark {
+                    title: if title.is_empty() { url.clone() } else { title },
+                    tags: node.get("tags").and_then(Value::as_str).map(split_tags).unwrap_or_default(),
+                    added,
+                    url,
+                }))
+            }
+            "text/x-moz-place-container" => {
+                let root = match node.get("guid").and_then(Value::as_str) {
+                    Some("toolbar_____") => Some("toolbar"),
+                    Some("unfiled_____") | Some("mobile______") => Some("other"),
+                    _ => None,
+                };
+                let title = match node.get("guid").and_then(Value::as_str) {
+                    Some("menu________") => "Bookmarks Menu".to_string(),
+                    _ => title,
+                };
+                Some(ImportedItem::Folder(ImportedFolder {
+                    title,
+                    children: children(node),
+                    added,
+                    root,
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    fn children(node: &Value) -> Vec<ImportedItem> {
+        node.get("children")
+            .and_then(Value::as_array)
+            .map(|children| children.iter().filter_map(item).collect())
+            .unwrap_or_default()
+    }
+
+    Ok(ImportedFolder {
+        children: children(&value),
+        ..Default::default()
+    })
+}
+
+/// Chromium's (and Chrome's, Brave's, Edge's) `Bookmarks` file
+pub fn parse_chromium(json: &str) -> Result<ImportedFolder, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| format!("Invalid Chromium bookmarks: {}", e))?;
+    let roots = value.get("roots").ok_or("Chromium bookmarks have no roots")?;
+
+    fn item(node: &Value) -> Option<ImportedItem> {
+        let added = node
+            .get("date_added")
+            .and_then(Value::as_str)
+            .and_then(|us| us.parse::<i64>().ok())
+            .and_then(|us| Utc.timestamp_opt(us / 1_000_000 - CHROMIUM_EPOCH_OFFSET, 0).single());
+        let title = node.get("name").and_then(Value::as_str).unwrap_or("").to_string();
+        match node.get("type").and_then(Value::as_str)? {
+            "url" => {
+                let url = node.get("url").and_then(Value::as_str)?.to_string();
+                Some(ImportedItem::Bookmark(ImportedBookmark {
+                    title: if title.is_empty() { url.clone() } else { title },
+                    tags: Vec::new(),
+                    added,
+                    url,
+                }))
+            }
+            "folder" => Some(ImportedItem::Folder(ImportedFolder {
+                title,
+                children: node
+                    .get("children")
+                    .and_then(Value::as_array)
+                    .map(|children| children.iter().filter_map(item).collect())
+                    .unwrap_or_default(),
+                added,
+                root: None,
+            })),
+            _ => None,
+        }
+    }
+
+    let mut folder = ImportedFolder::default();
+    for (key, root) in [("bookmark_bar", Some("toolbar")), ("other", Some("other")), ("synced", None)] {
+        if let Some(ImportedItem::Folder(mut imported)) = roots.get(key).and_then(item) {
+            imported.root = root;
+            folder.children.push(ImportedItem::Folder(imported));
+        }
+    }
+    Ok(folder)
+}
+
+impl BookmarkManager {
+    /// Import a bookmark file from another browser
+    pub fn import_file(&mut self, path: &Path) -> Result<ImportSummary, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let tree = parse(&content)?;
+        let summary = self.import_tree(&tree);
+        self.save().map_err(|e| format!("Failed to save bookmarks: {}", e))?;
+        Ok(summary)
+    }
+
+    /// Merge an imported tree into the store; top-level items go under
+    /// `other`
+    pub fn import_tree(&mut self, tree: &ImportedFolder) -> ImportSummary {
+        let mut summary = ImportSummary::default();
+        let target = tree.root.unwrap_or("other").to_string();
+        self.import_children(&tree.children, &target, &mut summary);
+        summary
+    }
+
+    fn import_children(&mut self, items: &[ImportedItem], folder_id: &str, summary: &mut ImportSummary) {
+        for item in items {
+            match item {
+                ImportedItem::Folder(folder) => {
+                    let id = match folder.root {
+                        Some(root) if self.store.folders.contains_key(root) => root.to_string(),
+                        _ => {
+                            let (id, created) = self.find_or_create_folder(&folder.title, folder_id, folder.added);
+                            if created {
+                                summary.folders += 1;
+                            }
+                            id
+                        }
+                    };
+                    self.import_children(&folder.children, &id, summary);
+                }
+                ImportedItem::Bookmark(bookmark) => {
+                    if self.import_bookmark(bookmark, folder_id) {
+                        summary.bookmarks += 1;
+                    } else {
+                        summary.duplicates += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add a bookmark unless its URL is already bookmarked, in which case
+    /// the tags are merged into the existing one
+    fn import_bookmark(&mut self, imported: &ImportedBookmark, folder_id: &str) -> bool {
+        let normalized = normalize_url(&imported.url);
+        if let Some(existing) = self.store.bookmarks.values_mut().find(|b| normalize_url(&b.url) == normalized) {
+            for tag in &imported.tags {
+                if !existing.tags.contains(tag) {
+                    existing.tags.push(tag.clone());
+                }
+            }
+            return false;
+        }
+
+        // Inserted directly; add_bookmark would save the file every time
+        let bookmark = Bookmark {
+            id: Uuid::new_v4().to_string(),
+            title: imported.title.clone(),
+            url: imported.url.clone(),
+            favicon: None,
+            folder_id: Some(folder_id.to_string()),
+            created_at: imported.added.unwrap_or_else(Utc::now),
+            tags: imported.tags.clone(),
+            position: self.next_position(Some(folder_id)),
+        };
+        self.store.bookmarks.insert(bookmark.id.clone(), bookmark);
+        true
+    }
+}
+
+/// Text of an element up to its closing tag, with entities decoded
+fn element_text<'a>(html: &'a str, close: &str) -> (String, &'a str) {
+    let upper = html.to_ascii_uppercase();
+    match upper.find(close) {
+        Some(end) => (decode_entities(html[..end].trim()), &html[end..]),
+        None => (String::new(), html),
+    }
+}
+
+/// Value of `name="..."` in a start tag
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let upper = tag.to_ascii_uppercase();
+    let needle = format!("{}=", name);
+    let mut from = 0;
+    while let Some(pos) = upper[from..].find(&needle) {
+        let pos = from + pos;
+        // Must be a whole attribute name, not the tail of another
+        if pos == 0 || tag.as_bytes()[pos - 1].is_ascii_whitespace() {
+            let value = &tag[pos + needle.len()..];
+            let value = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or(""),
+                _ => value.split_whitespace().next().unwrap_or(""),
+            };
+            return Some(decode_entities(value));
+        }
+        from = pos + needle.len();
+    }
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let end = match rest.find(';') {
+            Some(end) if end <= 10 => end,
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                u32::from_str_radix(&entity[2..], 16).ok().and_then(char::from_u32)
+            }
+            _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn unix_seconds(value: &str) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(value.trim().parse().ok()?, 0).single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETSCAPE: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks Menu</H1>
+<DL><p>
+    <DT><H3 ADD_DATE="1700000000" PERSONAL_TOOLBAR_FOLDER="true">Bookmarks Toolbar</H3>
+    <DL><p>
+        <DT><A HREF="https://www.rust-lang.org/" ADD_DATE="1700000001" TAGS="rust,lang">Rust &amp; Cargo</A>
+    </DL><p>
+    <DT><H3>Work</H3>
+    <DL><p>
+        <DT><H3>Tools</H3>
+        <DL><p>
+            <DT><A href="https://github.com">GitHub</A>
+        </DL><p>
+    </DL><p>
+    <DT><A HREF="https://example.com/?a=1&amp;b=2">Example</A>
+</DL>"#;
+
+    #[test]
+    fn netscape_keeps_hierarchy_and_tags() {
+        let tree = parse(NETSCAPE).unwrap();
+        assert_eq!(tree.children.len(), 3);
+
+        let toolbar = match &tree.children[0] {
+            ImportedItem::Folder(folder) => folder,
+            other => panic!("expected folder, got {:?}", other),
+        };
+        assert_eq!(toolbar.root, Some("toolbar"));
+        match &toolbar.children[0] {
+            ImportedItem::Bookmark(b) => {
+                assert_eq!(b.title, "Rust & Cargo");
+                assert_eq!(b.tags, ["rust", "lang"]);
+                assert_eq!(b.added.map(|d| d.timestamp()), Some(1_700_000_001));
+            }
+            other => panic!("expected bookmark, got {:?}", other),
+        }
+        match &tree.children[2] {
+            ImportedItem::Bookmark(b) => assert_eq!(b.url, "https://example.com/?a=1&b=2"),
+            other => panic!("expected bookmark, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn firefox_and_chromium_roots_map_to_our_folders() {
+        let firefox = r#"{"guid":"root________","type":"text/x-moz-place-container","children":[
+            {"guid":"toolbar_____","title":"toolbar","type":"text/x-moz-place-container","children":[
+                {"title":"MDN","type":"text/x-moz-place","uri":"https://developer.mozilla.org/","tags":"docs,web","dateAdded":1700000000000000},
+                {"title":"Recent","type":"text/x-moz-place","uri":"place:sort=8"}
+            ]}
+        ]}"#;
+        assert_eq!(BookmarkFormat::detect(firefox), Some(BookmarkFormat::FirefoxJson));
+        let tree = parse(firefox).unwrap();
+        match &tree.children[0] {
+            ImportedItem::Folder(f) => {
+                assert_eq!(f.root, Some("toolbar"));
+                assert_eq!(f.children.len(), 1);
+            }
+            other => panic!("expected folder, got {:?}", other),
+        }
+
+        let chromium = r#"{"roots":{
+            "bookmark_bar":{"type":"folder","name":"Bookmarks bar","children":[
+                {"type":"url","name":"Docs","url":"https://docs.rs/","date_added":"13345000000000000"}
+            ]},
+            "other":{"type":"folder","name":"Other bookmarks","children":[]}
+        },"version":1}"#;
+        assert_eq!(BookmarkFormat::detect(chromium), Some(BookmarkFormat::Chromium));
+        let tree = parse(chromium).unwrap();
+        match &tree.children[0] {
+            ImportedItem::Folder(f) => {
+                assert_eq!(f.root, Some("toolbar"));
+                match &f.children[0] {
+                    ImportedItem::Bookmark(b) => assert_eq!(b.added.map(|d| d.timestamp()), Some(1_700_526_400)),
+                    other => panic!("expected bookmark, got {:?}", other),
+                }
+            }
+            other => panic!("expected folder, got {:?}", other),
+        }
+    }
+}
@@ -3,16 +3,37 @@
 // https://github.com/bad-antics/marshall
 
 //! Bookmark management
+//!
+//! Folders form a tree under the `toolbar` and `other` roots. Folders and
+//! bookmarks in the same folder share one `position` order, which is what
+//! the bookmarks bar and the HTML export show.
+
+mod export;
+mod import;
+
+pub use import::{BookmarkFormat, ImportSummary, ImportedBookmark, ImportedFolder, ImportedItem};
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use tracing::warn;
 use uuid::Uuid;
 
 use crate::config::Config;
 
+static STORE: Lazy<RwLock<BookmarkManager>> = Lazy::new(|| RwLock::new(BookmarkManager::open()));
+
+/// The folder shown as the bookmarks bar
+pub const TOOLBAR_FOLDER: &str = "toolbar";
+/// Where bookmarks go when no folder is given
+pub const OTHER_FOLDER: &str = "other";
+/// Folders that always exist and can't be renamed, moved or deleted
+const ROOT_FOLDERS: [&str; 2] = [TOOLBAR_FOLDER, OTHER_FOLDER];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bookmark {
     pub id: String,
@@ -22,6 +43,10 @@ pub struct Bookmark {
     pub folder_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub tags: Vec<String>,
+    /// Order within the folder; stores from before ordering existed fall
+    /// back to creation time
+    #[serde(default)]
+    pub position: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +55,31 @@ pub struct BookmarkFolder {
     pub name: String,
     pub parent_id: Option<String>,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub position: u32,
+}
+
+/// A folder's child, in the folder's order
+#[derive(Debug, Clone, Copy)]
+pub enum BookmarkNode<'a> {
+    Folder(&'a BookmarkFolder),
+    Bookmark(&'a Bookmark),
+}
+
+impl BookmarkNode<'_> {
+    pub fn id(&self) -> &str {
+        match self {
+            BookmarkNode::Folder(folder) => &folder.id,
+            BookmarkNode::Bookmark(bookmark) => &bookmark.id,
+        }
+    }
+
+    fn sort_key(&self) -> (u32, DateTime<Utc>) {
+        match self {
+            BookmarkNode::Folder(folder) => (folder.position, folder.created_at),
+            BookmarkNode::Bookmark(bookmark) => (bookmark.position, bookmark.created_at),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,21 +94,23 @@ impl Default for BookmarkStore {
         
         // Create default folders
         let toolbar = BookmarkFolder {
-            id: "toolbar".to_string(),
+            id: TOOLBAR_FOLDER.to_string(),
             name: "Bookmarks Toolbar".to_string(),
             parent_id: None,
             created_at: Utc::now(),
+            position: 0,
         };
         
         let other = BookmarkFolder {
-            id: "other".to_string(),
+            id: OTHER_FOLDER.to_string(),
             name: "Other Bookmarks".to_string(),
             parent_id: None,
             created_at: Utc::now(),
+            position: 1,
         };
         
-        folders.insert("toolbar".to_string(), toolbar);
-        folders.insert("other".to_string(), other);
+        folders.insert(TOOLBAR_FOLDER.to_string(), toolbar);
+        folders.insert(OTHER_FOLDER.to_string(), other);
         
         Self {
             bookmarks: HashMap::new(),
@@ -69,10 +121,27 @@ impl Default for BookmarkStore {
 
 pub struct BookmarkManager {
     store: BookmarkStore,
-    file_path: PathBuf,
+    /// None when bookmarks.json couldn't be read; changes are then kept for
+    /// the session rather than overwriting the file
+    file_path: Option<PathBuf>,
 }
 
 impl BookmarkManager {
+    /// The bookmarks shared by every window
+    pub fn global() -> &'static RwLock<BookmarkManager> {
+        &STORE
+    }
+
+    fn open() -> Self {
+        Self::new().unwrap_or_else(|e| {
+            warn!("Bookmarks will not persist: {}", e);
+            Self {
+                store: BookmarkStore::default(),
+                file_path: None,
+            }
+        })
+    }
+
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
         let file_path = Config::data_dir()?.join("bookmarks.json");
         
@@ -83,7 +152,7 @@ impl BookmarkManager {
             BookmarkStore::default()
         };
         
-        Ok(Self { store, file_path })
+        Ok(Self { store, file_path: Some(file_path) })
     }
 
     pub fn add_bookmark(&mut self, title: &str, url: &str, folder_id: Option<&str>) -> String {
@@ -95,6 +164,7 @@ impl BookmarkManager {
             folder_id: folder_id.map(|s| s.to_string()),
             created_at: Utc::now(),
             tags: Vec::new(),
+            position: self.next_position(folder_id),
         };
         
         let id = bookmark.id.clone();
@@ -119,12 +189,194 @@ impl BookmarkManager {
         self.store.bookmarks.values().collect()
     }
 
+    /// Bookmarks directly in `folder_id`, in order
     pub fn get_bookmarks_in_folder(&self, folder_id: &str) -> Vec<&Bookmark> {
-        self.store.bookmarks.values()
-            .filter(|b| b.folder_id.as_deref() == Some(folder_id))
+        self.children(Some(folder_id))
+            .into_iter()
+            .filter_map(|node| match node {
+                BookmarkNode::Bookmark(bookmark) => Some(bookmark),
+                BookmarkNode::Folder(_) => None,
+            })
             .collect()
     }
 
+    /// Rename a bookmark
+    pub fn rename_bookmark(&mut self, id: &str, title: &str) -> Result<(), String> {
+        let bookmark = self.store.bookmarks.get_mut(id).ok_or_else(|| format!("No bookmark {}", id))?;
+        bookmark.title = title.trim().to_string();
+        self.save().map_err(|e| format!("Failed to save bookmarks: {}", e))
+    }
+
+    /// Move a bookmark to `index` among the children of `folder_id`
+    pub fn move_bookmark(&mut self, id: &str, folder_id: &str, index: usize) -> Result<(), String> {
+        if !self.store.folders.contains_key(folder_id) {
+            return Err(format!("No folder {}", folder_id));
+        }
+        let bookmark = self.store.bookmarks.get_mut(id).ok_or_else(|| format!("No bookmark {}", id))?;
+        bookmark.folder_id = Some(folder_id.to_string());
+        self.place(id, Some(folder_id), index);
+        self.save().map_err(|e| format!("Failed to save bookmarks: {}", e))
+    }
+
+    pub fn get_folder(&self, id: &str) -> Option<&BookmarkFolder> {
+        self.store.folders.get(id)
+    }
+
+    /// Folders and bookmarks directly in `folder_id` (the roots for None),
+    /// in order
+    pub fn children(&self, folder_id: Option<&str>) -> Vec<BookmarkNode<'_>> {
+        let folders = self
+            .store
+            .folders
+            .values()
+            .filter(|f| f.parent_id.as_deref() == folder_id)
+            .map(BookmarkNode::Folder);
+        let bookmarks = self
+            .store
+            .bookmarks
+            .values()
+            .filter(|b| b.folder_id.as_deref() == folder_id)
+            .map(BookmarkNode::Bookmark);
+        let mut nodes: Vec<BookmarkNode> = folders.chain(bookmarks).collect();
+        nodes.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()).then_with(|| a.id().cmp(b.id())));
+        nodes
+    }
+
+    /// Create a folder called `name` at the end of `parent_id`
+    pub fn create_folder(&mut self, name: &str, parent_id: &str) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Folder name is empty".to_string());
+        }
+        if !self.store.folders.contains_key(parent_id) {
+            return Err(format!("No folder {}", parent_id));
+        }
+        let folder = BookmarkFolder {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            parent_id: Some(parent_id.to_string()),
+            created_at: Utc::now(),
+            position: self.next_position(Some(parent_id)),
+        };
+        let id = folder.id.clone();
+        self.store.folders.insert(id.clone(), folder);
+        self.save().map_err(|e| format!("Failed to save bookmarks: {}", e))?;
+        Ok(id)
+    }
+
+    pub fn rename_folder(&mut self, id: &str, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Folder name is empty".to_string());
+        }
+        if ROOT_FOLDERS.contains(&id) {
+            return Err("Built-in folders can't be renamed".to_string());
+        }
+        let folder = self.store.folders.get_mut(id).ok_or_else(|| format!("No folder {}", id))?;
+        folder.name = name.to_string();
+        self.save().map_err(|e| format!("Failed to save bookmarks: {}", e))
+    }
+
+    /// Move a folder, with everything in it, to `index` among the children
+    /// of `parent_id`. A folder can't go inside itself.
+    pub fn move_folder(&mut self, id: &str, parent_id: &str, index: usize) -> Result<(), String> {
+        if ROOT_FOLDERS.contains(&id) {
+            return Err("Built-in folders can't be moved".to_string());
+        }
+        if !self.store.folders.contains_key(id) {
+            return Err(format!("No folder {}", id));
+        }
+        if !self.store.folders.contains_key(parent_id) {
+            return Err(format!("No folder {}", parent_id));
+        }
+        if self.is_within(parent_id, id) {
+            return Err("A folder can't be moved into itself".to_string());
+        }
+        if let Some(folder) = self.store.folders.get_mut(id) {
+            folder.parent_id = Some(parent_id.to_string());
+        }
+        self.place(id, Some(parent_id), index);
+        self.save().map_err(|e| format!("Failed to save bookmarks: {}", e))
+    }
+
+    /// Delete a folder with its subfolders and bookmarks; returns how many
+    /// bookmarks went with it
+    pub fn delete_folder(&mut self, id: &str) -> Result<usize, String> {
+        if ROOT_FOLDERS.contains(&id) {
+            return Err("Built-in folders can't be deleted".to_string());
+        }
+        if !self.store.folders.contains_key(id) {
+            return Err(format!("No folder {}", id));
+        }
+        let doomed: Vec<String> = self
+            .store
+            .folders
+            .keys()
+            .filter(|f| self.is_within(f, id))
+            .cloned()
+            .collect();
+        for folder in &doomed {
+            self.store.folders.remove(folder);
+        }
+        let before = self.store.bookmarks.len();
+        self.store
+            .bookmarks
+            .retain(|_, b| !b.folder_id.as_ref().is_some_and(|f| doomed.contains(f)));
+        let removed = before - self.store.bookmarks.len();
+        self.save().map_err(|e| format!("Failed to save bookmarks: {}", e))?;
+        Ok(removed)
+    }
+
+    /// Whether `folder_id` is `ancestor` or somewhere inside it. Stops
+    /// after as many steps as there are folders, in case the file on disk
+    /// has a loop.
+    fn is_within(&self, folder_id: &str, ancestor: &str) -> bool {
+        let mut current = Some(folder_id);
+        for _ in 0..=self.store.folders.len() {
+            match current {
+                Some(id) if id == ancestor => return true,
+                Some(id) => current = self.store.folders.get(id).and_then(|f| f.parent_id.as_deref()),
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Position after the last child of `folder_id`
+    fn next_position(&self, folder_id: Option<&str>) -> u32 {
+        self.children(folder_id)
+            .last()
+            .map(|node| node.sort_key().0 + 1)
+            .unwrap_or(0)
+    }
+
+    /// Put item `id`, already moved to `folder_id`, at `index` among its
+    /// children and number them all from 0
+    fn place(&mut self, id: &str, folder_id: Option<&str>, index: usize) {
+        let mut order: Vec<String> = self
+            .children(folder_id)
+            .iter()
+            .map(|node| node.id().to_string())
+            .filter(|child| child != id)
+            .collect();
+        order.insert(index.min(order.len()), id.to_string());
+
+        for (position, child) in order.iter().enumerate() {
+            let position = position as u32;
+            if let Some(folder) = self.store.folders.get_mut(child) {
+                folder.position = position;
+            } else if let Some(bookmark) = self.store.bookmarks.get_mut(child) {
+                bookmark.position = position;
+            }
+        }
+    }
+
+    /// The bookmark for `url`, however it is spelled
+    pub fn find_by_url(&self, url: &str) -> Option<&Bookmark> {
+        let normalized = normalize_url(url);
+        self.store.bookmarks.values().find(|b| normalize_url(&b.url) == normalized)
+    }
+
     pub fn search_bookmarks(&self, query: &str) -> Vec<&Bookmark> {
         let query = query.to_lowercase();
         self.store.bookmarks.values()
@@ -136,12 +388,112 @@ impl BookmarkManager {
             .collect()
     }
 
+    /// Id of the folder called `name` in `parent`, creating it if needed;
+    /// true when it was created
+    fn find_or_create_folder(&mut self, name: &str, parent: &str, created_at: Option<DateTime<Utc>>) -> (String, bool) {
+        let existing = self
+            .store
+            .folders
+            .values()
+            .find(|f| f.parent_id.as_deref() == Some(parent) && f.name == name);
+        if let Some(folder) = existing {
+            return (folder.id.clone(), false);
+        }
+
+        let folder = BookmarkFolder {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            parent_id: Some(parent.to_string()),
+            created_at: created_at.unwrap_or_else(Utc::now),
+            position: self.next_position(Some(parent)),
+        };
+        let id = folder.id.clone();
+        self.store.folders.insert(id.clone(), folder);
+        (id, true)
+    }
+
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(parent) = self.file_path.parent() {
+        let file_path = match &self.file_path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(parent) = file_path.parent() {
             fs::create_dir_all(parent)?;
         }
         let content = serde_json::to_string_pretty(&self.store)?;
-        fs::write(&self.file_path, content)?;
+        fs::write(file_path, content)?;
         Ok(())
     }
 }
+
+/// URL in the form bookmarks are compared by: host lower-cased, default
+/// port, empty query and fragment, trailing slash and `www.` dropped
+pub fn normalize_url(url: &str) -> String {
+    let mut parsed = match url::Url::parse(url.trim()) {
+        Ok(parsed) => parsed,
+        Err(_) => return url.trim().to_lowercase(),
+    };
+    if parsed.fragment() == Some("") {
+        parsed.set_fragment(None);
+    }
+    if parsed.query() == Some("") {
+        parsed.set_query(None);
+    }
+    let host = parsed.host_str().unwrap_or("").trim_start_matches("www.").to_string();
+    let port = parsed.port().map(|p| format!(":{}", p)).unwrap_or_default();
+    let mut rest = parsed.path().trim_end_matches('/').to_string();
+    if let Some(query) = parsed.query() {
+        rest.push('?');
+        rest.push_str(query);
+    }
+    if let Some(fragment) = parsed.fragment() {
+        rest.push('#');
+        rest.push_str(fragment);
+    }
+    format!("{}://{}{}{}", parsed.scheme(), host, port, rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> BookmarkManager {
+        BookmarkManager { store: BookmarkStore::default(), file_path: None }
+    }
+
+    fn order(manager: &BookmarkManager, folder: &str) -> Vec<String> {
+        manager.children(Some(folder)).iter().map(|node| node.id().to_string()).collect()
+    }
+
+    #[test]
+    fn folders_move_without_cycles_and_keep_order() {
+        let mut bookmarks = manager();
+        let news = bookmarks.create_folder("News", TOOLBAR_FOLDER).unwrap();
+        let local = bookmarks.create_folder("Local", &news).unwrap();
+        let rust = bookmarks.add_bookmark("Rust", "https://rust-lang.org/", Some(TOOLBAR_FOLDER));
+        let paper = bookmarks.add_bookmark("Paper", "https://paper.example/", Some(&local));
+        assert_eq!(order(&bookmarks, TOOLBAR_FOLDER), [news.clone(), rust.clone()]);
+
+        // Reorder, and move between folders
+        bookmarks.move_bookmark(&rust, TOOLBAR_FOLDER, 0).unwrap();
+        assert_eq!(order(&bookmarks, TOOLBAR_FOLDER), [rust.clone(), news.clone()]);
+        bookmarks.move_folder(&local, TOOLBAR_FOLDER, 1).unwrap();
+        assert_eq!(order(&bookmarks, TOOLBAR_FOLDER), [rust.clone(), local.clone(), news.clone()]);
+
+        // No folder inside itself or its own subfolder; roots stay put
+        bookmarks.move_folder(&local, &news, 0).unwrap();
+        assert!(bookmarks.move_folder(&news, &local, 0).is_err());
+        assert!(bookmarks.move_folder(&news, &news, 0).is_err());
+        assert!(bookmarks.move_folder(TOOLBAR_FOLDER, OTHER_FOLDER, 0).is_err());
+        assert!(bookmarks.rename_folder(OTHER_FOLDER, "Misc").is_err());
+
+        bookmarks.rename_folder(&news, "Reading").unwrap();
+        assert_eq!(bookmarks.get_folder(&news).unwrap().name, "Reading");
+
+        // Deleting takes subfolders and their bookmarks along
+        assert_eq!(bookmarks.delete_folder(&news).unwrap(), 1);
+        assert!(bookmarks.get_folder(&local).is_none());
+        assert!(bookmarks.get_bookmark(&paper).is_none());
+        assert_eq!(order(&bookmarks, TOOLBAR_FOLDER), [rust]);
+    }
+}
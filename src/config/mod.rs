@@ -9,6 +9,9 @@ use std::fs;
 use std::path::PathBuf;
 use directories::ProjectDirs;
 
+use crate::adblock::BlockCategory;
+use crate::security::SecurityConfig;
+
 mod defaults;
 
 pub use defaults::*;
@@ -22,6 +25,9 @@ pub struct Config {
     pub appearance: AppearanceConfig,
     pub network: NetworkConfig,
     pub keybindings: KeybindingsConfig,
+    /// Page hardening flags
+    #[serde(default)]
+    pub security: SecurityConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +39,10 @@ pub struct GeneralConfig {
     pub enable_javascript: bool,
     pub enable_images: bool,
     pub enable_webgl: bool,
+    /// Ask the search engine for suggestions while typing in the URL bar.
+    /// Off by default: every keystroke is sent to the engine.
+    #[serde(default)]
+    pub search_suggestions: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +57,47 @@ pub struct PrivacyConfig {
     pub user_agent: Option<String>,
     pub spoof_timezone: bool,
     pub spoof_language: bool,
+    #[serde(default)]
+    pub block_categories: BlockCategories,
+}
+
+/// Which categories of the built-in blocklist are blocked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlockCategories {
+    pub ads: bool,
+    pub analytics: bool,
+    pub social: bool,
+    pub telemetry: bool,
+    pub fingerprinting: bool,
+}
+
+impl Default for BlockCategories {
+    fn default() -> Self {
+        Self {
+            ads: true,
+            analytics: true,
+            social: true,
+            telemetry: true,
+            fingerprinting: true,
+        }
+    }
+}
+
+impl BlockCategories {
+    /// The enabled categories
+    pub fn enabled(&self) -> Vec<BlockCategory> {
+        [
+            (BlockCategory::Ads, self.ads),
+            (BlockCategory::Analytics, self.analytics),
+            (BlockCategory::Social, self.social),
+            (BlockCategory::Telemetry, self.telemetry),
+            (BlockCategory::Fingerprinting, self.fingerprinting),
+        ]
+        .into_iter()
+        .filter_map(|(category, on)| on.then_some(category))
+        .collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,12 +123,20 @@ pub struct AppearanceConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkConfig {
     pub proxy: Option<ProxyConfig>,
+    /// Route everything through Tor; needs the `tor` feature
     pub tor_enabled: bool,
+    /// Tor's SOCKS port as `host:port`
+    #[serde(default = "default_tor_socks")]
+    pub tor_socks: String,
     pub dns_over_https: bool,
     pub doh_server: String,
     pub timeout_seconds: u64,
 }
 
+fn default_tor_socks() -> String {
+    "127.0.0.1:9050".to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProxyConfig {
     pub protocol: String,
@@ -100,6 +159,12 @@ pub struct KeybindingsConfig {
     pub zoom_reset: String,
     pub developer_tools: String,
     pub private_window: String,
+    #[serde(default = "default_bookmark_page")]
+    pub bookmark_page: String,
+}
+
+fn default_bookmark_page() -> String {
+    "<Ctrl>d".to_string()
 }
 
 impl Default for Config {
@@ -116,6 +181,7 @@ impl Default for Config {
                 enable_javascript: true,
                 enable_images: true,
                 enable_webgl: false, // Disabled for privacy
+                search_suggestions: false,
             },
             privacy: PrivacyConfig {
                 strict_mode: true,
@@ -128,6 +194,7 @@ impl Default for Config {
                 user_agent: None, // Use randomized UA
                 spoof_timezone: true,
                 spoof_language: false,
+                block_categories: BlockCategories::default(),
             },
             adblock: AdblockConfig {
                 enabled: true,
@@ -152,6 +219,7 @@ impl Default for Config {
             network: NetworkConfig {
                 proxy: None,
                 tor_enabled: false,
+                tor_socks: default_tor_socks(),
                 dns_over_https: true,
                 doh_server: "https://cloudflare-dns.com/dns-query".to_string(),
                 timeout_seconds: 30,
@@ -168,7 +236,9 @@ impl Default for Config {
                 zoom_reset: "<Ctrl>0".to_string(),
                 developer_tools: "F12".to_string(),
                 private_window: "<Ctrl><Shift>p".to_string(),
+                bookmark_page: default_bookmark_page(),
             },
+            security: SecurityConfig::default(),
         }
     }
 }
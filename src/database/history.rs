@@ -0,0 +1,419 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Browsing history — places, their visits, and frecency-ranked search
+//!
+//! A place is a URL with its title and visit count; every visit to it is a
+//! row in `visits` with its time, how it was reached and the page it was
+//! reached from. `places_fts` indexes URLs and titles for search. Frecency
+//! scores a place by how often and how recently it was visited, weighted by
+//! how: a typed URL counts for more than a followed link.
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, OptionalExtension, Result as SqlResult, Row, Transaction};
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+/// Tables, search index and the triggers keeping the index in step. `meta`
+/// records one-off work such as imports that must not run twice.
+pub(super) const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS places (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL DEFAULT '',
+        visit_count INTEGER NOT NULL DEFAULT 0,
+        last_visit TEXT,
+        frecency INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE INDEX IF NOT EXISTS places_frecency ON places (frecency DESC);
+    CREATE INDEX IF NOT EXISTS places_last_visit ON places (last_visit DESC);
+
+    CREATE TABLE IF NOT EXISTS visits (
+        id INTEGER PRIMARY KEY,
+        place_id INTEGER NOT NULL REFERENCES places (id),
+        visited_at TEXT NOT NULL,
+        transition TEXT NOT NULL,
+        referrer_id INTEGER REFERENCES places (id)
+    );
+    CREATE INDEX IF NOT EXISTS visits_place ON visits (place_id, visited_at DESC);
+
+    CREATE VIRTUAL TABLE IF NOT EXISTS places_fts USING fts5 (
+        url, title, content = 'places', content_rowid = 'id'
+    );
+    CREATE TRIGGER IF NOT EXISTS places_fts_insert AFTER INSERT ON places BEGIN
+        INSERT INTO places_fts (rowid, url, title) VALUES (new.id, new.url, new.title);
+    END;
+    CREATE TRIGGER IF NOT EXISTS places_fts_delete AFTER DELETE ON places BEGIN
+        INSERT INTO places_fts (places_fts, rowid, url, title) VALUES ('delete', old.id, old.url, old.title);
+    END;
+    CREATE TRIGGER IF NOT EXISTS places_fts_update AFTER UPDATE OF url, title ON places BEGIN
+        INSERT INTO places_fts (places_fts, rowid, url, title) VALUES ('delete', old.id, old.url, old.title);
+        INSERT INTO places_fts (rowid, url, title) VALUES (new.id, new.url, new.title);
+    END;
+";
+
+/// Visits sampled when computing a place's frecency
+const FRECENCY_SAMPLE: usize = 10;
+
+/// How a visit was reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Transition {
+    /// Followed a link or a script navigation
+    Link,
+    /// Typed into the URL bar
+    Typed,
+    /// Opened from a bookmark
+    Bookmark,
+    /// Submitted a form
+    Form,
+    Reload,
+    Redirect,
+}
+
+impl Transition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Transition::Link => "link",
+            Transition::Typed => "typed",
+            Transition::Bookmark => "bookmark",
+            Transition::Form => "form",
+            Transition::Reload => "reload",
+            Transition::Redirect => "redirect",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "link" => Some(Transition::Link),
+            "typed" => Some(Transition::Typed),
+            "bookmark" => Some(Transition::Bookmark),
+            "form" => Some(Transition::Form),
+            "reload" => Some(Transition::Reload),
+            "redirect" => Some(Transition::Redirect),
+            _ => None,
+        }
+    }
+
+    /// Percentage a visit of this kind contributes to frecency
+    fn bonus(&self) -> i64 {
+        match self {
+            Transition::Typed => 200,
+            Transition::Bookmark => 140,
+            Transition::Link | Transition::Form => 100,
+            Transition::Redirect => 25,
+            Transition::Reload => 0,
+        }
+    }
+}
+
+/// A visited URL
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub url: String,
+    pub title: String,
+    /// Time of the latest visit
+    pub visited_at: DateTime<Utc>,
+    pub visit_count: u32,
+    pub frecency: i64,
+}
+
+impl HistoryEntry {
+    fn from_row(row: &Row) -> SqlResult<Self> {
+        Ok(Self {
+            url: row.get(0)?,
+            title: row.get(1)?,
+            visited_at: row.get(2)?,
+            visit_count: row.get(3)?,
+            frecency: row.get(4)?,
+        })
+    }
+}
+
+const SELECT_PLACE: &str = "SELECT places.url, places.title, places.last_visit,
+    places.visit_count, places.frecency FROM places";
+
+/// Weight of a visit by age, in the buckets Firefox uses
+fn age_weight(age: Duration) -> i64 {
+    match age.num_days() {
+        d if d <= 4 => 100,
+        d if d <= 14 => 70,
+        d if d <= 31 => 50,
+        d if d <= 90 => 30,
+        _ => 10,
+    }
+}
+
+/// Frecency from a place's most recent visits and its total visit count
+fn frecency(visits: &[(DateTime<Utc>, Transition)], visit_count: u32, now: DateTime<Utc>) -> i64 {
+    if visits.is_empty() {
+        return 0;
+    }
+    let points: i64 = visits
+        .iter()
+        .map(|(at, transition)| age_weight(now - *at) * transition.bonus() / 100)
+        .sum();
+    (visit_count as i64 * points + visits.len() as i64 - 1) / visits.len() as i64
+}
+
+/// Recompute the frecency of one place from its latest visits
+fn update_frecency(tx: &Transaction, place_id: i64, now: DateTime<Utc>) -> SqlResult<()> {
+    let visit_count: u32 = tx.query_row("SELECT visit_count FROM places WHERE id = ?1", params![place_id], |row| row.get(0))?;
+    let mut stmt = tx.prepare(
+        "SELECT visited_at, transition FROM visits WHERE place_id = ?1 ORDER BY visited_at DESC LIMIT ?2",
+    )?;
+    let visits = stmt
+        .query_map(params![place_id, FRECENCY_SAMPLE], |row| {
+            let transition: String = row.get(1)?;
+            Ok((row.get(0)?, Transition::parse(&transition).unwrap_or(Transition::Link)))
+        })?
+        .collect::<SqlResult<Vec<_>>>()?;
+    tx.execute(
+        "UPDATE places SET frecency = ?1 WHERE id = ?2",
+        params![frecency(&visits, visit_count, now), place_id],
+    )?;
+    Ok(())
+}
+
+/// FTS5 query matching every word of `query` as a prefix
+fn match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+impl Database {
+    /// Record a visit to `url` reached by `transition` from `referrer`. An
+    /// empty title keeps the one already stored.
+    pub fn record_visit(
+        &self,
+        url: &str,
+        title: &str,
+        transition: Transition,
+        referrer: Option<&str>,
+        at: DateTime<Utc>,
+    ) -> SqlResult<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+
+        let place_id: i64 = tx.query_row(
+            "INSERT INTO places (url, title, visit_count, last_visit) VALUES (?1, ?2, 1, ?3)
+             ON CONFLICT (url) DO UPDATE SET
+                visit_count = visit_count + 1,
+                last_visit = MAX(COALESCE(last_visit, ''), excluded.last_visit),
+                title = CASE WHEN excluded.title = '' THEN title ELSE excluded.title END
+             RETURNING id",
+            params![url, title, at],
+            |row| row.get(0),
+        )?;
+        let referrer_id: Option<i64> = match referrer {
+            Some(referrer) => tx
+                .query_row("SELECT id FROM places WHERE url = ?1", params![referrer], |row| row.get(0))
+                .optional()?,
+            None => None,
+        };
+        tx.execute(
+            "INSERT INTO visits (place_id, visited_at, transition, referrer_id) VALUES (?1, ?2, ?3, ?4)",
+            params![place_id, at, transition.as_str(), referrer_id],
+        )?;
+        update_frecency(&tx, place_id, Utc::now())?;
+
+        tx.commit()
+    }
+
+    /// Add places given as `(url, title, visit_count, visited_at)`, each
+    /// with its latest visit standing in for the rest, all or none of them.
+    /// The import is recorded under `marker` and returns false without
+    /// adding anything if `marker` was already imported.
+    pub fn import_places<'a>(
+        &self,
+        marker: &str,
+        places: impl IntoIterator<Item = (&'a str, &'a str, u32, DateTime<Utc>)>,
+    ) -> SqlResult<bool> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let done = tx.query_row("SELECT 1 FROM meta WHERE key = ?1", params![marker], |_| Ok(())).optional()?;
+        if done.is_some() {
+            return Ok(false);
+        }
+
+        let now = Utc::now();
+        for (url, title, visit_count, visited_at) in places {
+            let place_id: i64 = tx.query_row(
+                "INSERT INTO places (url, title, visit_count, last_visit) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (url) DO UPDATE SET visit_count = visit_count + excluded.visit_count
+                 RETURNING id",
+                params![url, title, visit_count.max(1), visited_at],
+                |row| row.get(0),
+            )?;
+            tx.execute(
+                "INSERT INTO visits (place_id, visited_at, transition) VALUES (?1, ?2, ?3)",
+                params![place_id, visited_at, Transition::Link.as_str()],
+            )?;
+            update_frecency(&tx, place_id, now)?;
+        }
+        tx.execute("INSERT INTO meta (key, value) VALUES (?1, ?2)", params![marker, now])?;
+
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Recompute every place's frecency as of `now`. Scores are stored when a
+    /// place is visited, so a place left alone keeps its score until this
+    /// ages it.
+    pub fn refresh_frecency(&self, now: DateTime<Utc>) -> SqlResult<usize> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let place_ids = {
+            let mut stmt = tx.prepare("SELECT id FROM places WHERE frecency > 0")?;
+            let ids = stmt.query_map([], |row| row.get::<_, i64>(0))?;
+            ids.collect::<SqlResult<Vec<_>>>()?
+        };
+        for place_id in &place_ids {
+            update_frecency(&tx, *place_id, now)?;
+        }
+        tx.commit()?;
+        Ok(place_ids.len())
+    }
+
+    /// Set the title of a visited URL, once the page reports it
+    pub fn set_place_title(&self, url: &str, title: &str) -> SqlResult<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE places SET title = ?2 WHERE url = ?1 AND title != ?2",
+            params![url, title],
+        )?;
+        Ok(())
+    }
+
+    pub fn history_entry(&self, url: &str) -> SqlResult<Option<HistoryEntry>> {
+        let conn = self.conn.lock();
+        conn.query_row(
+            &format!("{} WHERE url = ?1", SELECT_PLACE),
+            params![url],
+            HistoryEntry::from_row,
+        )
+        .optional()
+    }
+
+    /// Most recently visited places first
+    pub fn recent_history(&self, limit: usize) -> SqlResult<Vec<HistoryEntry>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!("{} ORDER BY last_visit DESC LIMIT ?1", SELECT_PLACE))?;
+        let entries = stmt.query_map(params![limit], HistoryEntry::from_row)?;
+        entries.collect()
+    }
+
+    /// Highest frecency first
+    pub fn top_places(&self, limit: usize) -> SqlResult<Vec<HistoryEntry>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!("{} ORDER BY frecency DESC, last_visit DESC LIMIT ?1", SELECT_PLACE))?;
+        let entries = stmt.query_map(params![limit], HistoryEntry::from_row)?;
+        entries.collect()
+    }
+
+    /// Places whose URL or title has words starting with each word of
+    /// `query`, highest frecency first
+    pub fn search_history(&self, query: &str, limit: usize) -> SqlResult<Vec<HistoryEntry>> {
+        let expression = match match_expression(query) {
+            Some(expression) => expression,
+            None => return Ok(Vec::new()),
+        };
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!(
+            "{} JOIN places_fts ON places_fts.rowid = places.id
+             WHERE places_fts MATCH ?1
+             ORDER BY places.frecency DESC, places.last_visit DESC LIMIT ?2",
+            SELECT_PLACE
+        ))?;
+        let entries = stmt.query_map(params![expression, limit], HistoryEntry::from_row)?;
+        entries.collect()
+    }
+
+    /// Forget a URL and all its visits
+    pub fn delete_history_url(&self, url: &str) -> SqlResult<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let place_id: Option<i64> = tx
+            .query_row("SELECT id FROM places WHERE url = ?1", params![url], |row| row.get(0))
+            .optional()?;
+        if let Some(place_id) = place_id {
+            tx.execute("DELETE FROM visits WHERE place_id = ?1", params![place_id])?;
+            tx.execute("UPDATE visits SET referrer_id = NULL WHERE referrer_id = ?1", params![place_id])?;
+            tx.execute("DELETE FROM places WHERE id = ?1", params![place_id])?;
+        }
+        tx.commit()
+    }
+
+    pub fn clear_history(&self) -> SqlResult<()> {
+        let conn = self.conn.lock();
+        conn.execute_batch("DELETE FROM visits; DELETE FROM places;")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseConfig;
+
+    fn memory_db() -> Database {
+        Database::new(DatabaseConfig { path: ":memory:".into(), enable_wal: false }).unwrap()
+    }
+
+    #[test]
+    fn search_ranks_by_frecency() {
+        let db = memory_db();
+        let now = Utc::now();
+        db.record_visit("https://docs.rs/rusqlite", "rusqlite - Rust", Transition::Link, None, now - Duration::days(60)).unwrap();
+        db.record_visit("https://github.com/rusqlite/rusqlite", "", Transition::Typed, None, now).unwrap();
+        db.record_visit("https://github.com/rusqlite/rusqlite", "GitHub - rusqlite", Transition::Link, Some("https://docs.rs/rusqlite"), now).unwrap();
+
+        let results = db.search_history("rusq", 10).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://github.com/rusqlite/rusqlite");
+        assert_eq!(results[0].visit_count, 2);
+        assert_eq!(results[0].title, "GitHub - rusqlite");
+        assert!(results[0].frecency > results[1].frecency);
+
+        // Every word has to match
+        assert_eq!(db.search_history("rust docs", 10).unwrap().len(), 1);
+        assert!(db.search_history("\"", 10).unwrap().is_empty());
+
+        db.delete_history_url("https://docs.rs/rusqlite").unwrap();
+        assert_eq!(db.search_history("rusqlite", 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn frecency_favours_recent_typed_visits() {
+        let now = Utc::now();
+        let recent_typed = frecency(&[(now, Transition::Typed)], 1, now);
+        let recent_link = frecency(&[(now, Transition::Link)], 1, now);
+        let old_link = frecency(&[(now - Duration::days(120), Transition::Link)], 1, now);
+        assert!(recent_typed > recent_link && recent_link > old_link);
+        assert_eq!(frecency(&[(now, Transition::Reload)], 5, now), 0);
+    }
+
+    #[test]
+    fn refresh_ages_frecency() {
+        let db = memory_db();
+        let now = Utc::now();
+        db.record_visit("https://example.com/", "Example", Transition::Typed, None, now).unwrap();
+        let fresh = db.history_entry("https://example.com/").unwrap().unwrap().frecency;
+
+        assert_eq!(db.refresh_frecency(now + Duration::days(100)).unwrap(), 1);
+        let aged = db.history_entry("https://example.com/").unwrap().unwrap().frecency;
+        assert!(aged < fresh && aged > 0);
+    }
+}
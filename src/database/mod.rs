@@ -5,6 +5,12 @@
 //! Database Module
 //! SQLite-based local storage for Marshall
 
+mod history;
+mod site_profiles;
+
+pub use history::{HistoryEntry, Transition};
+pub use site_profiles::{CookieSetting, SiteProfile};
+
 use rusqlite::{Connection, Result as SqlResult};
 use std::path::PathBuf;
 use parking_lot::Mutex;
@@ -167,6 +173,24 @@ impl Database {
             [],
         )?;
 
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS site_profiles (
+                origin TEXT PRIMARY KEY,
+                javascript INTEGER,
+                cookies TEXT,
+                images INTEGER,
+                webgl INTEGER,
+                fingerprint_protection INTEGER,
+                user_agent TEXT,
+                referrer_policy TEXT,
+                adblock_exempt INTEGER NOT NULL DEFAULT 0,
+                updated_at TEXT
+            )",
+            [],
+        )?;
+
+        conn.execute_batch(history::SCHEMA)?;
+
         Ok(())
     }
 
@@ -0,0 +1,178 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Per-site privacy and permission profiles
+
+use rusqlite::{params, OptionalExtension, Result as SqlResult, Row};
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+/// Cookie acceptance for a site
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CookieSetting {
+    All,
+    NoThirdParty,
+    None,
+}
+
+impl CookieSetting {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CookieSetting::All => "all",
+            CookieSetting::NoThirdParty => "no-third-party",
+            CookieSetting::None => "none",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "all" => Some(CookieSetting::All),
+            "no-third-party" => Some(CookieSetting::NoThirdParty),
+            "none" => Some(CookieSetting::None),
+            _ => None,
+        }
+    }
+}
+
+/// Overrides for one origin; `None` means "use the global setting"
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SiteProfile {
+    /// `scheme://host[:port]`
+    pub origin: String,
+    pub javascript: Option<bool>,
+    pub cookies: Option<CookieSetting>,
+    pub images: Option<bool>,
+    pub webgl: Option<bool>,
+    pub fingerprint_protection: Option<bool>,
+    pub user_agent: Option<String>,
+    pub referrer_policy: Option<String>,
+    pub adblock_exempt: bool,
+}
+
+impl SiteProfile {
+    pub fn new(origin: &str) -> Self {
+        Self {
+            origin: origin.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Origin of an http(s) URL, e.g. `https://example.com:8443`
+    pub fn origin_of(url: &str) -> Option<String> {
+        let url = url::Url::parse(url).ok()?;
+        match url.scheme() {
+            "http" | "https" => Some(url.origin().ascii_serialization()),
+            _ => None,
+        }
+    }
+
+    /// Host part of the origin
+    pub fn host(&self) -> Option<String> {
+        url::Url::parse(&self.origin).ok()?.host_str().map(str::to_string)
+    }
+
+    /// True when the profile overrides nothing
+    pub fn is_default(&self) -> bool {
+        Self { origin: self.origin.clone(), ..Default::default() } == *self
+    }
+
+    fn from_row(row: &Row) -> SqlResult<Self> {
+        let cookies: Option<String> = row.get(2)?;
+        Ok(Self {
+            origin: row.get(0)?,
+            javascript: row.get(1)?,
+            cookies: cookies.as_deref().and_then(CookieSetting::parse),
+            images: row.get(3)?,
+            webgl: row.get(4)?,
+            fingerprint_protection: row.get(5)?,
+            user_agent: row.get(6)?,
+            referrer_policy: row.get(7)?,
+            adblock_exempt: row.get(8)?,
+        })
+    }
+}
+
+const SELECT_PROFILE: &str = "SELECT origin, javascript, cookies, images, webgl,
+    fingerprint_protection, user_agent, referrer_policy, adblock_exempt FROM site_profiles";
+
+impl Database {
+    /// All stored site profiles, ordered by origin
+    pub fn site_profiles(&self) -> SqlResult<Vec<SiteProfile>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!("{} ORDER BY origin", SELECT_PROFILE))?;
+        let profiles = stmt.query_map([], SiteProfile::from_row)?;
+        profiles.collect()
+    }
+
+    pub fn site_profile(&self, origin: &str) -> SqlResult<Option<SiteProfile>> {
+        let conn = self.conn.lock();
+        conn.query_row(
+            &format!("{} WHERE origin = ?1", SELECT_PROFILE),
+            params![origin],
+            SiteProfile::from_row,
+        )
+        .optional()
+    }
+
+    /// Insert or replace the profile for its origin
+    pub fn save_site_profile(&self, profile: &SiteProfile) -> SqlResult<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO site_profiles (origin, javascript, cookies, images, webgl,
+                fingerprint_protection, user_agent, referrer_policy, adblock_exempt, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                profile.origin,
+                profile.javascript,
+                profile.cookies.map(|c| c.as_str()),
+                profile.images,
+                profile.webgl,
+                profile.fingerprint_protection,
+                profile.user_agent,
+                profile.referrer_policy,
+                profile.adblock_exempt,
+                chrono::Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_site_profile(&self, origin: &str) -> SqlResult<()> {
+        let conn = self.conn.lock();
+        conn.execute("DELETE FROM site_profiles WHERE origin = ?1", params![origin])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseConfig;
+
+    #[test]
+    fn profile_round_trip() {
+        let db = Database::new(DatabaseConfig { path: ":memory:".into(), enable_wal: false }).unwrap();
+
+        let mut profile = SiteProfile::new("https://example.com");
+        profile.javascript = Some(false);
+        profile.cookies = Some(CookieSetting::NoThirdParty);
+        profile.user_agent = Some("TestAgent/1.0".to_string());
+        profile.adblock_exempt = true;
+        db.save_site_profile(&profile).unwrap();
+
+        assert_eq!(db.site_profile("https://example.com").unwrap(), Some(profile.clone()));
+        assert_eq!(db.site_profiles().unwrap().len(), 1);
+
+        db.delete_site_profile("https://example.com").unwrap();
+        assert!(db.site_profile("https://example.com").unwrap().is_none());
+    }
+
+    #[test]
+    fn origin_normalization() {
+        assert_eq!(SiteProfile::origin_of("https://Example.com/path?q=1").as_deref(), Some("https://example.com"));
+        assert_eq!(SiteProfile::origin_of("http://example.com:8080/").as_deref(), Some("http://example.com:8080"));
+        assert!(SiteProfile::origin_of("marshall://privacy").is_none());
+    }
+}
@@ -0,0 +1,427 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Downloads: what was fetched, where it went, and whether it looked safe
+//!
+//! Records are kept in `downloads.json`. Downloads from private windows are
+//! held in memory only and forgotten when the private session ends.
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::config::{Config, GeneralConfig};
+use crate::security::threat_intel::{ThreatIntel, ThreatLevel};
+
+static STORE: Lazy<RwLock<DownloadManager>> = Lazy::new(|| RwLock::new(DownloadManager::open()));
+
+/// Files that run code when opened
+pub const DANGEROUS_EXTENSIONS: &[&str] = &[
+    "exe", "msi", "com", "scr", "pif", "bat", "cmd", "vbs", "vbe", "js", "jse", "wsf", "hta",
+    "ps1", "psm1", "reg", "lnk", "jar", "dll", "cpl", "msc", "sh", "run", "bin", "appimage",
+    "deb", "rpm", "apk", "dmg", "pkg", "command", "desktop", "iso", "img",
+];
+
+/// Extensions people expect to be harmless, used to spot `invoice.pdf.exe`
+const DOCUMENT_EXTENSIONS: &[&str] = &[
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "txt", "jpg", "jpeg", "png", "gif", "mp3", "mp4", "zip",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadState {
+    InProgress,
+    /// Stopped by the user; resuming starts the transfer again
+    Paused,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl DownloadState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DownloadState::InProgress => "Downloading",
+            DownloadState::Paused => "Paused",
+            DownloadState::Completed => "Completed",
+            DownloadState::Failed => "Failed",
+            DownloadState::Cancelled => "Cancelled",
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, DownloadState::Completed | DownloadState::Failed | DownloadState::Cancelled)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadRecord {
+    pub id: String,
+    pub url: String,
+    pub filename: String,
+    /// Where the file is written, once chosen
+    pub path: Option<PathBuf>,
+    pub received_bytes: u64,
+    /// 0 when the server didn't say
+    pub total_bytes: u64,
+    pub state: DownloadState,
+    pub error: Option<String>,
+    /// Hex digest of the finished file
+    pub sha256: Option<String>,
+    /// Why the file may be unsafe; the user was asked before it was saved
+    pub warnings: Vec<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// From a private window; never written to disk
+    #[serde(skip)]
+    pub private: bool,
+}
+
+impl DownloadRecord {
+    /// Progress from 0 to 1, when the size is known
+    pub fn progress(&self) -> Option<f64> {
+        if self.total_bytes == 0 {
+            None
+        } else {
+            Some((self.received_bytes as f64 / self.total_bytes as f64).min(1.0))
+        }
+    }
+}
+
+pub struct DownloadManager {
+    records: Vec<DownloadRecord>,
+    /// None when downloads.json can't be used; records last for the session
+    file_path: Option<PathBuf>,
+    /// Bumped on every change, so pages showing downloads know to redraw
+    revision: u64,
+}
+
+impl DownloadManager {
+    /// The downloads of every window
+    pub fn global() -> &'static RwLock<DownloadManager> {
+        &STORE
+    }
+
+    fn open() -> Self {
+        Self::new().unwrap_or_else(|e| {
+            warn!("Downloads will not be remembered: {}", e);
+            Self::in_memory()
+        })
+    }
+
+    pub fn new() -> Result<Self, String> {
+        let dir = Config::data_dir().map_err(|e| format!("No data directory: {}", e))?;
+        Self::load(dir.join("downloads.json"))
+    }
+
+    fn in_memory() -> Self {
+        Self {
+            records: Vec::new(),
+            file_path: None,
+            revision: 0,
+        }
+    }
+
+    fn load(file_path: PathBuf) -> Result<Self, String> {
+        let mut records: Vec<DownloadRecord> = if file_path.exists() {
+            let content = fs::read_to_string(&file_path)
+                .map_err(|e| format!("Failed to read {}: {}", file_path.display(), e))?;
+            serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse {}: {}", file_path.display(), e))?
+        } else {
+            Vec::new()
+        };
+
+        // Transfers don't survive a restart
+        for record in records.iter_mut().filter(|r| !r.state.is_finished()) {
+            record.state = DownloadState::Failed;
+            record.error = Some("Interrupted when Marshall closed".to_string());
+        }
+        Ok(Self {
+            records,
+            file_path: Some(file_path),
+            revision: 0,
+        })
+    }
+
+    /// Record a new download of `url`; returns its id
+    pub fn start(&mut self, url: &str, private: bool) -> String {
+        let filename = url
+            .split(['?', '#'])
+            .next()
+            .and_then(|u| u.rsplit('/').next())
+            .filter(|name| !name.is_empty())
+            .unwrap_or("download")
+            .to_string();
+        let record = DownloadRecord {
+            id: Uuid::new_v4().to_string(),
+            url: url.to_string(),
+            warnings: assess(url, &filename),
+            filename,
+            path: None,
+            received_bytes: 0,
+            total_bytes: 0,
+            state: DownloadState::InProgress,
+            error: None,
+            sha256: None,
+            started_at: Utc::now(),
+            finished_at: None,
+            private,
+        };
+        let id = record.id.clone();
+        self.records.push(record);
+        self.changed();
+        id
+    }
+
+    /// Start a paused, failed or cancelled download over
+    pub fn restart(&mut self, id: &str) {
+        if let Some(record) = self.get_mut(id) {
+            record.state = DownloadState::InProgress;
+            record.received_bytes = 0;
+            record.error = None;
+            record.sha256 = None;
+            record.finished_at = None;
+            record.started_at = Utc::now();
+        }
+        self.changed();
+    }
+
+    /// Where the file goes; the name decides the extension warnings
+    pub fn set_destination(&mut self, id: &str, path: &Path) {
+        if let Some(record) = self.get_mut(id) {
+            record.filename = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| record.filename.clone());
+            record.path = Some(path.to_path_buf());
+            record.warnings = assess(&record.url, &record.filename);
+        }
+        self.changed();
+    }
+
+    /// Progress isn't saved; a restart fails the transfer anyway
+    pub fn set_progress(&mut self, id: &str, received: u64, total: u64) {
+        if let Some(record) = self.get_mut(id) {
+            record.received_bytes = received;
+            record.total_bytes = total;
+        }
+        self.revision += 1;
+    }
+
+    pub fn set_state(&mut self, id: &str, state: DownloadState, error: Option<String>) {
+        if let Some(record) = self.get_mut(id) {
+            record.state = state;
+            record.error = error;
+            if state.is_finished() {
+                record.finished_at = Some(Utc::now());
+            }
+            if state == DownloadState::Completed && record.total_bytes == 0 {
+                record.total_bytes = record.received_bytes;
+            }
+        }
+        self.changed();
+    }
+
+    pub fn set_sha256(&mut self, id: &str, digest: &str) {
+        if let Some(record) = self.get_mut(id) {
+            record.sha256 = Some(digest.to_string());
+        }
+        self.changed();
+    }
+
+    pub fn get(&self, id: &str) -> Option<&DownloadRecord> {
+        self.records.iter().find(|r| r.id == id)
+    }
+
+    fn get_mut(&mut self, id: &str) -> Option<&mut DownloadRecord> {
+        self.records.iter_mut().find(|r| r.id == id)
+    }
+
+    /// Downloads of private windows, or of the others; newest first
+    pub fn all(&self, private: bool) -> Vec<&DownloadRecord> {
+        self.records.iter().rev().filter(|r| r.private == private).collect()
+    }
+
+    /// Forget a download; the file stays where it is
+    pub fn remove(&mut self, id: &str) {
+        self.records.retain(|r| r.id != id);
+        self.changed();
+    }
+
+    /// Forget every finished download of private windows, or of the others
+    pub fn clear_finished(&mut self, private: bool) {
+        self.records.retain(|r| r.private != private || !r.state.is_finished());
+        self.changed();
+    }
+
+    /// Called when the private session ends
+    pub fn forget_private(&mut self) {
+        self.records.retain(|r| !r.private);
+        self.revision += 1;
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn changed(&mut self) {
+        self.revision += 1;
+        if let Err(e) = self.save() {
+            warn!("{}", e);
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let file_path = match &self.file_path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let records: Vec<&DownloadRecord> = self.records.iter().filter(|r| !r.private).collect();
+        let content = serde_json::to_string_pretty(&records)
+            .map_err(|e| format!("Failed to serialize downloads: {}", e))?;
+        fs::write(file_path, content).map_err(|e| format!("Failed to write {}: {}", file_path.display(), e))
+    }
+}
+
+/// Reasons to think twice before saving `filename` from `url`
+pub fn assess(url: &str, filename: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    let lower = filename.to_lowercase();
+    let mut parts = lower.rsplit('.');
+    let extension = parts.next().filter(|_| lower.contains('.'));
+    let inner = parts.next().filter(|_| lower.matches('.').count() >= 2);
+    if let Some(extension) = extension.filter(|e| DANGEROUS_EXTENSIONS.contains(e)) {
+        match inner.filter(|i| DOCUMENT_EXTENSIONS.contains(i)) {
+            Some(inner) => warnings.push(format!(
+                "“.{}” file disguised as “.{}” — a common trick to get programs opened",
+                extension, inner
+            )),
+            None => warnings.push(format!("“.{}” files can run programs on your computer", extension)),
+        }
+    }
+
+    if url.starts_with("http://") {
+        warnings.push("Downloaded over an unencrypted connection; it could have been altered".to_string());
+    }
+
+    let analysis = ThreatIntel::global().read().analyze_url(url);
+    if analysis.level != ThreatLevel::Safe {
+        let reasons: Vec<&str> = analysis.indicators.iter().map(|i| i.description.as_str()).collect();
+        warnings.push(format!("Source rated {}: {}", analysis.level, reasons.join("; ")));
+    }
+    warnings
+}
+
+/// Where downloads are saved, with `~` expanded
+pub fn download_dir(config: &GeneralConfig) -> PathBuf {
+    match config.download_dir.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map(|home| home.join(rest)).unwrap_or_else(|| PathBuf::from(rest)),
+        None => PathBuf::from(&config.download_dir),
+    }
+}
+
+/// `dir/name`, or `dir/name (1).ext` and so on if that file exists. The
+/// name the server suggested is stripped of any directories.
+pub fn unique_path(dir: &Path, suggested: &str) -> PathBuf {
+    let name = suggested
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or("")
+        .trim_start_matches('.')
+        .trim();
+    let name = if name.is_empty() { "download" } else { name };
+
+    let path = dir.join(name);
+    if !path.exists() {
+        return path;
+    }
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name, String::new()),
+    };
+    (1..)
+        .map(|n| dir.join(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or(path)
+}
+
+/// Hex SHA-256 of a file, read in chunks
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warns_about_programs_and_disguises() {
+        assert!(assess("https://example.com/report.pdf", "report.pdf").is_empty());
+        let warnings = assess("https://example.com/setup.exe", "setup.exe");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains(".exe"));
+        assert!(assess("https://example.com/a", "invoice.pdf.exe")[0].contains("disguised"));
+        assert_eq!(assess("http://example.com/notes.txt", "notes.txt").len(), 1);
+        assert!(assess("https://paypal-login-verify.tk/x.zip", "x.zip").iter().any(|w| w.starts_with("Source rated")));
+    }
+
+    #[test]
+    fn hashes_files_and_avoids_overwriting() {
+        let dir = std::env::temp_dir().join(format!("marshall-downloads-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let first = unique_path(&dir, "../../etc/abc.txt");
+        assert_eq!(first, dir.join("abc.txt"));
+        fs::write(&first, "abc").unwrap();
+        assert_eq!(unique_path(&dir, "abc.txt"), dir.join("abc (1).txt"));
+        assert_eq!(
+            sha256_file(&first).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn private_downloads_are_not_saved() {
+        let path = std::env::temp_dir().join(format!("marshall-downloads-{}.json", std::process::id()));
+        let mut manager = DownloadManager::load(path.clone()).unwrap();
+        let kept = manager.start("https://example.com/a.zip", false);
+        let private = manager.start("https://example.com/b.zip", true);
+        manager.set_state(&private, DownloadState::Completed, None);
+        assert_eq!(manager.all(true).len(), 1);
+
+        let reloaded = DownloadManager::load(path.clone()).unwrap();
+        fs::remove_file(&path).ok();
+        let records = reloaded.all(false);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, kept);
+        // Still running when saved, so it didn't survive the restart
+        assert_eq!(records[0].state, DownloadState::Failed);
+        assert!(reloaded.all(true).is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! URL bar suggestions — merges open tabs, bookmarks, history, internal pages
+//! and search suggestions into one ranked list
+//!
+//! The first suggestion is always what Enter would do with the text as typed.
+//! Later ones are deduplicated by URL, ignoring the scheme, a leading `www.`
+//! and a trailing slash; an open tab wins over a bookmark, a bookmark over
+//! history.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use url::Url;
+
+use super::navigation::{resolve, NavTarget, SEARCH_TEMPLATE};
+use crate::bookmarks::Bookmark;
+use crate::history::HistoryEntry;
+use crate::network;
+
+/// DuckDuckGo's suggestion endpoint; answers `[{"phrase": "..."}, ...]`
+pub const SEARCH_SUGGEST_URL: &str = "https://duckduckgo.com/ac/?type=list&kl=wt-wt&q=";
+
+/// Internal pages offered while typing
+pub const INTERNAL_PAGES: &[(&str, &str)] = &[
+    ("marshall://home", "Home"),
+    ("marshall://menu", "Menu"),
+    ("marshall://settings", "Settings"),
+    ("marshall://privacy", "Privacy"),
+    ("marshall://containers", "Containers"),
+    ("marshall://leaktest", "Leak Test"),
+    ("marshall://downloads", "Downloads"),
+    ("marshall://assistant", "Dr Marshall AI"),
+    ("marshall://osint", "OSINT Tools"),
+    ("marshall://workforce", "Workforce"),
+    ("marshall://voip", "VoIP"),
+];
+
+/// Where a suggestion came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestionKind {
+    /// The typed text itself, as a URL or a search
+    Typed,
+    /// A page already open in a tab; choosing it switches to that tab
+    OpenTab,
+    Bookmark,
+    History,
+    Internal,
+    /// A query suggested by the search engine
+    Search,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub kind: SuggestionKind,
+    pub title: String,
+    /// What to load when the suggestion is chosen
+    pub url: String,
+    /// Set for `OpenTab`
+    pub tab_id: Option<String>,
+}
+
+impl Suggestion {
+    fn new(kind: SuggestionKind, title: &str, url: &str) -> Self {
+        Self {
+            kind,
+            title: title.to_string(),
+            url: url.to_string(),
+            tab_id: None,
+        }
+    }
+}
+
+/// A tab the user could switch to
+#[derive(Debug, Clone)]
+pub struct OpenTab {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+}
+
+/// Everything the suggestions are drawn from. History is expected in
+/// frecency order, as `HistoryManager::search` returns it.
+#[derive(Debug, Default)]
+pub struct SuggestionSources<'a> {
+    pub open_tabs: &'a [OpenTab],
+    pub bookmarks: &'a [Bookmark],
+    pub history: &'a [HistoryEntry],
+    pub search: &'a [String],
+}
+
+/// Ranked suggestions for `input`, at most `limit` of them
+pub fn suggest(input: &str, sources: &SuggestionSources, limit: usize) -> Vec<Suggestion> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Vec::new();
+    }
+    let words: Vec<String> = input.to_lowercase().split_whitespace().map(str::to_string).collect();
+    let matches = |title: &str, url: &str| {
+        let (title, url) = (title.to_lowercase(), url.to_lowercase());
+        words.iter().all(|w| title.contains(w.as_str()) || url.contains(w.as_str()))
+    };
+
+    let mut results = vec![match resolve(input) {
+        NavTarget::Search(url) => Suggestion::new(SuggestionKind::Typed, &format!("Search for “{}”", input), &url),
+        NavTarget::Web(url) | NavTarget::Internal(url) => Suggestion::new(SuggestionKind::Typed, &url, &url),
+    }];
+    let mut seen: HashSet<String> = results.iter().map(|s| normalize(&s.url)).collect();
+    let mut push = |results: &mut Vec<Suggestion>, suggestion: Suggestion| {
+        if seen.insert(normalize(&suggestion.url)) {
+            results.push(suggestion);
+        }
+    };
+
+    for tab in sources.open_tabs.iter().filter(|t| matches(&t.title, &t.url)) {
+        // Switching to the tab matters even when the typed URL is the same page
+        let mut suggestion = Suggestion::new(SuggestionKind::OpenTab, &tab.title, &tab.url);
+        suggestion.tab_id = Some(tab.id.clone());
+        if results[0].url == tab.url {
+            results.push(suggestion);
+        } else {
+            push(&mut results, suggestion);
+        }
+    }
+    for bookmark in sources.bookmarks.iter().filter(|b| matches(&b.title, &b.url)) {
+        push(&mut results, Suggestion::new(SuggestionKind::Bookmark, &bookmark.title, &bookmark.url));
+    }
+    for entry in sources.history {
+        let title = if entry.title.is_empty() { &entry.url } else { &entry.title };
+        push(&mut results, Suggestion::new(SuggestionKind::History, title, &entry.url));
+    }
+    for (url, title) in INTERNAL_PAGES.iter().filter(|(url, title)| matches(title, url)) {
+        push(&mut results, Suggestion::new(SuggestionKind::Internal, title, url));
+    }
+    for phrase in sources.search.iter().filter(|p| !p.eq_ignore_ascii_case(input)) {
+        let url = SEARCH_TEMPLATE.replace("{QUERY}", &urlencoding::encode(phrase));
+        push(&mut results, Suggestion::new(SuggestionKind::Search, phrase, &url));
+    }
+
+    results.truncate(limit);
+    results
+}
+
+/// The domain to complete `input` to: the host of the highest-ranked
+/// history entry that starts with what was typed
+pub fn inline_completion(input: &str, history: &[HistoryEntry]) -> Option<String> {
+    if input.is_empty() || input.contains(char::is_whitespace) || input.contains('/') {
+        return None;
+    }
+    let prefix = input.to_lowercase();
+    history
+        .iter()
+        .filter_map(|entry| Url::parse(&entry.url).ok()?.host_str().map(str::to_string))
+        .map(|host| host.strip_prefix("www.").map(str::to_string).unwrap_or(host))
+        .find(|host| host.starts_with(&prefix) && host.len() > prefix.len())
+}
+
+/// Ask the search engine what people searching for `query` search for
+pub async fn fetch_search_suggestions(query: &str) -> Result<Vec<String>, String> {
+    let body = network::client()?
+        .get(format!("{}{}", SEARCH_SUGGEST_URL, urlencoding::encode(query)))
+        .timeout(Duration::from_secs(3))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Search suggestions unavailable: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Search suggestions unavailable: {}", e))?;
+    Ok(parse_search_suggestions(&body))
+}
+
+/// Phrases from a response of `SEARCH_SUGGEST_URL`
+pub fn parse_search_suggestions(json: &str) -> Vec<String> {
+    #[derive(serde::Deserialize)]
+    struct Phrase {
+        phrase: String,
+    }
+    serde_json::from_str::<Vec<Phrase>>(json)
+        .map(|phrases| phrases.into_iter().map(|p| p.phrase).collect())
+        .unwrap_or_default()
+}
+
+/// URL without scheme, `www.` and trailing slash, for deduplication
+fn normalize(url: &str) -> String {
+    let url = url.to_lowercase();
+    let url = url.split_once("://").map(|(_, rest)| rest.to_string()).unwrap_or(url);
+    let url = url.strip_prefix("www.").unwrap_or(&url);
+    url.trim_end_matches('/').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn history(url: &str, title: &str) -> HistoryEntry {
+        HistoryEntry {
+            url: url.to_string(),
+            title: title.to_string(),
+            visited_at: Utc::now(),
+            visit_count: 1,
+            frecency: 100,
+        }
+    }
+
+    #[test]
+    fn merges_and_deduplicates_sources() {
+        let tabs = [OpenTab { id: "t1".into(), title: "GitHub".into(), url: "https://github.com/".into() }];
+        let history = [
+            history("https://www.github.com", "GitHub"),
+            history("https://github.com/rust-lang/rust", "rust-lang/rust"),
+        ];
+        let search = ["github copilot".to_string()];
+        let sources = SuggestionSources { open_tabs: &tabs, history: &history, search: &search, ..Default::default() };
+
+        let results = suggest("github", &sources, 10);
+        let kinds: Vec<SuggestionKind> = results.iter().map(|s| s.kind).collect();
+        assert_eq!(kinds, [SuggestionKind::Typed, SuggestionKind::OpenTab, SuggestionKind::History, SuggestionKind::Search]);
+        assert_eq!(results[1].tab_id.as_deref(), Some("t1"));
+        assert_eq!(results[2].url, "https://github.com/rust-lang/rust");
+
+        let results = suggest("priv", &SuggestionSources::default(), 10);
+        assert_eq!(results[1].url, "marshall://privacy");
+        assert!(suggest("  ", &sources, 10).is_empty());
+    }
+
+    #[test]
+    fn completes_to_top_domain() {
+        let history = [history("https://www.github.com/trending", ""), history("https://gitlab.com/", "")];
+        assert_eq!(inline_completion("git", &history).as_deref(), Some("github.com"));
+        assert_eq!(inline_completion("gitl", &history).as_deref(), Some("gitlab.com"));
+        assert_eq!(inline_completion("github.com", &history), None);
+        assert_eq!(inline_completion("git hub", &history), None);
+    }
+
+    #[test]
+    fn parses_search_suggestions() {
+        assert_eq!(parse_search_suggestions(r#"[{"phrase":"rust"},{"phrase":"rust book"}]"#), ["rust", "rust book"]);
+        assert!(parse_search_suggestions("not json").is_empty());
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! WebKit content filters — network rules compiled into the engine
+//!
+//! WebKitGTK cannot cancel a load from `resource-load-started`, so blocking
+//! is enforced by compiling the rules into a content-blocker list that the
+//! web process applies to every request. webkit2gtk-rs has no bindings for
+//! the filter store, so it is driven through the C API here.
+
+use std::path::Path;
+use std::ptr::NonNull;
+
+use glib::translate::*;
+use webkit2gtk::{ffi, UserContentManager, UserContentManagerExt};
+
+type CompileCallback = Box<dyn FnOnce(Result<ContentFilter, glib::Error>) + 'static>;
+
+struct PendingCompile {
+    identifier: String,
+    callback: CompileCallback,
+}
+
+/// A compiled content-blocker rule list
+pub struct ContentFilter {
+    raw: NonNull<ffi::WebKitUserContentFilter>,
+    identifier: String,
+}
+
+impl ContentFilter {
+    /// Compile content-blocker JSON into a filter stored under `store_dir`.
+    /// WebKit compiles off the main thread; `callback` runs on the main loop.
+    pub fn compile<F>(store_dir: &Path, identifier: &str, rules_json: &str, callback: F)
+    where
+        F: FnOnce(Result<ContentFilter, glib::Error>) + 'static,
+    {
+        let bytes = glib::Bytes::from(rules_json.as_bytes());
+        let pending = Box::new(PendingCompile {
+            identifier: identifier.to_string(),
+            callback: Box::new(callback),
+        });
+
+        unsafe {
+            let store = ffi::webkit_user_content_filter_store_new(store_dir.to_glib_none().0);
+            ffi::webkit_user_content_filter_store_save(
+                store,
+                identifier.to_glib_none().0,
+                bytes.to_glib_none().0,
+                std::ptr::null_mut(),
+                Some(compile_finished),
+                Box::into_raw(pending) as glib::ffi::gpointer,
+            );
+            // The pending task keeps its own reference to the store
+            glib::gobject_ffi::g_object_unref(store as *mut glib::gobject_ffi::GObject);
+        }
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// Start applying this filter to pages using `manager`
+    pub fn attach(&self, manager: &UserContentManager) {
+        unsafe {
+            ffi::webkit_user_content_manager_add_filter(manager.to_glib_none().0, self.raw.as_ptr());
+        }
+    }
+
+    /// Stop applying this filter to pages using `manager`
+    pub fn detach(&self, manager: &UserContentManager) {
+        manager.remove_filter_by_id(&self.identifier);
+    }
+}
+
+impl Clone for ContentFilter {
+    fn clone(&self) -> Self {
+        unsafe { ffi::webkit_user_content_filter_ref(self.raw.as_ptr()) };
+        Self {
+            raw: self.raw,
+            identifier: self.identifier.clone(),
+        }
+    }
+}
+
+impl Drop for ContentFilter {
+    fn drop(&mut self) {
+        unsafe { ffi::webkit_user_content_filter_unref(self.raw.as_ptr()) };
+    }
+}
+
+unsafe extern "C" fn compile_finished(
+    source: *mut glib::gobject_ffi::GObject,
+    result: *mut gio::ffi::GAsyncResult,
+    user_data: glib::ffi::gpointer,
+) {
+    let pending = Box::from_raw(user_data as *mut PendingCompile);
+    let mut error = std::ptr::null_mut();
+    let raw = ffi::webkit_user_content_filter_store_save_finish(
+        source as *mut ffi::WebKitUserContentFilterStore,
+        result,
+        &mut error,
+    );
+
+    let outcome = match NonNull::new(raw) {
+        Some(raw) => Ok(ContentFilter { raw, identifier: pending.identifier }),
+        None if !error.is_null() => Err(from_glib_full(error)),
+        None => Err(glib::Error::new(glib::FileError::Failed, "content filter compilation failed")),
+    };
+    (pending.callback)(outcome);
+}
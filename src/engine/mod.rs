@@ -6,6 +6,10 @@
 
 mod webview;
 mod navigation;
+mod content_filter;
+mod autocomplete;
 
 pub use webview::*;
 pub use navigation::*;
+pub use content_filter::ContentFilter;
+pub use autocomplete::*;
@@ -11,7 +11,7 @@ use tracing::info;
 const INTERNAL_SCHEME: &str = "marshall://";
 
 /// Default search engine template (DuckDuckGo dark-mode)
-const SEARCH_TEMPLATE: &str =
+pub(super) const SEARCH_TEMPLATE: &str =
     "https://duckduckgo.com/?q={QUERY}&kae=d&k1=-1&kaj=m&kam=osm&kp=-2";
 
 /// Navigation request types returned by the router
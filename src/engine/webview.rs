@@ -7,50 +7,52 @@
 use webkit2gtk::{
     WebView, WebViewExt, WebContext, WebContextExt,
     CookieAcceptPolicy, Settings, SettingsExt,
-    TLSErrorsPolicy,
+    TLSErrorsPolicy, WebsiteDataManager, WebsiteDataManagerExt, WebsiteDataManagerExtManual, WebsiteDataTypes,
+    CookiePersistentStorage, NetworkProxyMode, NetworkProxySettings,
 };
 use webkit2gtk::CookieManagerExt;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::config::Config;
+use crate::database::SiteProfile;
+use crate::network;
+use crate::security::persona::Persona;
+use crate::tabs::Container;
 
-/// Hardened user-agent strings (rotated per session)
-const HARDENED_USER_AGENTS: &[&str] = &[
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
-    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
-    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:125.0) Gecko/20100101 Firefox/125.0",
-    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10.15; rv:125.0) Gecko/20100101 Firefox/125.0",
-    "Mozilla/5.0 (X11; Linux x86_64; rv:125.0) Gecko/20100101 Firefox/125.0",
-    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36 Edg/124.0.0.0",
-    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Safari/605.1.15",
-];
+/// Where pages go when the configured proxy can't be used: nothing listens
+/// there, so they fail instead of connecting directly
+const UNUSABLE_PROXY: &str = "http://127.0.0.1:9";
 
 /// WebView configuration manager
 pub struct WebViewManager {
     privacy_strict: bool,
     block_fingerprinting: bool,
     block_third_party_cookies: bool,
-    user_agent: String,
+    enable_images: bool,
+    enable_webgl: bool,
+    persona: Persona,
 }
 
 impl WebViewManager {
     /// Create a new WebView manager from app config
     pub fn from_config(config: &Config) -> Self {
-        let mut rng = rand::thread_rng();
-        let idx = rand::Rng::gen_range(&mut rng, 0..HARDENED_USER_AGENTS.len());
-
         Self {
             privacy_strict: config.privacy.strict_mode,
             block_fingerprinting: config.privacy.block_fingerprinting,
             block_third_party_cookies: config.privacy.block_third_party_cookies,
-            user_agent: HARDENED_USER_AGENTS[idx].to_string(),
+            enable_images: config.general.enable_images,
+            enable_webgl: config.general.enable_webgl,
+            persona: Persona::session(&config.privacy),
         }
     }
 
-    /// Create an ephemeral (non-persistent) web context for maximum privacy
+    /// Create an ephemeral (non-persistent) web context for maximum privacy.
+    /// Its website data manager keeps cookies, cache and storage in memory,
+    /// so nothing the context loads reaches the disk. Each one has its own
+    /// Tor circuits in tor mode.
     pub fn create_ephemeral_context(&self) -> WebContext {
-        let context = WebContext::default().unwrap();
+        let data_manager = WebsiteDataManager::new_ephemeral();
+        let context = WebContext::with_website_data_manager(&data_manager);
 
         // Cookie policy
         if let Some(cookie_manager) = context.cookie_manager() {
@@ -64,39 +66,184 @@ impl WebViewManager {
 
         // Reject invalid TLS by default
         context.set_tls_errors_policy(TLSErrorsPolicy::Fail);
+        Self::apply_network_proxy(&context, &network::isolation_key());
 
         context
     }
 
-    /// Apply hardened settings to a WebView
-    pub fn harden_settings(&self, webview: &WebView) {
-        let settings: Settings = WebViewExt::settings(webview).unwrap();
+    /// Create a persistent context for a container. Its cookies, storage
+    /// and caches live in the container's own directories, and its traffic
+    /// goes through the container's proxy if it has one.
+    pub fn create_container_context(&self, container: &Container) -> Result<WebContext, String> {
+        let data_dir = container.data_dir()?;
+        let cache_dir = container.cache_dir()?;
+        std::fs::create_dir_all(&data_dir)
+            .map_err(|e| format!("Failed to create {}: {}", data_dir.display(), e))?;
 
-        // JavaScript — must stay on for internal pages & DDG
-        settings.set_enable_javascript(true);
+        let data_manager = WebsiteDataManager::builder()
+            .base_data_directory(&data_dir.to_string_lossy())
+            .base_cache_directory(&cache_dir.to_string_lossy())
+            .build();
+        let context = WebContext::with_website_data_manager(&data_manager);
+        Self::apply_container_proxy(&context, container);
+
+        if let Some(cookie_manager) = context.cookie_manager() {
+            cookie_manager.set_persistent_storage(
+                &data_dir.join("cookies.sqlite").to_string_lossy(),
+                CookiePersistentStorage::Sqlite,
+            );
+            let policy = if self.block_third_party_cookies {
+                CookieAcceptPolicy::NoThirdParty
+            } else {
+                CookieAcceptPolicy::Always
+            };
+            cookie_manager.set_accept_policy(policy);
+        }
+        context.set_tls_errors_policy(TLSErrorsPolicy::Fail);
+
+        info!("Container context ready: {}", container.name);
+        Ok(context)
+    }
+
+    /// Route a context through the proxy from the network settings, or
+    /// directly when there is none. In tor mode contexts with different
+    /// `isolation` keys use different circuits.
+    pub fn apply_network_proxy(context: &WebContext, isolation: &str) {
+        Self::set_proxy(context, Self::network_proxy(isolation).as_deref());
+    }
+
+    /// Route a container context through the container's proxy, or the
+    /// network settings when it has none. In tor mode the container's own
+    /// proxy is ignored and the container gets circuits of its own.
+    pub fn apply_container_proxy(context: &WebContext, container: &Container) {
+        let isolation = format!("container-{}", container.id);
+        let proxy = match &container.proxy {
+            Some(proxy) if !network::tor_mode() => Some(proxy.clone()),
+            _ => Self::network_proxy(&isolation),
+        };
+        Self::set_proxy(context, proxy.as_deref());
+    }
+
+    fn network_proxy(isolation: &str) -> Option<String> {
+        network::webkit_proxy(isolation).unwrap_or_else(|e| {
+            warn!("Pages will not load until the proxy is fixed: {}", e);
+            Some(UNUSABLE_PROXY.to_string())
+        })
+    }
+
+    /// System proxy settings are never used, as for Marshall's own traffic
+    fn set_proxy(context: &WebContext, proxy: Option<&str>) {
+        if let Some(data_manager) = context.website_data_manager() {
+            match proxy {
+                Some(proxy) => {
+                    let mut settings = NetworkProxySettings::new(Some(proxy), network::proxy_exemptions());
+                    data_manager.set_network_proxy_settings(NetworkProxyMode::Custom, Some(&mut settings));
+                }
+                None => data_manager.set_network_proxy_settings(NetworkProxyMode::NoProxy, None),
+            }
+        }
+    }
+
+    /// Drop all website data a context holds: cookies, caches, storage and
+    /// HSTS state
+    pub fn clear_website_data(context: &WebContext) {
+        Self::clear_website_data_then(context, || {});
+    }
 
-        // Privacy: disable fingerprinting surfaces when strict
-        if self.block_fingerprinting {
-            settings.set_enable_webgl(false);
-            settings.set_enable_webaudio(false);
+    /// Like `clear_website_data`, calling `done` once the data is gone or
+    /// clearing it has failed
+    pub fn clear_website_data_then(context: &WebContext, done: impl FnOnce() + Send + 'static) {
+        match context.website_data_manager() {
+            Some(data_manager) => data_manager.clear(
+                WebsiteDataTypes::ALL,
+                glib::TimeSpan(0),
+                None::<&gio::Cancellable>,
+                move |result| {
+                    if let Err(e) = result {
+                        warn!("Failed to clear website data: {}", e);
+                    }
+                    done();
+                },
+            ),
+            None => done(),
         }
+    }
+
+    /// Apply hardened settings to a WebView, then any overrides from the
+    /// profile of the origin it is about to load. `persona` is the tab's,
+    /// which is the container's in a container tab.
+    pub fn harden_settings(&self, webview: &WebView, persona: &Persona, profile: Option<&SiteProfile>) {
+        let settings: Settings = match WebViewExt::settings(webview) {
+            Some(settings) => settings,
+            None => return,
+        };
+
+        // JavaScript — must stay on for internal pages & userscript injection
+        settings.set_enable_javascript(true);
+        settings.set_auto_load_images(self.enable_images);
+
+        // Fingerprinting surfaces, unless the site's profile lifts protection
+        let block_fingerprinting = profile
+            .and_then(|p| p.fingerprint_protection)
+            .unwrap_or(self.block_fingerprinting);
+        settings.set_enable_webgl(self.enable_webgl && !block_fingerprinting);
+        settings.set_enable_webaudio(!block_fingerprinting);
 
         // Disable prefetching & cache to reduce tracking surface
         settings.set_enable_dns_prefetching(false);
         settings.set_enable_page_cache(false);
 
-        // User agent rotation
-        settings.set_user_agent(Some(&self.user_agent));
+        // Strict mode also drops <a ping> beacons
+        settings.set_enable_hyperlink_auditing(!self.privacy_strict);
 
-        // Security extras
-        settings.set_allow_modal_dialogs(false);
+        // The tab's persona's user agent and languages
+        Self::apply_persona(webview, persona);
 
-        info!("WebView hardened — UA rotated, fingerprint surfaces disabled");
+        if let Some(profile) = profile {
+            Self::apply_site_profile(&settings, profile);
+        }
+    }
+
+    /// Present `persona` in the view's user agent and its context's
+    /// languages, which WebKit sends as `Accept-Language` and shows as
+    /// `navigator.languages`
+    pub fn apply_persona(webview: &WebView, persona: &Persona) {
+        if let Some(settings) = WebViewExt::settings(webview) {
+            settings.set_user_agent(Some(&persona.user_agent));
+        }
+        if let Some(context) = webview.context() {
+            let languages: Vec<&str> = persona.languages.iter().map(String::as_str).collect();
+            context.set_preferred_languages(&languages);
+        }
+    }
+
+    /// Apply a site profile's overrides on top of settings already configured
+    /// from the global config. Cookies and referrer policy are not view
+    /// settings and are applied by the window.
+    pub fn apply_site_profile(settings: &Settings, profile: &SiteProfile) {
+        if let Some(javascript) = profile.javascript {
+            settings.set_enable_javascript(javascript);
+        }
+        if let Some(images) = profile.images {
+            settings.set_auto_load_images(images);
+        }
+        if let Some(webgl) = profile.webgl {
+            settings.set_enable_webgl(webgl);
+        }
+        if let Some(protect) = profile.fingerprint_protection {
+            settings.set_enable_webaudio(!protect);
+            if protect {
+                settings.set_enable_webgl(false);
+            }
+        }
+        if let Some(user_agent) = &profile.user_agent {
+            settings.set_user_agent(Some(user_agent));
+        }
     }
 
     /// Get the current spoofed user agent
     pub fn user_agent(&self) -> &str {
-        &self.user_agent
+        &self.persona.user_agent
     }
 }
 
@@ -106,7 +253,9 @@ impl Default for WebViewManager {
             privacy_strict: true,
             block_fingerprinting: true,
             block_third_party_cookies: true,
-            user_agent: HARDENED_USER_AGENTS[0].to_string(),
+            enable_images: true,
+            enable_webgl: false,
+            persona: Persona::session(&Config::default().privacy),
         }
     }
 }
@@ -2,122 +2,214 @@
 // Licensed under the MIT License. See LICENSE file in the project root.
 // https://github.com/bad-antics/marshall
 
-//! Browsing history management
+//! Browsing history management, stored in the SQLite database
+//!
+//! History used to live in `history.json`; that file is imported the first
+//! time the database is opened and renamed to `history.json.imported`. The
+//! database records the import, so it happens once even if the rename fails.
 
-use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use tracing::{info, warn};
 
 use crate::config::Config;
+use crate::database::{Database, DatabaseConfig};
+
+pub use crate::database::{HistoryEntry, Transition};
+
+static STORE: Lazy<HistoryManager> = Lazy::new(HistoryManager::open);
 
-const MAX_HISTORY_ENTRIES: usize = 10000;
+/// Marks `history.json` as imported in the database
+const LEGACY_IMPORT: &str = "import:history.json";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HistoryEntry {
-    pub url: String,
-    pub title: String,
-    pub visited_at: DateTime<Utc>,
-    pub visit_count: u32,
+/// An entry of the old `history.json`
+#[derive(Deserialize)]
+struct LegacyEntry {
+    url: String,
+    title: String,
+    visited_at: DateTime<Utc>,
+    visit_count: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct HistoryStore {
-    pub entries: VecDeque<HistoryEntry>,
+#[derive(Deserialize)]
+struct LegacyStore {
+    entries: Vec<LegacyEntry>,
 }
 
 pub struct HistoryManager {
-    store: HistoryStore,
-    file_path: PathBuf,
-    enabled: bool,
+    db: Database,
+    enabled: AtomicBool,
 }
 
 impl HistoryManager {
-    pub fn new(enabled: bool) -> Result<Self, Box<dyn std::error::Error>> {
-        let file_path = Config::data_dir()?.join("history.json");
-        
-        let store = if enabled && file_path.exists() {
-            let content = fs::read_to_string(&file_path)?;
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            HistoryStore::default()
-        };
-        
-        Ok(Self { store, file_path, enabled })
+    /// The history shared by every window
+    pub fn global() -> &'static HistoryManager {
+        &STORE
     }
 
-    pub fn add_entry(&mut self, url: &str, title: &str) {
-        if !self.enabled {
-            return;
+    pub fn new(db: Database, enabled: bool) -> Self {
+        Self {
+            db,
+            enabled: AtomicBool::new(enabled),
         }
+    }
 
-        // Check if URL already exists
-        if let Some(entry) = self.store.entries.iter_mut().find(|e| e.url == url) {
-            entry.visit_count += 1;
-            entry.visited_at = Utc::now();
-            entry.title = title.to_string();
-            return;
+    /// Open the history database, importing `history.json` if it is still
+    /// around. Without a database history lasts for the session only.
+    fn open() -> Self {
+        let db = Database::new(DatabaseConfig::default()).unwrap_or_else(|e| {
+            warn!("History will not persist: {}", e);
+            Database::new(DatabaseConfig { path: ":memory:".into(), enable_wal: false })
+                .expect("Failed to open in-memory database")
+        });
+        let manager = Self::new(db, true);
+
+        if let Ok(dir) = Config::data_dir() {
+            let legacy = dir.join("history.json");
+            if legacy.exists() {
+                match manager.import_json(&legacy) {
+                    Ok(imported) => {
+                        match imported {
+                            Some(count) => info!("Imported {} history entries from {}", count, legacy.display()),
+                            None => info!("{} was imported before", legacy.display()),
+                        }
+                        if let Err(e) = fs::rename(&legacy, legacy.with_extension("json.imported")) {
+                            warn!("Failed to retire {}: {}", legacy.display(), e);
+                        }
+                    }
+                    Err(e) => warn!("History not imported: {}", e),
+                }
+            }
         }
 
-        let entry = HistoryEntry {
-            url: url.to_string(),
-            title: title.to_string(),
-            visited_at: Utc::now(),
-            visit_count: 1,
-        };
+        // Age the scores of places not visited since they were last stored
+        if let Err(e) = manager.db.refresh_frecency(Utc::now()) {
+            warn!("Failed to refresh frecency: {}", e);
+        }
+        manager
+    }
 
-        self.store.entries.push_front(entry);
+    /// Import a `history.json` written by earlier versions, all of it or
+    /// nothing. Returns how many entries were imported, or None if the
+    /// database already holds them.
+    pub fn import_json(&self, path: &Path) -> Result<Option<usize>, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let store: LegacyStore = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+        let places = store
+            .entries
+            .iter()
+            .map(|entry| (entry.url.as_str(), entry.title.as_str(), entry.visit_count, entry.visited_at));
+        let imported = self
+            .db
+            .import_places(LEGACY_IMPORT, places)
+            .map_err(|e| format!("Failed to import {}: {}", path.display(), e))?;
+        Ok(imported.then_some(store.entries.len()))
+    }
 
-        // Trim if over limit
-        while self.store.entries.len() > MAX_HISTORY_ENTRIES {
-            self.store.entries.pop_back();
+    /// Record a visit to `url`, reached by `transition` from `referrer`
+    pub fn add_visit(&self, url: &str, title: &str, transition: Transition, referrer: Option<&str>) -> Result<(), String> {
+        if !self.is_enabled() {
+            return Ok(());
         }
+        self.db
+            .record_visit(url, title, transition, referrer, Utc::now())
+            .map_err(|e| format!("Failed to record visit to {}: {}", url, e))
+    }
 
-        self.save().ok();
+    /// Update the title shown for `url` once the page has one
+    pub fn set_title(&self, url: &str, title: &str) -> Result<(), String> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        self.db
+            .set_place_title(url, title)
+            .map_err(|e| format!("Failed to update title of {}: {}", url, e))
     }
 
-    pub fn get_recent(&self, limit: usize) -> Vec<&HistoryEntry> {
-        self.store.entries.iter().take(limit).collect()
+    pub fn get_recent(&self, limit: usize) -> Vec<HistoryEntry> {
+        self.db.recent_history(limit).unwrap_or_else(|e| {
+            warn!("Failed to read history: {}", e);
+            Vec::new()
+        })
     }
 
-    pub fn search(&self, query: &str) -> Vec<&HistoryEntry> {
-        let query = query.to_lowercase();
-        self.store.entries.iter()
-            .filter(|e| {
-                e.url.to_lowercase().contains(&query) ||
-                e.title.to_lowercase().contains(&query)
-            })
-            .collect()
+    /// The most visited entries, by frecency
+    pub fn most_visited(&self, limit: usize) -> Vec<HistoryEntry> {
+        self.db.top_places(limit).unwrap_or_else(|e| {
+            warn!("Failed to read history: {}", e);
+            Vec::new()
+        })
     }
 
-    pub fn clear(&mut self) {
-        self.store.entries.clear();
-        self.save().ok();
+    /// Entries matching every word of `query`, highest frecency first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<HistoryEntry> {
+        self.db.search_history(query, limit).unwrap_or_else(|e| {
+            warn!("History search failed: {}", e);
+            Vec::new()
+        })
     }
 
-    pub fn remove_entry(&mut self, url: &str) {
-        self.store.entries.retain(|e| e.url != url);
-        self.save().ok();
+    pub fn clear(&self) -> Result<(), String> {
+        self.db
+            .clear_history()
+            .map_err(|e| format!("Failed to clear history: {}", e))
     }
 
-    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        if !self.enabled {
-            return Ok(());
-        }
-        
-        if let Some(parent) = self.file_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        let content = serde_json::to_string(&self.store)?;
-        fs::write(&self.file_path, content)?;
-        Ok(())
+    pub fn remove_entry(&self, url: &str) -> Result<(), String> {
+        self.db
+            .delete_history_url(url)
+            .map_err(|e| format!("Failed to remove {} from history: {}", url, e))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
     }
 
-    pub fn set_enabled(&mut self, enabled: bool) {
-        self.enabled = enabled;
-        if !enabled {
-            self.clear();
+    /// Turning history off also forgets what was recorded
+    pub fn set_enabled(&self, enabled: bool) -> Result<(), String> {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if enabled {
+            Ok(())
+        } else {
+            self.clear()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_legacy_json() {
+        let path = std::env::temp_dir().join(format!("marshall-history-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{"entries":[
+                {"url":"https://example.com/","title":"Example","visited_at":"2026-01-02T03:04:05Z","visit_count":3},
+                {"url":"https://rust-lang.org/","title":"Rust","visited_at":"2026-01-01T00:00:00Z","visit_count":1}
+            ]}"#,
+        )
+        .unwrap();
+
+        let db = Database::new(DatabaseConfig { path: ":memory:".into(), enable_wal: false }).unwrap();
+        let history = HistoryManager::new(db, true);
+        assert_eq!(history.import_json(&path).unwrap(), Some(2));
+        // A second run, as after a failed rename, adds nothing
+        assert_eq!(history.import_json(&path).unwrap(), None);
+        fs::remove_file(&path).ok();
+
+        let found = history.search("example", 10);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].visit_count, 3);
+        assert_eq!(history.get_recent(1)[0].url, "https://example.com/");
+    }
+}
@@ -18,6 +18,7 @@ mod privacy;
 mod network;
 mod tabs;
 mod bookmarks;
+mod downloads;
 mod history;
 mod adblock;
 mod config;
@@ -133,6 +134,24 @@ fn main() {
         Config::default()
     });
 
+    // Route all of Marshall's own traffic by the network settings
+    network::configure(&config.network);
+    #[cfg(feature = "tor")]
+    if config.network.tor_enabled {
+        network::tor::spawn_monitor();
+    }
+
+    // Feeds can be large and protected sites come from history, so
+    // navigations are checked without them until read
+    let security_config = config.security.clone();
+    std::thread::spawn(move || {
+        let feeds = security::threat_feeds::FeedIndex::open(&security_config.threat_feeds);
+        let protected = security::confusables::Protected::gather(&security_config.protected_domains);
+        let mut intel = security::threat_intel::ThreatIntel::global().write();
+        intel.set_feeds(feeds);
+        intel.set_protected(protected);
+    });
+
     // Display module status
     println!("\n┌─────────────────────────────────────────────┐");
     println!("│          MODULE INITIALIZATION              │");
@@ -0,0 +1,194 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! DNS-over-HTTPS resolution
+//!
+//! The well-known resolvers use trust-dns's presets. Any other server is
+//! looked up once with the system resolver to bootstrap, and queried at its
+//! `/dns-query` path, the only one trust-dns supports.
+//!
+//! Behind a proxy `query_through` asks the DoH server over the proxy
+//! instead, for names that are looked up but not connected to.
+
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+
+use base64::Engine;
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::Client;
+use trust_dns_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use trust_dns_resolver::proto::op::{Message, MessageType, OpCode, Query};
+use trust_dns_resolver::proto::rr::{Name as DnsName, RData, RecordType};
+use trust_dns_resolver::TokioAsyncResolver;
+use url::Url;
+
+/// Resolves names over HTTPS, for reqwest and for raw connections
+#[derive(Clone)]
+pub struct DohResolver {
+    resolver: TokioAsyncResolver,
+}
+
+impl DohResolver {
+    /// A resolver querying `server`, e.g. `https://cloudflare-dns.com/dns-query`
+    pub fn new(server: &str) -> Result<Self, String> {
+        let url = Url::parse(server).map_err(|e| format!("Invalid DoH server {}: {}", server, e))?;
+        if url.scheme() != "https" {
+            return Err(format!("DoH server must be https: {}", server));
+        }
+        let host = url.host_str().ok_or_else(|| format!("DoH server has no host: {}", server))?;
+
+        let config = match host {
+            "cloudflare-dns.com" | "1.1.1.1" | "1.0.0.1" => ResolverConfig::cloudflare_https(),
+            "dns.google" | "8.8.8.8" | "8.8.4.4" => ResolverConfig::google_https(),
+            "dns.quad9.net" | "9.9.9.9" | "149.112.112.112" => ResolverConfig::quad9_https(),
+            _ => {
+                let port = url.port().unwrap_or(443);
+                let ips = bootstrap(host, port)?;
+                let servers = NameServerConfigGroup::from_ips_https(&ips, port, host.to_string(), true);
+                ResolverConfig::from_parts(None, vec![], servers)
+            }
+        };
+
+        Ok(Self {
+            resolver: TokioAsyncResolver::tokio(config, ResolverOpts::default()),
+        })
+    }
+
+    /// Addresses of `host`; IP literals are returned as they are
+    pub async fn lookup(&self, host: &str) -> Result<Vec<IpAddr>, String> {
+        if let Ok(ip) = host.trim_matches(['[', ']']).parse::<IpAddr>() {
+            return Ok(vec![ip]);
+        }
+        let ips: Vec<IpAddr> = self
+            .resolver
+            .lookup_ip(host)
+            .await
+            .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+            .iter()
+            .collect();
+        if ips.is_empty() {
+            return Err(format!("No addresses for {}", host));
+        }
+        Ok(ips)
+    }
+}
+
+impl Resolve for DohResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let addrs: Addrs = Box::new(
+                resolver
+                    .lookup(name.as_str())
+                    .await?
+                    .into_iter()
+                    .map(|ip| SocketAddr::new(ip, 0)),
+            );
+            Ok(addrs)
+        })
+    }
+}
+
+/// Addresses of the DoH server itself
+fn bootstrap(host: &str, port: u16) -> Result<Vec<IpAddr>, String> {
+    if let Ok(ip) = host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        return Ok(vec![ip]);
+    }
+    let ips: Vec<IpAddr> = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve DoH server {}: {}", host, e))?
+        .map(|addr| addr.ip())
+        .collect();
+    if ips.is_empty() {
+        return Err(format!("No addresses for DoH server {}", host));
+    }
+    Ok(ips)
+}
+
+/// Addresses of `host` from the DoH `server`, asked through `client` and
+/// so through its proxy (RFC 8484 GET)
+pub async fn query_through(client: &Client, server: &str, host: &str) -> Result<Vec<IpAddr>, String> {
+    if let Ok(ip) = host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        return Ok(vec![ip]);
+    }
+    let url = Url::parse(server).map_err(|e| format!("Invalid DoH server {}: {}", server, e))?;
+    if url.scheme() != "https" {
+        return Err(format!("DoH server must be https: {}", server));
+    }
+
+    let (v4, v6) = futures::join!(
+        query_record(client, &url, host, RecordType::A),
+        query_record(client, &url, host, RecordType::AAAA),
+    );
+    let ips: Vec<IpAddr> = v4?.into_iter().chain(v6.unwrap_or_default()).collect();
+    if ips.is_empty() {
+        return Err(format!("No addresses for {}", host));
+    }
+    Ok(ips)
+}
+
+async fn query_record(client: &Client, server: &Url, host: &str, record_type: RecordType) -> Result<Vec<IpAddr>, String> {
+    let name = DnsName::from_ascii(host).map_err(|e| format!("Invalid name {}: {}", host, e))?;
+    let mut message = Message::new();
+    message
+        .set_message_type(MessageType::Query)
+        .set_op_code(OpCode::Query)
+        .set_recursion_desired(true)
+        .add_query(Query::query(name, record_type));
+    let query = message.to_vec().map_err(|e| format!("Failed to encode query for {}: {}", host, e))?;
+
+    let mut url = server.clone();
+    url.query_pairs_mut()
+        .append_pair("dns", &base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(query));
+    let response = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "application/dns-message")
+        .send()
+        .await
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to resolve {}: HTTP {}", host, response.status()));
+    }
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?;
+    addresses(&body).map_err(|e| format!("Failed to resolve {}: {}", host, e))
+}
+
+/// The A and AAAA answers of a DNS response
+fn addresses(response: &[u8]) -> Result<Vec<IpAddr>, String> {
+    let message = Message::from_vec(response).map_err(|e| format!("Malformed response: {}", e))?;
+    Ok(message
+        .answers()
+        .iter()
+        .filter_map(|record| match record.data() {
+            Some(RData::A(a)) => Some(IpAddr::V4(a.0)),
+            Some(RData::AAAA(aaaa)) => Some(IpAddr::V6(aaaa.0)),
+            _ => None,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trust_dns_resolver::proto::rr::rdata::{A, CNAME};
+    use trust_dns_resolver::proto::rr::Record;
+
+    #[test]
+    fn answers_give_addresses() {
+        let name = DnsName::from_ascii("www.example.com.").unwrap();
+        let target = DnsName::from_ascii("example.com.").unwrap();
+        let mut message = Message::new();
+        message
+            .set_message_type(MessageType::Response)
+            .add_answer(Record::from_rdata(name, 60, RData::CNAME(CNAME(target.clone()))))
+            .add_answer(Record::from_rdata(target, 60, RData::A(A::new(93, 184, 216, 34))));
+
+        let ips = addresses(&message.to_vec().unwrap()).unwrap();
+        assert_eq!(ips, ["93.184.216.34".parse::<IpAddr>().unwrap()]);
+        assert!(addresses(b"not dns").is_err());
+    }
+}
@@ -0,0 +1,161 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Raw TCP connections (WHOIS, port scans) under the same rules as HTTP:
+//! tunnelled through the proxy, which resolves the name, or resolved over
+//! DNS-over-HTTPS and connected directly
+
+use std::io::{Error, ErrorKind};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use base64::Engine;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_socks::tcp::Socks5Stream;
+
+use super::{Network, ProxyKind, Upstream};
+
+/// Connect to `host:port` the way `NetworkConfig` says. A refused
+/// connection keeps `ErrorKind::ConnectionRefused`, through a proxy too.
+pub async fn connect(host: &str, port: u16, timeout: Duration) -> std::io::Result<TcpStream> {
+    connect_with(&Network::current(), host, port, timeout).await
+}
+
+pub(super) async fn connect_with(network: &Network, host: &str, port: u16, timeout: Duration) -> std::io::Result<TcpStream> {
+    let upstream = network.upstream().map_err(Error::other)?;
+    let attempt = async {
+        match upstream {
+            Some(upstream) => through_proxy(upstream, host, port).await,
+            None => direct(network, host, port).await,
+        }
+    };
+    tokio::time::timeout(timeout, attempt)
+        .await
+        .map_err(|_| Error::new(ErrorKind::TimedOut, format!("Connecting to {}:{} timed out", host, port)))?
+}
+
+async fn direct(network: &Network, host: &str, port: u16) -> std::io::Result<TcpStream> {
+    let addrs: Vec<SocketAddr> = network
+        .lookup(host)
+        .await
+        .map_err(|e| Error::new(ErrorKind::NotFound, e))?
+        .into_iter()
+        .map(|ip| SocketAddr::new(ip, port))
+        .collect();
+
+    let mut last = Error::new(ErrorKind::NotFound, format!("No addresses for {}", host));
+    for addr in addrs {
+        match TcpStream::connect(addr).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last = e,
+        }
+    }
+    Err(last)
+}
+
+async fn through_proxy(upstream: &Upstream, host: &str, port: u16) -> std::io::Result<TcpStream> {
+    let proxy = (upstream.host.as_str(), upstream.port);
+    match upstream.kind {
+        ProxyKind::Socks5 => {
+            let stream = match &upstream.username {
+                Some(user) => {
+                    let password = upstream.password.as_deref().unwrap_or("");
+                    Socks5Stream::connect_with_password(proxy, (host, port), user, password).await
+                }
+                None => Socks5Stream::connect(proxy, (host, port)).await,
+            };
+            stream.map(Socks5Stream::into_inner).map_err(|e| match e {
+                tokio_socks::Error::Io(e) => e,
+                tokio_socks::Error::ConnectionRefused => Error::new(ErrorKind::ConnectionRefused, e),
+                e => Error::other(e),
+            })
+        }
+        ProxyKind::Http => http_connect(upstream, host, port).await,
+        ProxyKind::Https => Err(Error::new(
+            ErrorKind::Unsupported,
+            "Raw connections can't go through an HTTPS proxy",
+        )),
+    }
+}
+
+/// Tunnel with `CONNECT` through an HTTP proxy
+async fn http_connect(upstream: &Upstream, host: &str, port: u16) -> std::io::Result<TcpStream> {
+    let mut stream = TcpStream::connect((upstream.host.as_str(), upstream.port)).await?;
+
+    let target = if host.contains(':') { format!("[{}]:{}", host, port) } else { format!("{}:{}", host, port) };
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+    if let Some(user) = &upstream.username {
+        let credentials = format!("{}:{}", user, upstream.password.as_deref().unwrap_or(""));
+        request.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            base64::engine::general_purpose::STANDARD.encode(credentials)
+        ));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // Read the response head a byte at a time so nothing after it is lost
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > 8192 {
+            return Err(Error::new(ErrorKind::InvalidData, "Proxy response too long"));
+        }
+        let byte = stream.read_u8().await?;
+        head.push(byte);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let status = head.split_whitespace().nth(1).unwrap_or("");
+    match status {
+        "200" => Ok(stream),
+        // Proxies answer 502/503 when the target refuses
+        "502" | "503" => Err(Error::new(ErrorKind::ConnectionRefused, format!("Proxy: {}", head.lines().next().unwrap_or("")))),
+        _ => Err(Error::other(format!("Proxy refused tunnel: {}", head.lines().next().unwrap_or("")))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{NetworkConfig, ProxyConfig};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn tunnels_through_http_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let proxy = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = [0u8; 1024];
+            let n = socket.read(&mut buffer).await.unwrap();
+            let request = String::from_utf8_lossy(&buffer[..n]).to_string();
+            socket.write_all(b"HTTP/1.1 200 Connection established\r\n\r\nwhois banner").await.unwrap();
+            request
+        });
+
+        let network = Network::new(&NetworkConfig {
+            proxy: Some(ProxyConfig {
+                protocol: "http".to_string(),
+                host: "127.0.0.1".to_string(),
+                port,
+                username: Some("user".to_string()),
+                password: Some("secret".to_string()),
+            }),
+            tor_enabled: false,
+            tor_socks: String::new(),
+            dns_over_https: true,
+            doh_server: String::new(),
+            timeout_seconds: 5,
+        });
+        let mut stream = connect_with(&network, "whois.example", 43, Duration::from_secs(5)).await.unwrap();
+        let mut banner = String::new();
+        stream.read_to_string(&mut banner).await.unwrap();
+        assert_eq!(banner, "whois banner");
+
+        let request = proxy.await.unwrap();
+        assert!(request.starts_with("CONNECT whois.example:43 HTTP/1.1\r\n"));
+        assert!(request.contains("Proxy-Authorization: Basic dXNlcjpzZWNyZXQ=\r\n"));
+    }
+}
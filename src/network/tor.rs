@@ -0,0 +1,257 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Tor mode: every connection through Tor's SOCKS port, names resolved by
+//! Tor
+//!
+//! Tor puts streams with different SOCKS credentials on different circuits
+//! (`IsolateSOCKSAuth`, on by default), so each tab, container and
+//! Marshall itself connect with credentials of their own. Whether Tor can
+//! carry traffic yet is found by opening a stream through it.
+
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use tokio_socks::tcp::Socks5Stream;
+use tracing::{info, warn};
+
+use super::{Network, ProxyKind, Upstream};
+use crate::config::NetworkConfig;
+
+/// Opened through Tor to see whether it has a circuit
+const PROBE_TARGET: (&str, u16) = ("check.torproject.org", 443);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often Tor is probed until it connects, and after
+const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+const CONNECTED_PROBE_INTERVAL: Duration = Duration::from_secs(60);
+
+static STATUS: Lazy<RwLock<TorStatus>> = Lazy::new(|| RwLock::new(TorStatus::Starting));
+
+/// How far Tor is from carrying traffic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TorStatus {
+    /// Not probed yet
+    Starting,
+    /// The SOCKS port answers but Tor can't open streams yet
+    Bootstrapping,
+    Connected,
+    /// Nothing answers on the SOCKS port
+    Unreachable,
+}
+
+impl TorStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TorStatus::Starting => "Starting",
+            TorStatus::Bootstrapping => "Bootstrapping",
+            TorStatus::Connected => "Connected",
+            TorStatus::Unreachable => "Not running",
+        }
+    }
+}
+
+/// Tor's SOCKS port from `tor_socks`
+pub fn upstream(config: &NetworkConfig) -> Result<Upstream, String> {
+    let (host, port) = config
+        .tor_socks
+        .trim()
+        .rsplit_once(':')
+        .ok_or_else(|| format!("Tor SOCKS address needs a port: {}", config.tor_socks))?;
+    let port = port
+        .parse()
+        .map_err(|_| format!("Invalid Tor SOCKS port: {}", config.tor_socks))?;
+    Ok(Upstream {
+        kind: ProxyKind::Socks5,
+        host: host.trim_matches(['[', ']']).to_string(),
+        port,
+        username: None,
+        password: None,
+        isolating: true,
+    })
+}
+
+/// Tor's state as last probed
+pub fn status() -> TorStatus {
+    *STATUS.read()
+}
+
+/// Open a stream to `PROBE_TARGET` through `upstream`
+pub async fn probe(upstream: &Upstream) -> TorStatus {
+    let proxy = (upstream.host.as_str(), upstream.port);
+    let attempt = async {
+        match (&upstream.username, &upstream.password) {
+            (Some(user), Some(password)) => {
+                Socks5Stream::connect_with_password(proxy, PROBE_TARGET, user, password).await
+            }
+            _ => Socks5Stream::connect(proxy, PROBE_TARGET).await,
+        }
+    };
+    match tokio::time::timeout(PROBE_TIMEOUT, attempt).await {
+        Ok(Ok(_)) => TorStatus::Connected,
+        Ok(Err(tokio_socks::Error::ProxyServerUnreachable | tokio_socks::Error::Io(_))) => TorStatus::Unreachable,
+        // Tor answers, but without a circuit
+        Ok(Err(_)) | Err(_) => TorStatus::Bootstrapping,
+    }
+}
+
+/// Probe Tor on a background thread for as long as tor mode is on,
+/// keeping `status()` current
+pub fn spawn_monitor() {
+    std::thread::spawn(|| {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                warn!("Tor monitor could not start: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            let network = Network::current();
+            if !network.config.tor_enabled {
+                return;
+            }
+            let status = match network.upstream() {
+                Ok(Some(upstream)) => runtime.block_on(probe(upstream)),
+                _ => TorStatus::Unreachable,
+            };
+            let previous = std::mem::replace(&mut *STATUS.write(), status);
+            if previous != status {
+                info!("Tor: {}", status.label());
+            }
+            std::thread::sleep(if status == TorStatus::Connected {
+                CONNECTED_PROBE_INTERVAL
+            } else {
+                PROBE_INTERVAL
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::mpsc;
+
+    use super::super::tcp;
+
+    /// A SOCKS5 server that logs each request's credentials and target,
+    /// answers with `reply` and serves "ok" over HTTP when it succeeds
+    async fn stand_in(reply: u8) -> (String, mpsc::UnboundedReceiver<(String, String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    let mut head = [0u8; 2];
+                    socket.read_exact(&mut head).await.unwrap();
+                    let mut methods = vec![0u8; head[1] as usize];
+                    socket.read_exact(&mut methods).await.unwrap();
+                    let (mut user, mut password) = (String::new(), String::new());
+                    if methods.contains(&2) {
+                        socket.write_all(&[5, 2]).await.unwrap();
+                        socket.read_u8().await.unwrap();
+                        user = read_field(&mut socket).await;
+                        password = read_field(&mut socket).await;
+                        socket.write_all(&[1, 0]).await.unwrap();
+                    } else {
+                        socket.write_all(&[5, 0]).await.unwrap();
+                    }
+
+                    let mut request = [0u8; 4];
+                    socket.read_exact(&mut request).await.unwrap();
+                    let target = match request[3] {
+                        3 => {
+                            let len = socket.read_u8().await.unwrap() as usize;
+                            let mut name = vec![0u8; len];
+                            socket.read_exact(&mut name).await.unwrap();
+                            String::from_utf8(name).unwrap()
+                        }
+                        1 => {
+                            let mut ip = [0u8; 4];
+                            socket.read_exact(&mut ip).await.unwrap();
+                            std::net::Ipv4Addr::from(ip).to_string()
+                        }
+                        _ => panic!("unexpected address type"),
+                    };
+                    socket.read_u16().await.unwrap();
+                    sender.send((user, password, target)).unwrap();
+                    socket.write_all(&[5, reply, 0, 1, 0, 0, 0, 0, 0, 0]).await.unwrap();
+
+                    let mut buffer = [0u8; 1024];
+                    if reply == 0 && socket.read(&mut buffer).await.unwrap_or(0) > 0 {
+                        let _ = socket.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok").await;
+                    }
+                });
+            }
+        });
+        (address, receiver)
+    }
+
+    async fn read_field(socket: &mut TcpStream) -> String {
+        let len = socket.read_u8().await.unwrap() as usize;
+        let mut value = vec![0u8; len];
+        socket.read_exact(&mut value).await.unwrap();
+        String::from_utf8(value).unwrap()
+    }
+
+    fn tor_config(address: &str) -> NetworkConfig {
+        NetworkConfig {
+            proxy: None,
+            tor_enabled: true,
+            tor_socks: address.to_string(),
+            dns_over_https: true,
+            doh_server: "https://cloudflare-dns.com/dns-query".to_string(),
+            timeout_seconds: 5,
+        }
+    }
+
+    #[tokio::test]
+    async fn everything_goes_through_tor_with_its_own_credentials() {
+        let (address, mut log) = stand_in(0).await;
+        let network = Network::new(&tor_config(&address));
+
+        // HTTP leaves names to Tor
+        let body = network.client.clone().unwrap()
+            .get("http://duckduckgogg42xjoc72x3sjasowoarfbgcmvfimaftt6twagswzczad.onion/")
+            .send().await.unwrap()
+            .text().await.unwrap();
+        assert_eq!(body, "ok");
+        let (user, internal, target) = log.recv().await.unwrap();
+        assert_eq!(user, "marshall");
+        assert!(target.ends_with(".onion"));
+
+        // Even loopback isn't exempt, and nothing is resolved locally
+        network.client.clone().unwrap().get("http://localhost/").send().await.unwrap();
+        assert_eq!(log.recv().await.unwrap().2, "localhost");
+        assert!(network.lookup("example.com").await.is_err());
+
+        // Raw connections share Marshall's credentials; tabs get their own
+        tcp::connect_with(&network, "whois.example", 43, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(log.recv().await.unwrap().1, internal);
+        let tab = network.upstream().unwrap().unwrap().isolated("tab-1");
+        assert_eq!(probe(&tab).await, TorStatus::Connected);
+        let (_, password, target) = log.recv().await.unwrap();
+        assert_eq!((password.as_str(), target.as_str()), ("tab-1", PROBE_TARGET.0));
+        assert_ne!(password, internal);
+    }
+
+    #[tokio::test]
+    async fn probe_tells_bootstrapping_from_not_running() {
+        // General failure: Tor is up without a circuit
+        let (address, _log) = stand_in(1).await;
+        let waiting = upstream(&tor_config(&address)).unwrap().isolated("probe");
+        assert_eq!(probe(&waiting).await, TorStatus::Bootstrapping);
+
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().to_string();
+        let stopped = upstream(&tor_config(&closed)).unwrap().isolated("probe");
+        assert_eq!(probe(&stopped).await, TorStatus::Unreachable);
+    }
+}
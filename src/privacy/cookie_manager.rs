@@ -6,6 +6,8 @@
 
 use std::collections::HashMap;
 
+use crate::utils::domain;
+
 /// Cookie manager with privacy controls
 pub struct CookieManager {
     block_third_party: bool,
@@ -22,7 +24,7 @@ impl CookieManager {
 
     pub fn should_allow_cookie(&self, cookie_domain: &str, page_domain: &str) -> bool {
         // Always allow first-party cookies
-        if cookie_domain == page_domain || cookie_domain.ends_with(&format!(".{}", page_domain)) {
+        if domain::same_site(cookie_domain, page_domain) {
             return true;
         }
 
@@ -6,14 +6,18 @@
 
 use rand::Rng;
 
-/// Fingerprint protection to prevent browser identification
+use crate::security::persona::Persona;
+
+/// Fingerprint protection to prevent browser identification. The spoofed
+/// values are the session persona's.
 pub struct FingerprintProtection {
     enabled: bool,
+    persona: Persona,
 }
 
 impl FingerprintProtection {
-    pub fn new(enabled: bool) -> Self {
-        Self { enabled }
+    pub fn new(enabled: bool, persona: Persona) -> Self {
+        Self { enabled, persona }
     }
 
     /// Get a randomized canvas noise value
@@ -29,7 +33,7 @@ impl FingerprintProtection {
     /// Get a spoofed timezone offset
     pub fn timezone_offset(&self) -> i32 {
         if self.enabled {
-            0 // UTC
+            self.persona.timezone_offset()
         } else {
             // Return actual timezone
             chrono::Local::now().offset().local_minus_utc() / 60
@@ -39,8 +43,7 @@ impl FingerprintProtection {
     /// Get spoofed screen dimensions
     pub fn screen_dimensions(&self) -> (u32, u32) {
         if self.enabled {
-            // Return common resolution
-            (1920, 1080)
+            self.persona.screen
         } else {
             // Would return actual dimensions
             (1920, 1080)
@@ -50,7 +53,7 @@ impl FingerprintProtection {
     /// Get spoofed hardware concurrency
     pub fn hardware_concurrency(&self) -> u32 {
         if self.enabled {
-            4 // Common value
+            self.persona.cores
         } else {
             num_cpus::get() as u32
         }
@@ -59,7 +62,7 @@ impl FingerprintProtection {
     /// Get spoofed device memory
     pub fn device_memory(&self) -> u32 {
         if self.enabled {
-            8 // Common value (GB)
+            self.persona.memory.unwrap_or(8)
         } else {
             8 // Would detect actual
         }
@@ -7,12 +7,17 @@
 mod tracker_blocker;
 mod fingerprint_protection;
 mod cookie_manager;
+mod shields;
+mod site_profiles;
 
 pub use tracker_blocker::TrackerBlocker;
 pub use fingerprint_protection::FingerprintProtection;
 pub use cookie_manager::CookieManager;
+pub use shields::{Shields, host_of};
+pub use site_profiles::{SiteProfiles, REFERRER_POLICIES};
 
 use crate::config::PrivacyConfig;
+use crate::security::persona::Persona;
 
 /// Privacy engine that coordinates all privacy features
 pub struct PrivacyEngine {
@@ -25,8 +30,8 @@ pub struct PrivacyEngine {
 impl PrivacyEngine {
     pub fn new(config: PrivacyConfig) -> Self {
         Self {
-            tracker_blocker: TrackerBlocker::new(config.block_trackers),
-            fingerprint_protection: FingerprintProtection::new(config.block_fingerprinting),
+            tracker_blocker: TrackerBlocker::new(&config),
+            fingerprint_protection: FingerprintProtection::new(config.block_fingerprinting, Persona::session(&config)),
             cookie_manager: CookieManager::new(config.block_third_party_cookies),
             config,
         }
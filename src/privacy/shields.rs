@@ -0,0 +1,168 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Shields — the single blocking decision for every request a page makes
+
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use tracing::{info, warn};
+
+use crate::adblock::{host_matches, AdBlocker, FilterListManager, NetworkFilter};
+use crate::config::Config;
+use super::{PrivacyEngine, SiteProfiles};
+
+/// Combines the ad blocker and tracker blocker with the per-site whitelist
+pub struct Shields {
+    /// Shared with other windows once filter lists are refreshed
+    adblocker: RwLock<Arc<AdBlocker>>,
+    privacy: PrivacyEngine,
+    whitelist: RwLock<Vec<String>>,
+    /// Site profiles, some of which exempt their site
+    profiles: &'static SiteProfiles,
+    blocked_count: RwLock<u64>,
+}
+
+impl Shields {
+    pub fn new(config: &Config) -> Self {
+        let lists = FilterListManager::open(&config.adblock)
+            .map_err(|e| warn!("Filter list cache unavailable: {}", e))
+            .ok();
+        Self::with_stores(config, lists.as_ref(), SiteProfiles::global())
+    }
+
+    /// Shields with cached filter lists from `lists` and exemptions from
+    /// `profiles`, rather than the ones in the user's directories
+    pub fn with_stores(config: &Config, lists: Option<&FilterListManager>, profiles: &'static SiteProfiles) -> Self {
+        let adblocker = AdBlocker::with_lists(&config.adblock, &config.privacy.block_categories, lists);
+        let privacy = PrivacyEngine::new(config.privacy.clone());
+        info!("Shields ready — {} network rules, {} cosmetic rules",
+            adblocker.rule_count(), adblocker.cosmetic_rule_count());
+
+        Self {
+            adblocker: RwLock::new(Arc::new(adblocker)),
+            privacy,
+            whitelist: RwLock::new(config.adblock.whitelist.clone()),
+            profiles,
+            blocked_count: RwLock::new(0),
+        }
+    }
+
+    /// Decide whether a subresource request from `source_url` should be blocked
+    pub fn should_block(&self, url: &str, source_url: &str, request_type: &str) -> bool {
+        if !self.is_enabled_for(&host_of(source_url)) {
+            return false;
+        }
+
+        let blocked = self.privacy.should_block(url)
+            || self.adblocker.read().should_block(url, source_url, request_type);
+        if blocked {
+            *self.blocked_count.write() += 1;
+        }
+        blocked
+    }
+
+    /// Whether protection is on for pages on `host`, honouring both the
+    /// whitelist and site profiles that exempt the site
+    pub fn is_enabled_for(&self, host: &str) -> bool {
+        host.is_empty()
+            || (!self.whitelist.read().iter().any(|w| host_matches(host, w))
+                && !self.profiles.is_adblock_exempt(host))
+    }
+
+    /// Flip protection for `host`, returning the new state
+    pub fn toggle_host(&self, host: &str) -> bool {
+        let mut whitelist = self.whitelist.write();
+        if whitelist.iter().any(|w| host_matches(host, w)) {
+            whitelist.retain(|w| !host_matches(host, w));
+            info!("Shields enabled for {}", host);
+            true
+        } else {
+            whitelist.push(host.to_string());
+            info!("Shields disabled for {}", host);
+            false
+        }
+    }
+
+    /// Hosts protection is currently disabled for
+    pub fn whitelist(&self) -> Vec<String> {
+        self.whitelist.read().clone()
+    }
+
+    /// Element-hiding stylesheet for `host`, unless it is whitelisted
+    pub fn cosmetic_stylesheet(&self, host: &str) -> Option<String> {
+        if !self.is_enabled_for(host) {
+            return None;
+        }
+        self.adblocker.read().cosmetic_stylesheet(host)
+    }
+
+    /// All network rules as WebKit content-blocker JSON
+    pub fn content_rules_json(&self) -> String {
+        let tracker_rules = self.privacy.tracker_blocker.rules();
+        let rules: Vec<serde_json::Value> = tracker_rules
+            .iter()
+            .filter_map(|rule| NetworkFilter::parse(rule))
+            .flat_map(|filter| filter.to_content_rules())
+            .chain(self.adblocker.read().content_rules())
+            .collect();
+        serde_json::Value::Array(rules).to_string()
+    }
+
+    /// Swap in an ad blocker rebuilt from refreshed filter lists
+    pub fn set_adblocker(&self, adblocker: Arc<AdBlocker>) {
+        info!("Shields reloaded — {} network rules, {} cosmetic rules",
+            adblocker.rule_count(), adblocker.cosmetic_rule_count());
+        *self.adblocker.write() = adblocker;
+    }
+
+    /// Total requests blocked this session
+    pub fn blocked_count(&self) -> u64 {
+        *self.blocked_count.read()
+    }
+}
+
+/// Hostname of a URL, or an empty string
+pub fn host_of(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_ascii_lowercase()))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AD: &str = "https://doubleclick.net/ad.js";
+    const TRACKER: &str = "https://www.google-analytics.com/analytics.js";
+    const PAGE: &str = "https://news.example/";
+
+    fn blocks(config: &Config) -> (bool, bool) {
+        let profiles: &'static SiteProfiles = Box::leak(Box::new(SiteProfiles::new(None)));
+        let shields = Shields::with_stores(config, None, profiles);
+        (shields.should_block(AD, PAGE, "script"), shields.should_block(TRACKER, PAGE, "script"))
+    }
+
+    #[test]
+    fn ad_and_tracker_flags_are_independent() {
+        let mut config = Config::default();
+        assert_eq!(blocks(&config), (true, true));
+
+        config.privacy.block_trackers = false;
+        assert_eq!(blocks(&config), (true, false));
+
+        config.privacy.block_trackers = true;
+        config.adblock.enabled = false;
+        assert_eq!(blocks(&config), (false, true));
+
+        config.adblock.enabled = true;
+        config.privacy.block_categories.ads = false;
+        assert_eq!(blocks(&config), (false, true));
+
+        config.privacy.block_categories.ads = true;
+        config.privacy.block_categories.analytics = false;
+        assert_eq!(blocks(&config), (true, false));
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Site profile store — per-origin overrides cached in memory, persisted in SQLite
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use tracing::warn;
+
+use crate::database::{CookieSetting, Database, DatabaseConfig, SiteProfile};
+
+static STORE: Lazy<SiteProfiles> = Lazy::new(SiteProfiles::open);
+
+/// Referrer policies a profile may set
+pub const REFERRER_POLICIES: &[&str] = &[
+    "no-referrer",
+    "no-referrer-when-downgrade",
+    "origin",
+    "origin-when-cross-origin",
+    "same-origin",
+    "strict-origin",
+    "strict-origin-when-cross-origin",
+    "unsafe-url",
+];
+
+/// All site profiles, keyed by origin
+pub struct SiteProfiles {
+    db: Option<Database>,
+    profiles: RwLock<HashMap<String, SiteProfile>>,
+}
+
+impl SiteProfiles {
+    /// The store shared by every window
+    pub fn global() -> &'static SiteProfiles {
+        &STORE
+    }
+
+    /// Load profiles from `db`; without a database profiles last for the session only
+    pub fn new(db: Option<Database>) -> Self {
+        let profiles = db
+            .as_ref()
+            .map(|db| db.site_profiles().unwrap_or_else(|e| {
+                warn!("Failed to load site profiles: {}", e);
+                Vec::new()
+            }))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| (p.origin.clone(), p))
+            .collect();
+
+        Self {
+            db,
+            profiles: RwLock::new(profiles),
+        }
+    }
+
+    fn open() -> Self {
+        let db = Database::new(DatabaseConfig::default())
+            .map_err(|e| warn!("Site profiles will not persist: {}", e))
+            .ok();
+        Self::new(db)
+    }
+
+    pub fn get(&self, origin: &str) -> Option<SiteProfile> {
+        self.profiles.read().get(origin).cloned()
+    }
+
+    /// Profile for the origin of `url`
+    pub fn for_url(&self, url: &str) -> Option<SiteProfile> {
+        SiteProfile::origin_of(url).and_then(|origin| self.get(&origin))
+    }
+
+    pub fn all(&self) -> Vec<SiteProfile> {
+        let mut profiles: Vec<SiteProfile> = self.profiles.read().values().cloned().collect();
+        profiles.sort_by(|a, b| a.origin.cmp(&b.origin));
+        profiles
+    }
+
+    /// Store a profile; one that overrides nothing is removed instead
+    pub fn save(&self, profile: SiteProfile) -> Result<(), String> {
+        if profile.is_default() {
+            return self.remove(&profile.origin);
+        }
+        if let Some(db) = &self.db {
+            db.save_site_profile(&profile)
+                .map_err(|e| format!("Failed to save profile for {}: {}", profile.origin, e))?;
+        }
+        self.profiles.write().insert(profile.origin.clone(), profile);
+        Ok(())
+    }
+
+    pub fn remove(&self, origin: &str) -> Result<(), String> {
+        if let Some(db) = &self.db {
+            db.delete_site_profile(origin)
+                .map_err(|e| format!("Failed to delete profile for {}: {}", origin, e))?;
+        }
+        self.profiles.write().remove(origin);
+        Ok(())
+    }
+
+    /// Apply a submitted `marshall://privacy/site?...` form: save the profile
+    /// it describes, or remove it when `action=delete`
+    pub fn apply_form(&self, query: &str) -> Result<(), String> {
+        let fields: HashMap<String, String> = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        let field = |name: &str| fields.get(name).map(|v| v.trim()).filter(|v| !v.is_empty() && *v != "default");
+        let toggle = |name: &str| field(name).map(|v| v == "on");
+
+        let origin = field("origin").ok_or("No site given")?;
+        let origin = if origin.contains("://") { origin.to_string() } else { format!("https://{}", origin) };
+        let origin = SiteProfile::origin_of(&origin).ok_or_else(|| format!("Not a web origin: {}", origin))?;
+
+        if field("action") == Some("delete") {
+            return self.remove(&origin);
+        }
+
+        self.save(SiteProfile {
+            javascript: toggle("javascript"),
+            cookies: field("cookies").and_then(CookieSetting::parse),
+            images: toggle("images"),
+            webgl: toggle("webgl"),
+            fingerprint_protection: toggle("fingerprint_protection"),
+            user_agent: field("user_agent").map(str::to_string),
+            referrer_policy: field("referrer_policy")
+                .filter(|p| REFERRER_POLICIES.contains(p))
+                .map(str::to_string),
+            adblock_exempt: fields.contains_key("adblock_exempt"),
+            origin,
+        })
+    }
+
+    /// Whether a profile exempts pages on `host` from ad and tracker blocking
+    pub fn is_adblock_exempt(&self, host: &str) -> bool {
+        self.profiles
+            .read()
+            .values()
+            .any(|p| p.adblock_exempt && p.host().as_deref() == Some(host))
+    }
+
+    /// URL patterns, e.g. `https://example.com/*`, of origins whose profile
+    /// turns fingerprint protection off. WebKit patterns have no port, so
+    /// every port on the host is exempt.
+    pub fn fingerprint_exempt_patterns(&self) -> Vec<String> {
+        self.profiles
+            .read()
+            .values()
+            .filter(|p| p.fingerprint_protection == Some(false))
+            .filter_map(|p| {
+                let url = url::Url::parse(&p.origin).ok()?;
+                Some(format!("{}://{}/*", url.scheme(), url.host_str()?))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn form_saves_and_deletes_profiles() {
+        let store = SiteProfiles::new(None);
+        store.apply_form("origin=Example.com&javascript=off&cookies=none&images=default&referrer_policy=no-referrer&adblock_exempt=on").unwrap();
+
+        let profile = store.get("https://example.com").unwrap();
+        assert_eq!(profile.javascript, Some(false));
+        assert_eq!(profile.cookies, Some(CookieSetting::None));
+        assert_eq!(profile.images, None);
+        assert_eq!(profile.referrer_policy.as_deref(), Some("no-referrer"));
+        assert!(store.is_adblock_exempt("example.com"));
+        assert!(store.fingerprint_exempt_patterns().is_empty());
+        store.apply_form("origin=http://example.org:8080&fingerprint_protection=off").unwrap();
+        assert_eq!(store.fingerprint_exempt_patterns(), vec!["http://example.org/*"]);
+        store.apply_form("origin=http://example.org:8080&action=delete").unwrap();
+
+        store.apply_form("origin=https%3A%2F%2Fexample.com&action=delete").unwrap();
+        assert!(store.all().is_empty());
+    }
+}
@@ -4,75 +4,60 @@
 
 //! Tracker blocking
 
-use std::collections::HashSet;
-use once_cell::sync::Lazy;
+use crate::adblock::{BlockCategory, Blocklist};
+use crate::config::PrivacyConfig;
 
-/// Known tracking domains
-static TRACKING_DOMAINS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
-    let mut set = HashSet::new();
-    // Major trackers
-    set.insert("google-analytics.com");
-    set.insert("googletagmanager.com");
-    set.insert("doubleclick.net");
-    set.insert("facebook.com/tr");
-    set.insert("connect.facebook.net");
-    set.insert("pixel.facebook.com");
-    set.insert("analytics.twitter.com");
-    set.insert("ads.twitter.com");
-    set.insert("scorecardresearch.com");
-    set.insert("quantserve.com");
-    set.insert("hotjar.com");
-    set.insert("fullstory.com");
-    set.insert("mixpanel.com");
-    set.insert("segment.io");
-    set.insert("amplitude.com");
-    set.insert("newrelic.com");
-    set.insert("bugsnag.com");
-    set.insert("sentry.io");
-    set.insert("crashlytics.com");
-    set
-});
-
-/// Tracker blocker
+/// Tracker blocker backed by the built-in categorized blocklist. Ad
+/// domains are left to the ad blocker.
 pub struct TrackerBlocker {
     enabled: bool,
     blocked_count: u64,
-    custom_blocklist: HashSet<String>,
+    blocklist: Blocklist,
 }
 
 impl TrackerBlocker {
-    pub fn new(enabled: bool) -> Self {
+    pub fn new(config: &PrivacyConfig) -> Self {
         Self {
-            enabled,
+            enabled: config.block_trackers,
             blocked_count: 0,
-            custom_blocklist: HashSet::new(),
+            blocklist: Blocklist::new(&Self::categories(config)),
         }
     }
 
+    /// Enabled categories other than `Ads`
+    fn categories(config: &PrivacyConfig) -> Vec<BlockCategory> {
+        let mut categories = config.block_categories.enabled();
+        categories.retain(|c| *c != BlockCategory::Ads);
+        categories
+    }
+
     pub fn should_block(&self, url: &str) -> bool {
-        if !self.enabled {
-            return false;
-        }
+        self.category_of(url).is_some()
+    }
 
-        // Check against known trackers
-        for domain in TRACKING_DOMAINS.iter() {
-            if url.contains(domain) {
-                return true;
-            }
+    /// Category of the blocklist entry matching `url`, if it is blocked
+    pub fn category_of(&self, url: &str) -> Option<BlockCategory> {
+        if !self.enabled {
+            return None;
         }
+        self.blocklist.matches(url)
+    }
 
-        // Check custom blocklist
-        for pattern in &self.custom_blocklist {
-            if url.contains(pattern) {
-                return true;
-            }
+    /// Enabled blocklist entries as ABP network rules
+    pub fn rules(&self) -> Vec<String> {
+        if !self.enabled {
+            return Vec::new();
         }
-
-        false
+        self.blocklist.rules()
     }
 
+    /// Block a `domain` or `domain/path` as a tracker
     pub fn add_to_blocklist(&mut self, pattern: String) {
-        self.custom_blocklist.insert(pattern);
+        self.blocklist.add(&pattern, BlockCategory::Analytics);
+    }
+
+    pub fn blocklist(&self) -> &Blocklist {
+        &self.blocklist
     }
 
     pub fn increment_blocked(&mut self) {
@@ -10,6 +10,7 @@ pub mod whois;
 pub mod portscan;
 pub mod vulnerability;
 pub mod osint;
+pub mod typosquat;
 
 use std::sync::Arc;
 use parking_lot::RwLock;
@@ -21,6 +22,7 @@ pub use whois::*;
 pub use portscan::*;
 pub use vulnerability::*;
 pub use osint::*;
+pub use typosquat::*;
 
 /// Search configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
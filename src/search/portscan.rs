@@ -2,9 +2,12 @@
 // OSINT Search - Port Scanner
 
 use serde::{Deserialize, Serialize};
-use std::net::{TcpStream, ToSocketAddrs};
 use std::time::Duration;
 use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::network;
 
 /// Information about an open port
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -148,22 +151,11 @@ impl PortScanner {
     /// Scan common ports on a target
     pub async fn scan(&self, target: &str) -> Result<Vec<PortInfo>, String> {
         let mut results = Vec::new();
-        
-        // Resolve hostname first
-        let addr = format!("{}:80", target);
-        let socket_addrs: Vec<_> = addr.to_socket_addrs()
-            .map_err(|e| format!("Failed to resolve {}: {}", target, e))?
-            .collect();
-        
-        if socket_addrs.is_empty() {
-            return Err(format!("Could not resolve hostname: {}", target));
-        }
-
-        let ip = socket_addrs[0].ip();
+        let (host, timeout) = self.target(target).await?;
 
         // Scan common ports
         for (port, service, risk_level) in COMMON_PORTS {
-            let port_info = self.scan_port(ip.to_string().as_str(), *port, service, risk_level.clone());
+            let port_info = self.scan_port(&host, *port, service, risk_level.clone(), timeout).await;
             if port_info.state == PortState::Open {
                 results.push(port_info);
             }
@@ -175,16 +167,10 @@ impl PortScanner {
     /// Scan all extended ports
     pub async fn scan_extended(&self, target: &str) -> Result<Vec<PortInfo>, String> {
         let mut results = self.scan(target).await?;
-        
-        let addr = format!("{}:80", target);
-        let socket_addrs: Vec<_> = addr.to_socket_addrs()
-            .map_err(|_| "Resolution failed".to_string())?
-            .collect();
-        
-        let ip = socket_addrs[0].ip();
+        let (host, timeout) = self.target(target).await?;
 
         for (port, service) in EXTENDED_PORTS {
-            let port_info = self.scan_port(&ip.to_string(), *port, service, RiskLevel::Medium);
+            let port_info = self.scan_port(&host, *port, service, RiskLevel::Medium, timeout).await;
             if port_info.state == PortState::Open {
                 results.push(port_info);
             }
@@ -197,17 +183,11 @@ impl PortScanner {
     /// Scan specific port range
     pub async fn scan_range(&self, target: &str, start: u16, end: u16) -> Result<Vec<PortInfo>, String> {
         let mut results = Vec::new();
-        
-        let addr = format!("{}:80", target);
-        let socket_addrs: Vec<_> = addr.to_socket_addrs()
-            .map_err(|_| "Resolution failed".to_string())?
-            .collect();
-        
-        let ip = socket_addrs[0].ip();
+        let (host, timeout) = self.target(target).await?;
 
         for port in start..=end {
             let service = self.identify_service(port);
-            let port_info = self.scan_port(&ip.to_string(), port, &service, RiskLevel::Medium);
+            let port_info = self.scan_port(&host, port, &service, RiskLevel::Medium, timeout).await;
             if port_info.state == PortState::Open {
                 results.push(port_info);
             }
@@ -216,17 +196,24 @@ impl PortScanner {
         Ok(results)
     }
 
+    /// The host to probe and how long to wait for each port. Resolved once
+    /// up front, unless a proxy resolves it; proxied connects are slower.
+    async fn target(&self, target: &str) -> Result<(String, Duration), String> {
+        match network::resolve(target).await? {
+            Some(ips) => {
+                let ip = ips.first().ok_or_else(|| format!("Could not resolve hostname: {}", target))?;
+                Ok((ip.to_string(), Duration::from_millis(500)))
+            }
+            None => Ok((target.to_string(), self.timeout)),
+        }
+    }
+
     /// Scan a single port
-    fn scan_port(&self, ip: &str, port: u16, service: &str, risk_level: RiskLevel) -> PortInfo {
-        let addr = format!("{}:{}", ip, port);
-        
-        let state = match TcpStream::connect_timeout(
-            &addr.parse().unwrap(),
-            Duration::from_millis(500),
-        ) {
+    async fn scan_port(&self, host: &str, port: u16, service: &str, risk_level: RiskLevel, timeout: Duration) -> PortInfo {
+        let state = match network::connect(host, port, timeout).await {
             Ok(mut stream) => {
                 // Try to grab banner
-                let banner = self.grab_banner(&mut stream, port);
+                let banner = self.grab_banner(&mut stream, port).await;
                 return PortInfo {
                     port,
                     service: service.to_string(),
@@ -256,11 +243,8 @@ impl PortScanner {
     }
 
     /// Try to grab a service banner
-    fn grab_banner(&self, stream: &mut TcpStream, port: u16) -> Option<String> {
-        use std::io::{Read, Write};
-        
-        stream.set_read_timeout(Some(Duration::from_secs(2))).ok()?;
-        stream.set_write_timeout(Some(Duration::from_secs(2))).ok()?;
+    async fn grab_banner(&self, stream: &mut TcpStream, port: u16) -> Option<String> {
+        let wait = Duration::from_secs(2);
 
         // Send appropriate probe based on port
         let probe = match port {
@@ -271,12 +255,12 @@ impl PortScanner {
         };
 
         if !probe.is_empty() {
-            stream.write_all(&probe).ok()?;
+            tokio::time::timeout(wait, stream.write_all(&probe)).await.ok()?.ok()?;
         }
 
         let mut buffer = [0u8; 1024];
-        match stream.read(&mut buffer) {
-            Ok(n) if n > 0 => {
+        match tokio::time::timeout(wait, stream.read(&mut buffer)).await {
+            Ok(Ok(n)) if n > 0 => {
                 let banner = String::from_utf8_lossy(&buffer[..n])
                     .trim()
                     .chars()
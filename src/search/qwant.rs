@@ -2,11 +2,15 @@
 // OSINT Search - Qwant API Client
 
 use serde::{Deserialize, Serialize};
-use reqwest::Client;
+use reqwest::RequestBuilder;
+
+use crate::network;
+use crate::utils::domain;
+
+const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; rv:128.0) Gecko/20100101 Firefox/128.0";
 
 /// Qwant API client for privacy-respecting search
 pub struct QwantClient {
-    client: Client,
     region: String,
     base_url: String,
 }
@@ -14,15 +18,19 @@ pub struct QwantClient {
 impl QwantClient {
     pub fn new(region: &str) -> Self {
         Self {
-            client: Client::builder()
-                .user_agent("Mozilla/5.0 (Windows NT 10.0; rv:128.0) Gecko/20100101 Firefox/128.0")
-                .build()
-                .unwrap_or_default(),
             region: region.to_string(),
             base_url: "https://api.qwant.com/v3/search/web".to_string(),
         }
     }
 
+    /// A JSON request through the shared client
+    fn get(&self, url: &str) -> Result<RequestBuilder, String> {
+        Ok(network::client()?
+            .get(url)
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .header("Accept", "application/json"))
+    }
+
     /// Search Qwant and return parsed results
     pub async fn search(&self, query: &str, count: usize) -> Result<Vec<super::WebResult>, String> {
         let url = format!(
@@ -33,9 +41,7 @@ impl QwantClient {
             self.region
         );
 
-        let response = self.client
-            .get(&url)
-            .header("Accept", "application/json")
+        let response = self.get(&url)?
             .send()
             .await
             .map_err(|e| format!("Request failed: {}", e))?;
@@ -85,9 +91,7 @@ impl QwantClient {
             self.region
         );
 
-        let response = self.client
-            .get(&url)
-            .header("Accept", "application/json")
+        let response = self.get(&url)?
             .send()
             .await
             .map_err(|e| format!("Request failed: {}", e))?;
@@ -109,9 +113,7 @@ impl QwantClient {
             self.region
         );
 
-        let response = self.client
-            .get(&url)
-            .header("Accept", "application/json")
+        let response = self.get(&url)?
             .send()
             .await
             .map_err(|e| format!("Request failed: {}", e))?;
@@ -127,9 +129,7 @@ impl QwantClient {
 
 /// Extract domain from URL
 fn extract_domain(url: &str) -> String {
-    url::Url::parse(url)
-        .map(|u| u.host_str().unwrap_or("unknown").to_string())
-        .unwrap_or_else(|_| "unknown".to_string())
+    domain::host(url).unwrap_or_else(|| "unknown".to_string())
 }
 
 // Qwant API Response structures
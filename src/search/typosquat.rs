@@ -0,0 +1,241 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Typosquat and lookalike domains of a monitored domain
+//!
+//! Permutations are made from the registrable domain's name, the part a
+//! squatter would register: `login.example.co.uk` gives `exmple.co.uk`,
+//! `example.com` and so on. Homoglyphs come from the same table the threat
+//! intel matches lookalikes against. Each candidate is then resolved, and
+//! looked up in WHOIS when it resolves, to see who got there first.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use super::WhoisLookup;
+use crate::network::Network;
+use crate::security::confusables;
+use crate::utils::domain;
+
+/// Suffixes tried by `Technique::TldSwap`
+const SWAP_TLDS: &[&str] = &[
+    "com", "net", "org", "info", "biz", "co", "io", "app", "xyz", "online",
+    "site", "top", "shop", "live", "us", "uk", "co.uk", "de", "cn", "ru",
+];
+
+/// Candidates resolved and looked up at the same time
+const CONCURRENT_CHECKS: usize = 16;
+
+/// How a permutation was made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Technique {
+    Omission,
+    Transposition,
+    BitFlip,
+    Homoglyph,
+    TldSwap,
+    Hyphenation,
+    SubdomainInsertion,
+}
+
+impl Technique {
+    pub const ALL: [Technique; 7] = [
+        Technique::Omission,
+        Technique::Transposition,
+        Technique::BitFlip,
+        Technique::Homoglyph,
+        Technique::TldSwap,
+        Technique::Hyphenation,
+        Technique::SubdomainInsertion,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Technique::Omission => "Omission",
+            Technique::Transposition => "Transposition",
+            Technique::BitFlip => "Bit-flip",
+            Technique::Homoglyph => "Homoglyph",
+            Technique::TldSwap => "TLD swap",
+            Technique::Hyphenation => "Hyphenation",
+            Technique::SubdomainInsertion => "Subdomain insertion",
+        }
+    }
+}
+
+/// A lookalike of the monitored domain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Permutation {
+    /// As queried, with `xn--` labels
+    pub domain: String,
+    /// As shown, decoded
+    pub display: String,
+    pub technique: Technique,
+}
+
+/// A permutation with what DNS and WHOIS say about it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lookalike {
+    #[serde(flatten)]
+    pub permutation: Permutation,
+    pub addresses: Vec<String>,
+    pub created: Option<DateTime<Utc>>,
+    pub registrar: Option<String>,
+    /// Why WHOIS had no answer for a domain that resolves
+    pub error: Option<String>,
+}
+
+impl Lookalike {
+    pub fn resolves(&self) -> bool {
+        !self.addresses.is_empty()
+    }
+}
+
+/// Typosquat permutations of `target`'s registrable domain, each domain once
+pub fn permutations(target: &str) -> Result<Vec<Permutation>, String> {
+    let host = domain::host(target).ok_or_else(|| format!("Not a domain name: {}", target))?;
+    let registered = domain::registrable_domain(&host)
+        .ok_or_else(|| format!("{} has no registrable domain", host))?;
+    let suffix = domain::effective_tld(&registered).unwrap_or_default();
+    let name = registered
+        .strip_suffix(&format!(".{}", suffix))
+        .ok_or_else(|| format!("{} has no name before its suffix", registered))?;
+    let name: Vec<char> = confusables::decode(name)?.chars().collect();
+
+    let mut variants: Vec<(String, Technique)> = Vec::new();
+    let mut add = |label: String, technique: Technique| variants.push((format!("{}.{}", label, suffix), technique));
+
+    for i in 0..name.len() {
+        let mut omitted = name.clone();
+        omitted.remove(i);
+        add(omitted.iter().collect(), Technique::Omission);
+    }
+    for i in 1..name.len() {
+        let mut swapped = name.clone();
+        swapped.swap(i - 1, i);
+        add(swapped.iter().collect(), Technique::Transposition);
+    }
+    for (i, c) in name.iter().enumerate().filter(|(_, c)| c.is_ascii()) {
+        for bit in 0..8 {
+            let flipped = (*c as u8 ^ (1 << bit)) as char;
+            if flipped.is_ascii_lowercase() || flipped.is_ascii_digit() || flipped == '-' {
+                add(replaced(&name, i, &flipped.to_string()), Technique::BitFlip);
+            }
+        }
+    }
+    for (i, c) in name.iter().enumerate() {
+        for lookalike in confusables::homoglyphs(*c) {
+            add(replaced(&name, i, lookalike), Technique::Homoglyph);
+        }
+    }
+    for i in 1..name.len() {
+        let (before, after) = name.split_at(i);
+        let (before, after): (String, String) = (before.iter().collect(), after.iter().collect());
+        add(format!("{}-{}", before, after), Technique::Hyphenation);
+        add(format!("{}.{}", before, after), Technique::SubdomainInsertion);
+    }
+    let name: String = name.iter().collect();
+    for tld in SWAP_TLDS.iter().filter(|tld| **tld != suffix) {
+        variants.push((format!("{}.{}", name, tld), Technique::TldSwap));
+    }
+
+    let mut seen = HashSet::from([registered]);
+    Ok(variants
+        .into_iter()
+        .filter_map(|(display, technique)| {
+            let ascii = idna::domain_to_ascii(&display).ok()?;
+            let valid = ascii.split('.').all(|label| {
+                !label.is_empty() && label.len() <= 63 && !label.starts_with('-') && !label.ends_with('-')
+            });
+            (valid && seen.insert(ascii.clone())).then_some(Permutation { domain: ascii, display, technique })
+        })
+        .collect())
+}
+
+fn replaced(name: &[char], i: usize, with: &str) -> String {
+    let mut label: String = name[..i].iter().collect();
+    label.push_str(with);
+    label.extend(&name[i + 1..]);
+    label
+}
+
+/// Resolve `permutation`, and ask WHOIS when and where it was registered
+/// if it does. Most candidates are unregistered, so they go no further.
+pub async fn check(permutation: Permutation, network: &Network, whois: &WhoisLookup) -> Lookalike {
+    let addresses: Vec<String> = network
+        .query(&permutation.domain)
+        .await
+        .map(|ips| ips.iter().map(|ip| ip.to_string()).collect())
+        .unwrap_or_default();
+    let mut lookalike = Lookalike {
+        permutation,
+        addresses,
+        created: None,
+        registrar: None,
+        error: None,
+    };
+    if lookalike.resolves() {
+        match whois.lookup(&lookalike.permutation.domain).await {
+            Ok(info) => {
+                lookalike.created = info.creation_date;
+                lookalike.registrar = info.registrar;
+            }
+            Err(e) => lookalike.error = Some(e),
+        }
+    }
+    lookalike
+}
+
+/// Check every permutation, a few at a time, handing each to `checked` as
+/// it finishes
+pub async fn check_all(permutations: Vec<Permutation>, network: &Network, mut checked: impl FnMut(Lookalike)) {
+    let whois = WhoisLookup::new();
+    futures::stream::iter(permutations)
+        .map(|permutation| check(permutation, network, &whois))
+        .buffer_unordered(CONCURRENT_CHECKS)
+        .for_each(|lookalike| {
+            checked(lookalike);
+            futures::future::ready(())
+        })
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn made_by(found: &[Permutation], domain: &str) -> Option<Technique> {
+        found.iter().find(|p| p.domain == domain).map(|p| p.technique)
+    }
+
+    #[test]
+    fn permutes_the_registrable_name() {
+        let found = permutations("https://login.paypal.com/signin").unwrap();
+        assert_eq!(made_by(&found, "paypl.com"), Some(Technique::Omission));
+        assert_eq!(made_by(&found, "papyal.com"), Some(Technique::Transposition));
+        assert_eq!(made_by(&found, "qaypal.com"), Some(Technique::BitFlip));
+        assert_eq!(made_by(&found, "paypa1.com"), Some(Technique::Homoglyph));
+        assert_eq!(made_by(&found, "xn--pypal-4ve.com"), Some(Technique::Homoglyph));
+        assert_eq!(made_by(&found, "paypal.net"), Some(Technique::TldSwap));
+        assert_eq!(made_by(&found, "pay-pal.com"), Some(Technique::Hyphenation));
+        assert_eq!(made_by(&found, "pay.pal.com"), Some(Technique::SubdomainInsertion));
+
+        assert!(made_by(&found, "paypal.com").is_none());
+        assert!(found.iter().all(|p| !p.domain.contains("-.") && !p.domain.starts_with('-')));
+        let unique: HashSet<&str> = found.iter().map(|p| p.domain.as_str()).collect();
+        assert_eq!(unique.len(), found.len());
+    }
+
+    #[test]
+    fn keeps_multi_label_suffixes() {
+        let found = permutations("example.co.uk").unwrap();
+        assert_eq!(made_by(&found, "exmple.co.uk"), Some(Technique::Omission));
+        assert_eq!(made_by(&found, "example.com"), Some(Technique::TldSwap));
+        assert!(made_by(&found, "example.co.uk").is_none());
+        assert!(permutations("192.168.1.1").is_err());
+        assert!(permutations("co.uk").is_err());
+    }
+}
@@ -3,9 +3,11 @@
 
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
-use std::net::TcpStream;
-use std::io::{Read, Write};
 use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::network;
+use crate::utils::domain;
 
 /// WHOIS information for a domain
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,19 +49,28 @@ impl WhoisLookup {
         }
     }
 
-    /// Lookup WHOIS information for a domain
+    /// Lookup WHOIS information for a domain. Registries only know the
+    /// registered domain, so `www.example.co.uk` is looked up as
+    /// `example.co.uk`.
     pub async fn lookup(&self, domain: &str) -> Result<WhoisInfo, String> {
-        let tld = domain.rsplit('.').next().unwrap_or("com");
-        let whois_server = self.get_whois_server(tld);
+        let host = domain::host(domain).ok_or_else(|| format!("Not a domain name: {}", domain))?;
+        if domain::is_ip_literal(&host) {
+            return Err(format!("{} is an address, not a domain name", host));
+        }
+        let registered = domain::icann_registrable_domain(&host).unwrap_or(host);
+        let tld = domain::icann_tld(&registered).unwrap_or_default();
+        let whois_server = self.get_whois_server(&tld);
 
-        let raw_response = self.query_whois(&whois_server, domain)?;
-        let info = self.parse_whois(&raw_response, domain);
+        let raw_response = self.query_whois(&whois_server, &registered).await?;
+        let info = self.parse_whois(&raw_response, &registered);
 
         Ok(info)
     }
 
-    /// Get appropriate WHOIS server for TLD
+    /// Get appropriate WHOIS server for a public suffix. Registries run
+    /// one per top-level domain, so `co.uk` is asked at `.uk`'s.
     fn get_whois_server(&self, tld: &str) -> String {
+        let tld = tld.rsplit('.').next().unwrap_or(tld);
         match tld.to_lowercase().as_str() {
             "com" | "net" => "whois.verisign-grs.com".to_string(),
             "org" => "whois.pir.org".to_string(),
@@ -69,7 +80,7 @@ impl WhoisLookup {
             "me" => "whois.nic.me".to_string(),
             "biz" => "whois.biz".to_string(),
             "us" => "whois.nic.us".to_string(),
-            "uk" | "co.uk" => "whois.nic.uk".to_string(),
+            "uk" => "whois.nic.uk".to_string(),
             "de" => "whois.denic.de".to_string(),
             "fr" => "whois.afnic.fr".to_string(),
             "nl" => "whois.sidn.nl".to_string(),
@@ -87,23 +98,20 @@ impl WhoisLookup {
     }
 
     /// Query WHOIS server
-    fn query_whois(&self, server: &str, domain: &str) -> Result<String, String> {
-        let addr = format!("{}:43", server);
-        
-        let mut stream = TcpStream::connect_timeout(
-            &addr.parse().map_err(|e| format!("Invalid address: {}", e))?,
-            self.timeout,
-        ).map_err(|e| format!("Connection failed: {}", e))?;
-
-        stream.set_read_timeout(Some(self.timeout))
-            .map_err(|e| format!("Failed to set timeout: {}", e))?;
+    async fn query_whois(&self, server: &str, domain: &str) -> Result<String, String> {
+        let mut stream = network::connect(server, 43, self.timeout)
+            .await
+            .map_err(|e| format!("Connection failed: {}", e))?;
 
         let query = format!("{}\r\n", domain);
         stream.write_all(query.as_bytes())
+            .await
             .map_err(|e| format!("Write failed: {}", e))?;
 
         let mut response = String::new();
-        stream.read_to_string(&mut response)
+        tokio::time::timeout(self.timeout, stream.read_to_string(&mut response))
+            .await
+            .map_err(|_| "Read timed out".to_string())?
             .map_err(|e| format!("Read failed: {}", e))?;
 
         Ok(response)
@@ -257,6 +265,8 @@ mod tests {
         assert_eq!(lookup.get_whois_server("com"), "whois.verisign-grs.com");
         assert_eq!(lookup.get_whois_server("org"), "whois.pir.org");
         assert_eq!(lookup.get_whois_server("io"), "whois.nic.io");
+        assert_eq!(lookup.get_whois_server("co.uk"), "whois.nic.uk");
+        assert_eq!(lookup.get_whois_server("com.au"), "whois.auda.org.au");
     }
 
     #[test]
@@ -1,73 +1,34 @@
 //! Anti-fingerprinting protections
-//! Rotates user agents, spoofs headers, and normalises OS/hardware signals
+//! Presents the session's persona in headers, and normalises OS/hardware
+//! signals to match it
 
-use rand::Rng;
+use super::persona::Persona;
+use super::SecurityConfig;
 
-/// Common screen resolutions to randomise per session
-const COMMON_RESOLUTIONS: &[(u32, u32)] = &[
+/// Common screen resolutions personas pick from
+pub const COMMON_RESOLUTIONS: &[(u32, u32)] = &[
     (1920, 1080), (2560, 1440), (1366, 768), (1536, 864),
     (1440, 900), (1680, 1050), (1280, 720), (1600, 900),
 ];
 
-/// Hardware concurrency values typical of mid-range to high-end machines
-const COMMON_CORE_COUNTS: &[u32] = &[4, 6, 8, 12, 16];
+/// Hardware concurrency values typical of mid-range to high-end machines,
+/// which personas pick from
+pub const COMMON_CORE_COUNTS: &[u32] = &[4, 6, 8, 12, 16];
 
+/// Presents a `Persona` to pages, in headers and in scripts
 pub struct AntiFingerprintEngine {
-    user_agent: String,
-    accept_language: String,
-    screen_resolution: (u32, u32),
-    hardware_concurrency: u32,
-    timezone_offset: i32,
+    persona: Persona,
 }
 
 impl AntiFingerprintEngine {
-    pub fn new() -> Self {
-        let mut rng = rand::thread_rng();
-        let res = COMMON_RESOLUTIONS[rng.gen_range(0..COMMON_RESOLUTIONS.len())];
-        let cores = COMMON_CORE_COUNTS[rng.gen_range(0..COMMON_CORE_COUNTS.len())];
-
-        Self {
-            user_agent: Self::generate_user_agent(),
-            accept_language: "en-US,en;q=0.9".into(),
-            screen_resolution: res,
-            hardware_concurrency: cores,
-            timezone_offset: 0,
-        }
-    }
-    
-    fn generate_user_agent() -> String {
-        // Rotate between common, high-entropy user agents across OS/browser combos
-        let agents = [
-            // Chrome – Windows
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/123.0.0.0 Safari/537.36",
-            // Chrome – macOS
-            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
-            "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_4) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
-            // Chrome – Linux
-            "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
-            // Firefox – Windows
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:125.0) Gecko/20100101 Firefox/125.0",
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:124.0) Gecko/20100101 Firefox/124.0",
-            // Firefox – macOS
-            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10.15; rv:125.0) Gecko/20100101 Firefox/125.0",
-            // Firefox – Linux
-            "Mozilla/5.0 (X11; Linux x86_64; rv:125.0) Gecko/20100101 Firefox/125.0",
-            // Edge – Windows
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36 Edg/124.0.0.0",
-            // Safari – macOS
-            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Safari/605.1.15",
-            "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_4) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Safari/605.1.15",
-        ];
-        
-        let mut rng = rand::thread_rng();
-        agents[rng.gen_range(0..agents.len())].into()
+    pub fn new(persona: Persona) -> Self {
+        Self { persona }
     }
-    
+
     pub fn get_spoofed_headers(&self) -> Vec<(String, String)> {
         vec![
-            ("User-Agent".into(), self.user_agent.clone()),
-            ("Accept-Language".into(), self.accept_language.clone()),
+            ("User-Agent".into(), self.persona.user_agent.clone()),
+            ("Accept-Language".into(), self.persona.accept_language()),
             ("Accept-Encoding".into(), "gzip, deflate, br".into()),
             ("Accept".into(), "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8".into()),
             ("Sec-Fetch-Dest".into(), "document".into()),
@@ -79,45 +40,93 @@ impl AntiFingerprintEngine {
         ]
     }
 
-    /// Generate JavaScript to inject into pages that spoofs navigator properties
-    pub fn generate_spoofing_script(&self) -> String {
-        format!(
-            r#"
-            (function() {{
-                Object.defineProperty(navigator, 'hardwareConcurrency', {{ get: () => {cores} }});
-                Object.defineProperty(screen, 'width',  {{ get: () => {sw} }});
-                Object.defineProperty(screen, 'height', {{ get: () => {sh} }});
-                Object.defineProperty(screen, 'availWidth',  {{ get: () => {sw} }});
-                Object.defineProperty(screen, 'availHeight', {{ get: () => {sh} }});
-                Object.defineProperty(navigator, 'deviceMemory', {{ get: () => 8 }});
-                Object.defineProperty(navigator, 'maxTouchPoints', {{ get: () => 0 }});
-                const origGetTimezoneOffset = Date.prototype.getTimezoneOffset;
-                Date.prototype.getTimezoneOffset = function() {{ return {tz}; }};
-                const origToDataURL = HTMLCanvasElement.prototype.toDataURL;
-                HTMLCanvasElement.prototype.toDataURL = function(type) {{
-                    const ctx = this.getContext('2d');
-                    if (ctx) {{
-                        const imageData = ctx.getImageData(0, 0, this.width, this.height);
-                        for (let i = 0; i < imageData.data.length; i += 4) {{
-                            imageData.data[i] ^= 1;
-                        }}
-                        ctx.putImageData(imageData, 0, 0);
-                    }}
-                    return origToDataURL.apply(this, arguments);
-                }};
-            }})();
-            "#,
-            cores = self.hardware_concurrency,
-            sw = self.screen_resolution.0,
-            sh = self.screen_resolution.1,
-            tz = self.timezone_offset,
-        )
+    /// Generate JavaScript to inject into pages that spoofs navigator
+    /// properties, with the parts `config` turns on. Canvas noise is part
+    /// of `SecurityManager::apply_page_hardening`.
+    pub fn generate_spoofing_script(&self, config: &SecurityConfig) -> String {
+        let persona = &self.persona;
+        let json = |value: &str| serde_json::to_string(value).unwrap_or_default();
+        let mut parts = Vec::new();
+
+        if config.anti_fingerprint {
+            let languages = serde_json::to_string(&persona.languages).unwrap_or_else(|_| "[]".into());
+            let memory = match persona.memory {
+                Some(memory) => format!("Object.defineProperty(nav, 'deviceMemory', {{ get: () => {} }});", memory),
+                None => "delete nav.deviceMemory;".to_string(),
+            };
+            parts.push(format!(
+                r#"
+                const nav = Navigator.prototype;
+                Object.defineProperty(nav, 'userAgent', {{ get: () => {ua} }});
+                Object.defineProperty(nav, 'appVersion', {{ get: () => {app_version} }});
+                Object.defineProperty(nav, 'platform', {{ get: () => {platform} }});
+                Object.defineProperty(nav, 'language', {{ get: () => {languages}[0] }});
+                Object.defineProperty(nav, 'languages', {{ get: () => Object.freeze({languages}) }});
+                Object.defineProperty(nav, 'hardwareConcurrency', {{ get: () => {cores} }});
+                Object.defineProperty(nav, 'maxTouchPoints', {{ get: () => 0 }});
+                {memory}
+                const scr = Screen.prototype;
+                Object.defineProperty(scr, 'width',  {{ get: () => {sw} }});
+                Object.defineProperty(scr, 'height', {{ get: () => {sh} }});
+                Object.defineProperty(scr, 'availWidth',  {{ get: () => {sw} }});
+                Object.defineProperty(scr, 'availHeight', {{ get: () => {avail} }});
+                Object.defineProperty(scr, 'colorDepth', {{ get: () => 24 }});
+                Object.defineProperty(scr, 'pixelDepth', {{ get: () => 24 }});"#,
+                ua = json(&persona.user_agent),
+                app_version = json(persona.user_agent.trim_start_matches("Mozilla/")),
+                platform = json(&persona.platform),
+                languages = languages,
+                cores = persona.cores,
+                memory = memory,
+                sw = persona.screen.0,
+                sh = persona.screen.1,
+                avail = persona.screen.1.saturating_sub(persona.taskbar),
+            ));
+        }
+
+        if config.webgl_spoof {
+            parts.push(format!(
+                r#"
+                [window.WebGLRenderingContext, window.WebGL2RenderingContext].forEach(function(gl) {{
+                    if (!gl) return;
+                    const getParameter = gl.prototype.getParameter;
+                    gl.prototype.getParameter = function(param) {{
+                        // UNMASKED_VENDOR_WEBGL, UNMASKED_RENDERER_WEBGL
+                        if (param === 37445) return {vendor};
+                        if (param === 37446) return {renderer};
+                        return getParameter.call(this, param);
+                    }};
+                }});"#,
+                vendor = json(&persona.webgl_vendor),
+                renderer = json(&persona.webgl_renderer),
+            ));
+        }
+
+        if config.timezone_spoof || persona.utc {
+            parts.push(
+                r#"
+                Date.prototype.getTimezoneOffset = function() { return 0; };
+                const OrigDateTimeFormat = Intl.DateTimeFormat;
+                Intl.DateTimeFormat = function(locales, options) {
+                    return new OrigDateTimeFormat(locales, Object.assign({ timeZone: 'UTC' }, options));
+                };
+                Intl.DateTimeFormat.prototype = OrigDateTimeFormat.prototype;
+                Intl.DateTimeFormat.supportedLocalesOf = OrigDateTimeFormat.supportedLocalesOf;"#
+                    .to_string(),
+            );
+        }
+
+        if parts.is_empty() {
+            return String::new();
+        }
+        format!("\n            (function() {{{}\n            }})();\n", parts.concat())
     }
 
-    pub fn user_agent(&self) -> &str { &self.user_agent }
-    pub fn screen_resolution(&self) -> (u32, u32) { self.screen_resolution }
+    pub fn persona(&self) -> &Persona { &self.persona }
+    pub fn user_agent(&self) -> &str { &self.persona.user_agent }
+    pub fn screen_resolution(&self) -> (u32, u32) { self.persona.screen }
 }
 
 impl Default for AntiFingerprintEngine {
-    fn default() -> Self { Self::new() }
+    fn default() -> Self { Self::new(Persona::session(&crate::config::Config::default().privacy)) }
 }
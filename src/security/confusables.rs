@@ -0,0 +1,210 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Lookalike domains: punycode, mixed scripts and confusable skeletons
+//!
+//! Domains are decoded from `xn--` labels and compared by their Unicode
+//! TR39 skeleton, which maps every character to the one it is confusable
+//! with, so `pаypal.com` with a Cyrillic `а` and `paypa1.com` both come out
+//! as `paypal.com`. Protected domains are the configured brands plus the
+//! user's bookmarked and most visited sites.
+
+use std::collections::HashSet;
+
+use unicode_script::{Script, UnicodeScript};
+use unicode_security::MixedScript;
+
+use crate::bookmarks::BookmarkManager;
+use crate::history::HistoryManager;
+use crate::utils::domain::host;
+
+/// History places whose domains are protected as most visited
+const MOST_VISITED_PLACES: usize = 100;
+
+/// Brands protected unless `SecurityConfig::protected_domains` says otherwise
+pub const PROTECTED_BRANDS: &[&str] = &[
+    "paypal.com", "google.com", "apple.com", "icloud.com", "microsoft.com",
+    "live.com", "outlook.com", "office.com", "amazon.com", "facebook.com",
+    "instagram.com", "whatsapp.com", "github.com", "netflix.com", "linkedin.com",
+    "twitter.com", "dropbox.com", "chase.com", "wellsfargo.com",
+    "bankofamerica.com", "coinbase.com", "binance.com",
+];
+
+/// Characters that pass for each letter or digit, for generating lookalikes.
+/// Each one's skeleton is the character it imitates, so `impersonated`
+/// catches whatever is made from them.
+pub const HOMOGLYPHS: &[(char, &[&str])] = &[
+    ('a', &["\u{430}", "\u{251}"]),
+    ('c', &["\u{441}", "\u{3f2}"]),
+    ('d', &["\u{501}"]),
+    ('e', &["\u{435}"]),
+    ('g', &["\u{261}"]),
+    ('h', &["\u{4bb}"]),
+    ('i', &["\u{456}", "\u{131}", "\u{4cf}"]),
+    ('j', &["\u{458}"]),
+    ('l', &["1"]),
+    ('m', &["rn"]),
+    ('n', &["\u{578}"]),
+    ('o', &["0", "\u{43e}", "\u{3bf}"]),
+    ('p', &["\u{440}"]),
+    ('q', &["\u{51b}"]),
+    ('s', &["\u{455}"]),
+    ('u', &["\u{3c5}"]),
+    ('v', &["\u{3bd}"]),
+    ('w', &["\u{51d}"]),
+    ('x', &["\u{445}"]),
+    ('y', &["\u{443}"]),
+    ('0', &["o"]),
+    ('1', &["l"]),
+];
+
+/// Why a domain is protected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtectedSource {
+    Brand,
+    Bookmark,
+    MostVisited,
+}
+
+impl ProtectedSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProtectedSource::Brand => "protected brand",
+            ProtectedSource::Bookmark => "bookmarked",
+            ProtectedSource::MostVisited => "most visited",
+        }
+    }
+}
+
+/// A domain lookalikes of which are flagged
+#[derive(Debug, Clone)]
+pub struct Protected {
+    pub domain: String,
+    pub source: ProtectedSource,
+    skeleton: String,
+}
+
+impl Protected {
+    pub fn new(domain: &str, source: ProtectedSource) -> Option<Self> {
+        let domain = decode(domain).ok()?;
+        let domain = domain.strip_prefix("www.").unwrap_or(&domain).to_string();
+        domain.contains('.').then(|| Self {
+            skeleton: skeleton(&domain),
+            domain,
+            source,
+        })
+    }
+
+    /// `brands`, then bookmarked and most visited domains, each once
+    pub fn gather(brands: &[String]) -> Vec<Self> {
+        let bookmarks: Vec<String> = BookmarkManager::global()
+            .read()
+            .get_all_bookmarks()
+            .iter()
+            .map(|bookmark| bookmark.url.clone())
+            .collect();
+        let visited: Vec<String> = HistoryManager::global()
+            .most_visited(MOST_VISITED_PLACES)
+            .into_iter()
+            .map(|entry| entry.url)
+            .collect();
+
+        let sources = brands
+            .iter()
+            .map(|brand| (brand.clone(), ProtectedSource::Brand))
+            .chain(bookmarks.iter().filter_map(|url| host(url)).map(|h| (h, ProtectedSource::Bookmark)))
+            .chain(visited.iter().filter_map(|url| host(url)).map(|h| (h, ProtectedSource::MostVisited)));
+        let mut seen = HashSet::new();
+        sources
+            .filter_map(|(domain, source)| Self::new(&domain, source))
+            .filter(|protected| seen.insert(protected.domain.clone()))
+            .collect()
+    }
+}
+
+/// `domain` with its `xn--` labels decoded, lowercased
+pub fn decode(domain: &str) -> Result<String, String> {
+    let (unicode, result) = idna::domain_to_unicode(domain.trim().trim_end_matches('.'));
+    result.map_err(|e| format!("Invalid internationalized domain {}: {:?}", domain, e))?;
+    Ok(unicode)
+}
+
+/// The TR39 skeleton of `domain`, case-insensitive
+pub fn skeleton(domain: &str) -> String {
+    unicode_security::skeleton(&domain.to_lowercase()).collect::<String>().to_lowercase()
+}
+
+/// Labels of a decoded domain that mix scripts, with the scripts they mix
+pub fn mixed_script_labels(domain: &str) -> Vec<(String, Vec<&'static str>)> {
+    domain
+        .split('.')
+        .filter(|label| !label.is_single_script())
+        .map(|label| {
+            let mut scripts: Vec<&'static str> = Vec::new();
+            for script in label.chars().map(|c| c.script()) {
+                if !matches!(script, Script::Common | Script::Inherited) && !scripts.contains(&script.full_name()) {
+                    scripts.push(script.full_name());
+                }
+            }
+            (label.to_string(), scripts)
+        })
+        .collect()
+}
+
+/// What passes for `c` in a lookalike domain
+pub fn homoglyphs(c: char) -> &'static [&'static str] {
+    HOMOGLYPHS
+        .iter()
+        .find(|(letter, _)| *letter == c)
+        .map(|(_, lookalikes)| *lookalikes)
+        .unwrap_or(&[])
+}
+
+/// The protected domain a decoded `domain` looks like but isn't
+pub fn impersonated<'a>(domain: &str, protected: &'a [Protected]) -> Option<&'a Protected> {
+    let domain = domain.strip_prefix("www.").unwrap_or(domain);
+    let skeleton = skeleton(domain);
+    protected.iter().find(|target| {
+        let genuine = domain == target.domain || domain.ends_with(&format!(".{}", target.domain));
+        let alike = skeleton == target.skeleton || skeleton.ends_with(&format!(".{}", target.skeleton));
+        alike && !genuine
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_and_spots_mixed_scripts() {
+        // pаypal with a Cyrillic а
+        let decoded = decode("xn--pypal-4ve.com").unwrap();
+        assert_eq!(decoded, "p\u{430}ypal.com");
+        assert_eq!(mixed_script_labels(&decoded), vec![("p\u{430}ypal".to_string(), vec!["Latin", "Cyrillic"])]);
+        assert!(mixed_script_labels("web1.example.com").is_empty());
+        assert!(mixed_script_labels("日本語テスト.jp").is_empty());
+    }
+
+    #[test]
+    fn finds_the_impersonated_domain() {
+        let protected: Vec<Protected> = ["paypal.com", "www.github.com"]
+            .iter()
+            .filter_map(|domain| Protected::new(domain, ProtectedSource::Brand))
+            .collect();
+        assert_eq!(impersonated("p\u{430}ypal.com", &protected).unwrap().domain, "paypal.com");
+        assert_eq!(impersonated("login.paypa1.com", &protected).unwrap().domain, "paypal.com");
+        assert_eq!(impersonated("g\u{456}thub.com", &protected).unwrap().domain, "github.com");
+        assert!(impersonated("www.paypal.com", &protected).is_none());
+        assert!(impersonated("web1.example.com", &protected).is_none());
+    }
+
+    #[test]
+    fn homoglyphs_share_the_skeleton_of_their_letter() {
+        for (letter, lookalikes) in HOMOGLYPHS {
+            for lookalike in *lookalikes {
+                assert_eq!(skeleton(lookalike), skeleton(&letter.to_string()), "{} for {}", lookalike, letter);
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! The marshall://leaktest self-check
+//!
+//! `LeakPromises` is what the settings in force say a page should see; the
+//! page's own script compares it with what it does see. Which way pages
+//! reach the network is only known here, so those checks come ready-made.
+
+use serde::Serialize;
+
+use super::anti_fingerprint::{COMMON_CORE_COUNTS, COMMON_RESOLUTIONS};
+use super::SecurityConfig;
+use crate::config::{NetworkConfig, PrivacyConfig};
+use crate::network::{Network, ProxyKind};
+#[cfg(feature = "tor")]
+use crate::network::Upstream;
+
+/// How a check turned out against its promise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Pass,
+    Fail,
+    /// Nothing was promised; what was found is only reported
+    Info,
+}
+
+/// One row of the leak test
+#[derive(Debug, Clone, Serialize)]
+pub struct LeakCheck {
+    pub name: &'static str,
+    pub verdict: Verdict,
+    pub detail: String,
+}
+
+/// What pages should see under the current settings
+#[derive(Debug, Clone, Serialize)]
+pub struct LeakPromises {
+    /// The User-Agent header pages are requested with
+    pub user_agent: String,
+    pub block_webrtc: bool,
+    /// Screen size and core count are among `screens` and `core_counts`,
+    /// whatever this machine has
+    pub common_hardware: bool,
+    pub screens: Vec<(u32, u32)>,
+    pub core_counts: Vec<u32>,
+    pub real_screen: Option<(u32, u32)>,
+    pub real_cores: u32,
+    pub canvas_noise: bool,
+    pub utc_timezone: bool,
+    pub hide_fonts: bool,
+    pub do_not_track: bool,
+    /// Canvas hash from the previous run, to compare across reloads
+    pub previous_canvas: Option<String>,
+}
+
+impl LeakPromises {
+    pub fn new(privacy: &PrivacyConfig, security: &SecurityConfig, network: &NetworkConfig, user_agent: &str) -> Self {
+        let security = security.in_force(privacy, network);
+        Self {
+            user_agent: user_agent.to_string(),
+            block_webrtc: security.block_webrtc,
+            common_hardware: security.anti_fingerprint,
+            screens: COMMON_RESOLUTIONS.to_vec(),
+            core_counts: COMMON_CORE_COUNTS.to_vec(),
+            real_screen: None,
+            real_cores: num_cpus::get() as u32,
+            canvas_noise: security.canvas_noise,
+            utc_timezone: security.timezone_spoof,
+            hide_fonts: security.anti_fingerprint,
+            do_not_track: privacy.do_not_track,
+            previous_canvas: None,
+        }
+    }
+}
+
+/// Which way pages' connections and name lookups go
+pub fn network_checks(network: &Network) -> Vec<LeakCheck> {
+    let (proxy, dns) = match network.upstream() {
+        Err(e) => (
+            LeakCheck {
+                name: "Proxy",
+                verdict: Verdict::Fail,
+                detail: format!("The configured proxy can't be used, so pages can't load: {}", e),
+            },
+            LeakCheck {
+                name: "DNS",
+                verdict: Verdict::Pass,
+                detail: "Nothing is looked up while the proxy is unusable".to_string(),
+            },
+        ),
+        Ok(Some(upstream)) => {
+            let kind = match upstream.kind {
+                ProxyKind::Http => "HTTP",
+                ProxyKind::Https => "HTTPS",
+                ProxyKind::Socks5 => "SOCKS5",
+            };
+            let proxy = LeakCheck {
+                name: "Proxy",
+                verdict: Verdict::Pass,
+                detail: format!("Pages connect through the {} proxy at {}", kind, upstream.authority()),
+            };
+            #[cfg(feature = "tor")]
+            let proxy = if network.config.tor_enabled { tor_check(upstream) } else { proxy };
+            let dns = LeakCheck {
+                name: "DNS",
+                verdict: Verdict::Pass,
+                detail: "Names are resolved by the proxy".to_string(),
+            };
+            (proxy, dns)
+        }
+        Ok(None) => {
+            let proxy = LeakCheck {
+                name: "Proxy",
+                verdict: Verdict::Info,
+                detail: "No proxy is configured; pages connect directly".to_string(),
+            };
+            // WebKit resolves page names itself, so DoH only covers Marshall's own requests
+            let dns = if network.config.dns_over_https {
+                let server = match network.resolver() {
+                    Some(_) => format!("only Marshall's own requests use {}", network.config.doh_server),
+                    None => format!("{} is unavailable", network.config.doh_server),
+                };
+                LeakCheck {
+                    name: "DNS",
+                    verdict: Verdict::Fail,
+                    detail: format!(
+                        "DNS-over-HTTPS is on, but pages are looked up by the system resolver; {}. \
+                         Set a proxy to keep page lookups private",
+                        server
+                    ),
+                }
+            } else {
+                LeakCheck {
+                    name: "DNS",
+                    verdict: Verdict::Info,
+                    detail: "Names are resolved by the system resolver".to_string(),
+                }
+            };
+            (proxy, dns)
+        }
+    };
+    vec![proxy, dns]
+}
+
+/// Tor is only a way out once it has a circuit
+#[cfg(feature = "tor")]
+fn tor_check(upstream: &Upstream) -> LeakCheck {
+    use crate::network::tor::{self, TorStatus};
+
+    let status = tor::status();
+    LeakCheck {
+        name: "Proxy",
+        verdict: if status == TorStatus::Connected { Verdict::Pass } else { Verdict::Fail },
+        detail: format!(
+            "Pages connect through Tor at {}, on a circuit per tab (Tor: {})",
+            upstream.authority(),
+            status.label()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, ProxyConfig};
+
+    #[test]
+    fn pages_only_skip_the_system_resolver_through_a_proxy() {
+        let mut config = Config::default().network;
+        config.tor_enabled = false;
+        config.proxy = None;
+        config.dns_over_https = true;
+        let direct = network_checks(&Network::new(&config));
+        assert_eq!(direct[0].verdict, Verdict::Info);
+        assert_eq!(direct[1].verdict, Verdict::Fail);
+
+        config.proxy = Some(ProxyConfig {
+            protocol: "socks5".to_string(),
+            host: "127.0.0.1".to_string(),
+            port: 1080,
+            username: Some("user".to_string()),
+            password: Some("secret".to_string()),
+        });
+        let proxied = network_checks(&Network::new(&config));
+        assert_eq!(proxied[0].verdict, Verdict::Pass);
+        assert!(!proxied[0].detail.contains("secret"));
+        assert_eq!(proxied[1].verdict, Verdict::Pass);
+
+        config.proxy.as_mut().unwrap().protocol = "gopher".to_string();
+        assert_eq!(network_checks(&Network::new(&config))[0].verdict, Verdict::Fail);
+    }
+
+    #[test]
+    fn tor_mode_promises_no_webrtc() {
+        let config = Config::default();
+        let security = SecurityConfig {
+            block_webrtc: false,
+            ..SecurityConfig::default()
+        };
+        let mut network = config.network.clone();
+        network.tor_enabled = false;
+        assert!(!LeakPromises::new(&config.privacy, &security, &network, "UA").block_webrtc);
+        network.tor_enabled = true;
+        assert!(LeakPromises::new(&config.privacy, &security, &network, "UA").block_webrtc);
+    }
+}
@@ -7,11 +7,22 @@ pub mod anti_fingerprint;
 pub mod traffic;
 pub mod memory;
 pub mod threat_intel;
+pub mod threat_feeds;
+pub mod confusables;
+pub mod leaktest;
+pub mod persona;
 
 use std::sync::Arc;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{NetworkConfig, PrivacyConfig};
+use anti_fingerprint::AntiFingerprintEngine;
+use persona::Persona;
 
 /// Security configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SecurityConfig {
     /// Enable process isolation
     pub isolation_enabled: bool,
@@ -29,6 +40,14 @@ pub struct SecurityConfig {
     pub webgl_spoof: bool,
     /// Resist timezone fingerprinting
     pub timezone_spoof: bool,
+    /// Local threat feed files: domain or URL lists, hosts files, CSV
+    /// exports such as URLhaus's, or STIX 2.1 bundles
+    pub threat_feeds: Vec<String>,
+    /// Domains whose lookalikes are treated as phishing, alongside
+    /// bookmarked and most visited sites
+    pub protected_domains: Vec<String>,
+    /// Domains listed on the OSINT page for typosquat reports
+    pub monitored_domains: Vec<String>,
 }
 
 impl Default for SecurityConfig {
@@ -42,7 +61,27 @@ impl Default for SecurityConfig {
             canvas_noise: true,
             webgl_spoof: true,
             timezone_spoof: false,
+            threat_feeds: Vec::new(),
+            protected_domains: confusables::PROTECTED_BRANDS.iter().map(|d| d.to_string()).collect(),
+            monitored_domains: Vec::new(),
+        }
+    }
+}
+
+impl SecurityConfig {
+    /// The flags that apply alongside `privacy` and `network`: fingerprint
+    /// defences need `block_fingerprinting`, `spoof_timezone` turns on the
+    /// timezone spoof, and tor mode always blocks WebRTC
+    pub fn in_force(&self, privacy: &PrivacyConfig, network: &NetworkConfig) -> Self {
+        let mut config = self.clone();
+        if !privacy.block_fingerprinting {
+            config.anti_fingerprint = false;
+            config.canvas_noise = false;
+            config.webgl_spoof = false;
         }
+        config.timezone_spoof |= privacy.spoof_timezone;
+        config.block_webrtc |= network.tor_enabled;
+        config
     }
 }
 
@@ -56,20 +95,36 @@ impl SecurityManager {
             config: Arc::new(RwLock::new(config)),
         }
     }
-    
+
+    /// Everything injected into pages: the hardening, then `persona`'s
+    /// signals. Empty when every flag is off.
+    pub fn page_script(&self, persona: &Persona) -> String {
+        let spoofing = AntiFingerprintEngine::new(persona.clone()).generate_spoofing_script(&self.config.read());
+        self.apply_page_hardening() + &spoofing
+    }
+
+    /// JavaScript to inject into pages for security, with a part per flag
     pub fn apply_page_hardening(&self) -> String {
-        // JavaScript to inject into pages for security
-        r#"
-        (function() {
-            'use strict';
-            
+        let config = self.config.read();
+        let mut parts = Vec::new();
+
+        if config.block_webrtc {
+            parts.push(
+                r#"
             // Block WebRTC IP leaks
-            if (window.RTCPeerConnection) {
-                window.RTCPeerConnection = function() {
-                    throw new Error('WebRTC blocked by Marshall');
-                };
-            }
-            
+            ['RTCPeerConnection', 'webkitRTCPeerConnection'].forEach(function(name) {
+                if (window[name]) {
+                    window[name] = function() {
+                        throw new Error('WebRTC blocked by Marshall');
+                    };
+                }
+            });"#,
+            );
+        }
+
+        if config.canvas_noise {
+            parts.push(
+                r#"
             // Randomize canvas fingerprint
             const originalGetContext = HTMLCanvasElement.prototype.getContext;
             HTMLCanvasElement.prototype.getContext = function(type, attrs) {
@@ -87,35 +142,68 @@ impl SecurityManager {
                 }
                 return ctx;
             };
-            
-            // Spoof WebGL renderer
-            const getParameterProto = WebGLRenderingContext.prototype.getParameter;
-            WebGLRenderingContext.prototype.getParameter = function(param) {
-                if (param === 37445) return 'Intel Inc.';
-                if (param === 37446) return 'Intel Iris OpenGL Engine';
-                return getParameterProto.call(this, param);
-            };
-            
+            const originalToDataURL = HTMLCanvasElement.prototype.toDataURL;
+            HTMLCanvasElement.prototype.toDataURL = function() {
+                const ctx = this.getContext('2d');
+                if (ctx && this.width && this.height) {
+                    ctx.putImageData(ctx.getImageData(0, 0, this.width, this.height), 0, 0);
+                }
+                return originalToDataURL.apply(this, arguments);
+            };"#,
+            );
+        }
+
+        if config.anti_fingerprint {
+            parts.push(
+                r#"
             // Block battery API
             if (navigator.getBattery) {
                 navigator.getBattery = undefined;
             }
-            
-            // Spoof screen resolution
-            Object.defineProperty(screen, 'width', { value: 1920 });
-            Object.defineProperty(screen, 'height', { value: 1080 });
-            Object.defineProperty(screen, 'availWidth', { value: 1920 });
-            Object.defineProperty(screen, 'availHeight', { value: 1040 });
-            
+
             // Block font enumeration
-            Object.defineProperty(document, 'fonts', {
-                get: function() {
-                    return { check: () => true, forEach: () => {}, entries: () => [] };
-                }
-            });
-            
-            console.log('[Marshall] Security hardening applied');
-        })();
-        "#.into()
+            const fonts = {
+                status: 'loaded', size: 0, ready: Promise.resolve(),
+                check: () => true, load: () => Promise.resolve([]),
+                add: () => {}, delete: () => false, has: () => false, clear: () => {},
+                forEach: () => {}, entries: () => [][Symbol.iterator](),
+                addEventListener: () => {}, removeEventListener: () => {},
+            };
+            fonts.ready = Promise.resolve(fonts);
+            Object.defineProperty(document, 'fonts', { get: () => fonts });"#,
+            );
+        }
+
+        if parts.is_empty() {
+            return String::new();
+        }
+        format!(
+            "\n        (function() {{\n            'use strict';\n{}\n        }})();\n",
+            parts.concat()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn page_script_follows_the_flags_in_force() {
+        let mut config = Config::default();
+        let persona = Persona::session(&config.privacy);
+        let script = SecurityManager::new(config.security.in_force(&config.privacy, &config.network)).page_script(&persona);
+        assert!(script.contains("RTCPeerConnection") && script.contains("hardwareConcurrency") && script.contains("37446"));
+
+        // Without fingerprint protection only the WebRTC block is left
+        config.privacy.block_fingerprinting = false;
+        config.privacy.spoof_timezone = false;
+        let security = config.security.in_force(&config.privacy, &config.network);
+        let script = SecurityManager::new(security.clone()).page_script(&persona);
+        assert!(script.contains("RTCPeerConnection") && !script.contains("hardwareConcurrency") && !script.contains("getImageData"));
+
+        let off = SecurityConfig { block_webrtc: false, ..security };
+        assert_eq!(SecurityManager::new(off).page_script(&Persona { utc: false, ..persona }), "");
     }
 }
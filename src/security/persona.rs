@@ -0,0 +1,289 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Fingerprint personas
+//!
+//! A persona is the one machine pages are told they're talking to: its user
+//! agent, platform, languages, screen, cores, memory, timezone and GPU. The
+//! session has one and each container its own, picked once and kept until
+//! Marshall exits, so headers, scripts and WebKit settings never disagree.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use rand::Rng;
+
+use super::anti_fingerprint::{COMMON_CORE_COUNTS, COMMON_RESOLUTIONS};
+use crate::config::PrivacyConfig;
+use crate::tabs::Container;
+
+/// Languages pages see when `spoof_language` is on
+const SPOOFED_LANGUAGES: &[&str] = &["en-US", "en"];
+
+/// Personas by container id; the session's is under ""
+static PERSONAS: Lazy<RwLock<HashMap<String, Persona>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// A browser and OS pairing whose signals belong together
+struct Platform {
+    user_agent: &'static str,
+    /// `navigator.platform`
+    platform: &'static str,
+    webgl_vendor: &'static str,
+    webgl_renderer: &'static str,
+    /// Screen height taken by the taskbar or menu bar
+    taskbar: u32,
+    /// Only Chromium browsers have `navigator.deviceMemory`
+    chromium: bool,
+}
+
+const PLATFORMS: &[Platform] = &[
+    Platform {
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
+        platform: "Win32",
+        webgl_vendor: "Google Inc. (Intel)",
+        webgl_renderer: "ANGLE (Intel, Intel(R) UHD Graphics 630 Direct3D11 vs_5_0 ps_5_0, D3D11)",
+        taskbar: 40,
+        chromium: true,
+    },
+    Platform {
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36 Edg/124.0.0.0",
+        platform: "Win32",
+        webgl_vendor: "Google Inc. (Intel)",
+        webgl_renderer: "ANGLE (Intel, Intel(R) UHD Graphics 630 Direct3D11 vs_5_0 ps_5_0, D3D11)",
+        taskbar: 40,
+        chromium: true,
+    },
+    Platform {
+        user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
+        platform: "MacIntel",
+        webgl_vendor: "Google Inc. (Apple)",
+        webgl_renderer: "ANGLE (Apple, Apple M1, OpenGL 4.1)",
+        taskbar: 25,
+        chromium: true,
+    },
+    Platform {
+        user_agent: "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36",
+        platform: "Linux x86_64",
+        webgl_vendor: "Google Inc. (Intel)",
+        webgl_renderer: "ANGLE (Intel, Mesa Intel(R) UHD Graphics 620 (KBL GT2), OpenGL 4.6)",
+        taskbar: 32,
+        chromium: true,
+    },
+    Platform {
+        user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:125.0) Gecko/20100101 Firefox/125.0",
+        platform: "Win32",
+        webgl_vendor: "Mozilla",
+        webgl_renderer: "Mozilla",
+        taskbar: 40,
+        chromium: false,
+    },
+    Platform {
+        user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10.15; rv:125.0) Gecko/20100101 Firefox/125.0",
+        platform: "MacIntel",
+        webgl_vendor: "Mozilla",
+        webgl_renderer: "Mozilla",
+        taskbar: 25,
+        chromium: false,
+    },
+    Platform {
+        user_agent: "Mozilla/5.0 (X11; Linux x86_64; rv:125.0) Gecko/20100101 Firefox/125.0",
+        platform: "Linux x86_64",
+        webgl_vendor: "Mozilla",
+        webgl_renderer: "Mozilla",
+        taskbar: 32,
+        chromium: false,
+    },
+    Platform {
+        user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Safari/605.1.15",
+        platform: "MacIntel",
+        webgl_vendor: "Apple Inc.",
+        webgl_renderer: "Apple GPU",
+        taskbar: 25,
+        chromium: false,
+    },
+];
+
+/// Everything pages can learn about the machine, kept consistent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Persona {
+    pub user_agent: String,
+    /// `navigator.platform`, matching the user agent's OS
+    pub platform: String,
+    /// Preferred first; also sent as `Accept-Language`
+    pub languages: Vec<String>,
+    pub screen: (u32, u32),
+    /// Screen height taken by the taskbar, missing from `availHeight`
+    pub taskbar: u32,
+    pub cores: u32,
+    /// `navigator.deviceMemory` in GB; `None` where the browser has none
+    pub memory: Option<u32>,
+    /// Pages see UTC instead of the local timezone
+    pub utc: bool,
+    pub webgl_vendor: String,
+    pub webgl_renderer: String,
+}
+
+impl Persona {
+    /// A new persona from the common platforms and hardware
+    pub fn random(privacy: &PrivacyConfig) -> Self {
+        let mut rng = rand::thread_rng();
+        let platform = &PLATFORMS[rng.gen_range(0..PLATFORMS.len())];
+        let mut persona = Self {
+            user_agent: String::new(),
+            platform: String::new(),
+            languages: Vec::new(),
+            screen: COMMON_RESOLUTIONS[rng.gen_range(0..COMMON_RESOLUTIONS.len())],
+            taskbar: 0,
+            cores: COMMON_CORE_COUNTS[rng.gen_range(0..COMMON_CORE_COUNTS.len())],
+            memory: None,
+            utc: false,
+            webgl_vendor: String::new(),
+            webgl_renderer: String::new(),
+        };
+        persona.take_platform(platform);
+        persona.follow(privacy);
+        persona
+    }
+
+    /// The session's persona
+    pub fn session(privacy: &PrivacyConfig) -> Self {
+        Self::for_container(privacy, None)
+    }
+
+    /// The persona of tabs in `container`, or the session's outside one.
+    /// A container's own user agent replaces the persona's, and the
+    /// platform follows it.
+    pub fn for_container(privacy: &PrivacyConfig, container: Option<&Container>) -> Self {
+        let key = container.map(|c| c.id.as_str()).unwrap_or("");
+        let mut persona = PERSONAS
+            .write()
+            .entry(key.to_string())
+            .or_insert_with(|| Self::random(privacy))
+            .clone();
+        persona.follow(privacy);
+        if let Some(user_agent) = container.and_then(|c| c.user_agent.as_deref()) {
+            persona.take_user_agent(user_agent);
+        }
+        persona
+    }
+
+    /// Present as `user_agent`, with the platform and GPU of the nearest
+    /// known browser
+    fn take_user_agent(&mut self, user_agent: &str) {
+        let chromium = user_agent.contains("Chrome/");
+        if let Some(platform) = PLATFORMS
+            .iter()
+            .find(|p| p.platform == platform_of(user_agent) && p.chromium == chromium)
+        {
+            self.take_platform(platform);
+        }
+        self.platform = platform_of(user_agent).to_string();
+        self.user_agent = user_agent.to_string();
+    }
+
+    fn take_platform(&mut self, platform: &Platform) {
+        self.user_agent = platform.user_agent.to_string();
+        self.platform = platform.platform.to_string();
+        self.taskbar = platform.taskbar;
+        // Chromium rounds down and caps at 8
+        self.memory = platform.chromium.then_some(if self.cores >= 8 { 8 } else { 4 });
+        self.webgl_vendor = platform.webgl_vendor.to_string();
+        self.webgl_renderer = platform.webgl_renderer.to_string();
+    }
+
+    /// Settings that can change while the persona lives
+    fn follow(&mut self, privacy: &PrivacyConfig) {
+        self.languages = if privacy.spoof_language {
+            SPOOFED_LANGUAGES.iter().map(|l| l.to_string()).collect()
+        } else {
+            system_languages()
+        };
+        self.utc = privacy.spoof_timezone;
+    }
+
+    /// `Accept-Language` for `languages`, e.g. `en-US,en;q=0.9`
+    pub fn accept_language(&self) -> String {
+        self.languages
+            .iter()
+            .enumerate()
+            .map(|(i, language)| match i {
+                0 => language.clone(),
+                _ => format!("{};q=0.{}", language, 10 - i.min(9)),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Minutes ahead of UTC pages see
+    pub fn timezone_offset(&self) -> i32 {
+        if self.utc {
+            0
+        } else {
+            chrono::Local::now().offset().local_minus_utc() / 60
+        }
+    }
+}
+
+/// `navigator.platform` for the OS a user agent names
+fn platform_of(user_agent: &str) -> &'static str {
+    if user_agent.contains("Windows") {
+        "Win32"
+    } else if user_agent.contains("Macintosh") || user_agent.contains("Mac OS X") {
+        "MacIntel"
+    } else if user_agent.contains("iPhone") {
+        "iPhone"
+    } else {
+        "Linux x86_64"
+    }
+}
+
+/// Languages from the locale, e.g. `de_DE.UTF-8` gives `de-DE, de`
+fn system_languages() -> Vec<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX");
+    let Some(locale) = locale else {
+        return SPOOFED_LANGUAGES.iter().map(|l| l.to_string()).collect();
+    };
+    let tag = locale.split(['.', '@']).next().unwrap_or("").replace('_', "-");
+    let base = tag.split('-').next().unwrap_or("").to_string();
+    if tag.is_empty() {
+        SPOOFED_LANGUAGES.iter().map(|l| l.to_string()).collect()
+    } else if base == tag {
+        vec![tag]
+    } else {
+        vec![tag, base]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn platforms_agree_with_their_user_agents() {
+        for platform in PLATFORMS {
+            assert_eq!(platform_of(platform.user_agent), platform.platform, "{}", platform.user_agent);
+            assert_eq!(platform.chromium, platform.user_agent.contains("Chrome/"));
+        }
+    }
+
+    #[test]
+    fn containers_keep_a_persona_of_their_own() {
+        let mut privacy = Config::default().privacy;
+        privacy.spoof_language = true;
+        let mut container = Container::new("Persona test", "blue");
+        let first = Persona::for_container(&privacy, Some(&container));
+        assert_eq!(Persona::for_container(&privacy, Some(&container)), first);
+        assert_eq!(first.accept_language(), "en-US,en;q=0.9");
+
+        container.user_agent = Some(PLATFORMS[4].user_agent.to_string());
+        let custom = Persona::for_container(&privacy, Some(&container));
+        assert_eq!((custom.platform.as_str(), custom.screen), ("Win32", first.screen));
+        assert_eq!((custom.memory, custom.webgl_vendor.as_str()), (None, "Mozilla"));
+    }
+}
@@ -0,0 +1,485 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Local threat feeds for `ThreatIntel`
+//!
+//! Feeds are files named in `SecurityConfig::threat_feeds`: plain domain or
+//! URL lists, hosts files, CSV exports such as URLhaus's, and STIX 2.1
+//! bundles, told apart by their content. Domains match themselves and their
+//! subdomains; URLs match every address they are a prefix of.
+
+use std::collections::HashMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tracing::{info, warn};
+use url::{Position, Url};
+
+use super::threat_intel::ThreatLevel;
+
+/// Observables in a STIX pattern that name something to block
+static STIX_OBSERVABLE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(domain-name|url|ipv4-addr|ipv6-addr):value\s*=\s*'((?:[^'\\]|\\.)*)'").unwrap()
+});
+
+/// A loaded feed
+#[derive(Debug, Clone)]
+pub struct ThreatFeed {
+    pub name: String,
+    /// When the feed file last changed
+    pub updated: Option<DateTime<Utc>>,
+    pub entries: usize,
+}
+
+/// One domain or URL as a feed lists it
+#[derive(Debug, Clone)]
+struct Listing {
+    feed: usize,
+    level: ThreatLevel,
+    /// When the feed says the entry was added, if it says
+    added: Option<DateTime<Utc>>,
+}
+
+/// How a feed entry matched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeedMatch {
+    Domain,
+    /// A parent of the domain is listed
+    ParentDomain(String),
+    UrlPrefix(String),
+}
+
+/// A feed entry that matched a URL
+#[derive(Debug, Clone)]
+pub struct FeedHit {
+    pub feed: String,
+    pub matched: FeedMatch,
+    pub level: ThreatLevel,
+    /// When the entry was added, or else when its feed was updated
+    pub listed: Option<DateTime<Utc>>,
+}
+
+impl FeedHit {
+    /// How long the entry has been listed, e.g. "3 days old"
+    pub fn age(&self) -> String {
+        let Some(listed) = self.listed else {
+            return "age unknown".to_string();
+        };
+        let age = Utc::now() - listed;
+        if age.num_days() >= 1 {
+            format!("{} day{} old", age.num_days(), if age.num_days() == 1 { "" } else { "s" })
+        } else if age.num_hours() >= 1 {
+            format!("{} hour{} old", age.num_hours(), if age.num_hours() == 1 { "" } else { "s" })
+        } else {
+            "under an hour old".to_string()
+        }
+    }
+}
+
+/// Every feed's entries, indexed for lookup by domain and URL
+#[derive(Debug, Default)]
+pub struct FeedIndex {
+    feeds: Vec<ThreatFeed>,
+    domains: HashMap<String, Vec<Listing>>,
+    /// URL prefixes without their scheme, by host
+    urls: HashMap<String, Vec<(String, Listing)>>,
+}
+
+impl FeedIndex {
+    /// Load the feed files at `paths`, skipping any that can't be read
+    pub fn open(paths: &[String]) -> Self {
+        let mut index = Self::default();
+        for path in paths {
+            match index.load(Path::new(path)) {
+                Ok(count) => info!("Threat feed {}: {} entries", path, count),
+                Err(e) => warn!("Threat feed {} not loaded: {}", path, e),
+            }
+        }
+        index
+    }
+
+    pub fn feeds(&self) -> &[ThreatFeed] {
+        &self.feeds
+    }
+
+    /// Load one feed file, named after the file
+    pub fn load(&mut self, path: &Path) -> Result<usize, String> {
+        let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let updated = fs::metadata(path).and_then(|m| m.modified()).ok().map(DateTime::<Utc>::from);
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        self.add_feed(&name, &content, updated)
+    }
+
+    /// Index `content` as the feed `name`
+    pub fn add_feed(&mut self, name: &str, content: &str, updated: Option<DateTime<Utc>>) -> Result<usize, String> {
+        let feed = self.feeds.len();
+        let level = default_level(name);
+        let entries = if content.trim_start().starts_with('{') {
+            parse_stix(content, level)?
+        } else if is_csv(content) {
+            parse_csv(content, level)
+        } else {
+            parse_lines(content, level)
+        };
+        let count = entries
+            .into_iter()
+            .filter(|(entry, level, added)| {
+                self.add(entry, Listing {
+                    feed,
+                    level: level.clone(),
+                    added: *added,
+                })
+            })
+            .count();
+        if count == 0 {
+            return Err("no domains or URLs found".to_string());
+        }
+        self.feeds.push(ThreatFeed {
+            name: name.to_string(),
+            updated,
+            entries: count,
+        });
+        Ok(count)
+    }
+
+    /// A feed of its own for domains blocked while browsing
+    pub fn block_domain(&mut self, feed: &str, domain: &str) {
+        let index = match self.feeds.iter().position(|f| f.name == feed) {
+            Some(index) => index,
+            None => {
+                self.feeds.push(ThreatFeed {
+                    name: feed.to_string(),
+                    updated: None,
+                    entries: 0,
+                });
+                self.feeds.len() - 1
+            }
+        };
+        if self.add(domain, Listing {
+            feed: index,
+            level: ThreatLevel::Malicious,
+            added: Some(Utc::now()),
+        }) {
+            self.feeds[index].entries += 1;
+        }
+    }
+
+    /// Index one entry; false if it names nothing on the web
+    fn add(&mut self, entry: &str, listing: Listing) -> bool {
+        if entry.contains("://") {
+            match url_key(entry) {
+                Some((host, prefix)) => self.urls.entry(host).or_default().push((prefix, listing)),
+                None => return false,
+            }
+        } else {
+            match normalize_domain(entry) {
+                Some(domain) => self.domains.entry(domain).or_default().push(listing),
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Feed entries matching `url` on `domain`
+    pub fn lookup(&self, domain: &str, url: &str) -> Vec<FeedHit> {
+        let mut hits = Vec::new();
+        let Some(domain) = normalize_domain(domain) else {
+            return hits;
+        };
+
+        let mut candidates = vec![domain.clone()];
+        if domain.parse::<IpAddr>().is_err() {
+            let mut rest = domain.as_str();
+            while let Some((_, parent)) = rest.split_once('.') {
+                if parent.contains('.') {
+                    candidates.push(parent.to_string());
+                }
+                rest = parent;
+            }
+        }
+        for candidate in &candidates {
+            for listing in self.domains.get(candidate).into_iter().flatten() {
+                let matched = if *candidate == domain {
+                    FeedMatch::Domain
+                } else {
+                    FeedMatch::ParentDomain(candidate.clone())
+                };
+                hits.push(self.hit(listing, matched));
+            }
+        }
+
+        if let Some((host, target)) = url_key(url) {
+            for (prefix, listing) in self.urls.get(&host).into_iter().flatten() {
+                if target.starts_with(prefix.as_str()) {
+                    hits.push(self.hit(listing, FeedMatch::UrlPrefix(prefix.clone())));
+                }
+            }
+        }
+        hits
+    }
+
+    fn hit(&self, listing: &Listing, matched: FeedMatch) -> FeedHit {
+        let feed = &self.feeds[listing.feed];
+        FeedHit {
+            feed: feed.name.clone(),
+            matched,
+            level: listing.level.clone(),
+            listed: listing.added.or(feed.updated),
+        }
+    }
+}
+
+type Entry = (String, ThreatLevel, Option<DateTime<Utc>>);
+
+/// What a feed's entries are, going by its name
+fn default_level(name: &str) -> ThreatLevel {
+    let name = name.to_lowercase();
+    if name.contains("phish") {
+        ThreatLevel::Phishing
+    } else if name.contains("urlhaus") || name.contains("malware") {
+        ThreatLevel::Malware
+    } else {
+        ThreatLevel::Malicious
+    }
+}
+
+/// Lowercase, without a wildcard or trailing dot; `None` for what can't
+/// be a host on the web
+fn normalize_domain(domain: &str) -> Option<String> {
+    let domain = domain
+        .trim()
+        .trim_start_matches("*.")
+        .trim_start_matches('.')
+        .trim_end_matches('.')
+        .trim_matches(['[', ']'])
+        .to_lowercase();
+    let unroutable = domain == "localhost"
+        || domain.ends_with(".localhost")
+        || domain.ends_with(".local")
+        || domain.parse::<IpAddr>().is_ok_and(|ip| ip.is_unspecified() || ip.is_loopback());
+    let valid = (domain.contains('.') || domain.contains(':'))
+        && !domain.chars().any(|c| c.is_whitespace() || c == '/' || c == '"');
+    (valid && !unroutable).then_some(domain)
+}
+
+/// A URL's host and everything from the host on, for prefix matching
+/// whichever scheme was used
+fn url_key(url: &str) -> Option<(String, String)> {
+    let parsed = Url::parse(url.trim()).ok()?;
+    let host = normalize_domain(parsed.host_str()?)?;
+    Some((host, parsed[Position::BeforeHost..Position::AfterQuery].to_string()))
+}
+
+/// Dates as feeds write them
+fn parse_date(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    DateTime::parse_from_rfc3339(text)
+        .map(|date| date.with_timezone(&Utc))
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").map(|date| date.and_utc()))
+        .ok()
+}
+
+/// Domain and URL lists, one per line, and hosts files
+fn parse_lines(content: &str, level: ThreatLevel) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [] => {}
+            // hosts: an address, then the names pointed at it
+            [address, names @ ..] if address.parse::<IpAddr>().is_ok() && !names.is_empty() => {
+                entries.extend(names.iter().map(|name| (name.to_string(), level.clone(), None)));
+            }
+            [entry] if entry.contains("://") || normalize_domain(entry).is_some() => {
+                entries.push((entry.to_string(), level.clone(), None));
+            }
+            _ => {}
+        }
+    }
+    entries
+}
+
+/// Whether the first data line has several fields
+fn is_csv(content: &str) -> bool {
+    content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .is_some_and(|line| split_csv(line).len() > 1)
+}
+
+/// CSV with or without a header; URLhaus puts its header in a comment
+fn parse_csv(content: &str, level: ThreatLevel) -> Vec<Entry> {
+    let mut header: Vec<String> = Vec::new();
+    let mut entries = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            let fields = split_csv(comment.trim());
+            if fields.len() > 1 && fields.iter().any(|f| is_entry_column(f)) {
+                header = fields.iter().map(|f| f.to_lowercase()).collect();
+            }
+            continue;
+        }
+        let fields = split_csv(line);
+        if header.is_empty() && fields.iter().any(|f| is_entry_column(f)) {
+            header = fields.iter().map(|f| f.to_lowercase()).collect();
+            continue;
+        }
+
+        let column = |names: &[&str]| {
+            header
+                .iter()
+                .position(|h| names.contains(&h.as_str()))
+                .and_then(|i| fields.get(i))
+                .map(String::as_str)
+        };
+        let entry = column(&["url", "domain", "host", "hostname"]).or_else(|| {
+            fields
+                .iter()
+                .map(String::as_str)
+                .find(|f| f.contains("://") || normalize_domain(f).is_some())
+        });
+        let Some(entry) = entry.filter(|e| !e.is_empty()) else {
+            continue;
+        };
+        let added = column(&["dateadded", "date_added", "first_seen", "firstseen", "date"]).and_then(parse_date);
+        let level = match column(&["threat", "type"]).map(str::to_lowercase) {
+            Some(threat) if threat.contains("phish") => ThreatLevel::Phishing,
+            Some(threat) if threat.contains("malware") => ThreatLevel::Malware,
+            _ => level.clone(),
+        };
+        entries.push((entry.to_string(), level, added));
+    }
+    entries
+}
+
+fn is_entry_column(name: &str) -> bool {
+    matches!(name.to_lowercase().as_str(), "url" | "domain" | "host" | "hostname")
+}
+
+/// Fields of one CSV line, unquoted
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// Domains, URLs and addresses from the patterns of a STIX 2.1 bundle's
+/// indicators that are neither revoked nor expired
+fn parse_stix(content: &str, level: ThreatLevel) -> Result<Vec<Entry>, String> {
+    let bundle: serde_json::Value = serde_json::from_str(content).map_err(|e| format!("Invalid STIX bundle: {}", e))?;
+    let objects = bundle
+        .get("objects")
+        .and_then(|o| o.as_array())
+        .ok_or("STIX bundle has no objects")?;
+
+    let now = Utc::now();
+    let mut entries = Vec::new();
+    for indicator in objects.iter().filter(|o| o["type"] == "indicator") {
+        let expired = indicator["valid_until"].as_str().and_then(parse_date).is_some_and(|until| until < now);
+        if indicator["revoked"] == true || expired {
+            continue;
+        }
+        let Some(pattern) = indicator["pattern"].as_str() else {
+            continue;
+        };
+        let added = ["valid_from", "created"]
+            .iter()
+            .find_map(|key| indicator[*key].as_str().and_then(parse_date));
+        let labels: Vec<String> = ["indicator_types", "labels"]
+            .iter()
+            .filter_map(|key| indicator[*key].as_array())
+            .flatten()
+            .filter_map(|label| label.as_str().map(str::to_lowercase))
+            .collect();
+        let level = if labels.iter().any(|l| l.contains("phish")) {
+            ThreatLevel::Phishing
+        } else if labels.iter().any(|l| l.contains("malware")) {
+            ThreatLevel::Malware
+        } else {
+            level.clone()
+        };
+        for observable in STIX_OBSERVABLE.captures_iter(pattern) {
+            let value = observable[2].replace("\\'", "'").replace("\\\\", "\\");
+            entries.push((value, level.clone(), added));
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_each_feed_format() {
+        let mut index = FeedIndex::default();
+        let hosts = "# hosts\n127.0.0.1 localhost\n0.0.0.0 ads.example tracker.example # both\n";
+        assert_eq!(index.add_feed("hosts", hosts, None), Ok(2));
+        let list = "evil.example\n*.bad.example\nhttps://files.example/payload/\n";
+        assert_eq!(index.add_feed("openphish", list, None), Ok(3));
+        let urlhaus = "# URLhaus dump\n# id,dateadded,url,url_status,last_online,threat,tags,urlhaus_link,reporter\n\
+            \"1\",\"2024-07-29 11:06:07\",\"http://117.215.246.56:43219/bin.sh\",\"online\",\"\",\"malware_download\",\"elf,mips\",\"https://urlhaus.abuse.ch/url/1/\",\"someone\"\n";
+        assert_eq!(index.add_feed("urlhaus", urlhaus, None), Ok(1));
+        let stix = r#"{"type": "bundle", "objects": [
+            {"type": "indicator", "created": "2024-01-01T00:00:00Z", "indicator_types": ["malicious-activity"],
+             "pattern": "[domain-name:value = 'c2.example'] OR [url:value = 'http://drop.example/x.exe']"},
+            {"type": "indicator", "revoked": true, "pattern": "[domain-name:value = 'old.example']"}
+        ]}"#;
+        assert_eq!(index.add_feed("stix", stix, None), Ok(2));
+
+        assert!(index.lookup("localhost", "http://localhost/").is_empty());
+        assert_eq!(index.lookup("tracker.example", "https://tracker.example/")[0].feed, "hosts");
+        assert_eq!(index.lookup("old.example", "https://old.example/").len(), 0);
+
+        let malware = index.lookup("117.215.246.56", "http://117.215.246.56:43219/bin.sh");
+        assert_eq!((malware[0].level.clone(), malware[0].listed.is_some()), (ThreatLevel::Malware, true));
+        let c2 = index.lookup("c2.example", "https://c2.example/");
+        assert_eq!((c2[0].feed.as_str(), c2[0].level.clone()), ("stix", ThreatLevel::Malicious));
+        assert!(c2[0].age().ends_with("days old"));
+    }
+
+    #[test]
+    fn matches_domains_parents_and_url_prefixes() {
+        let mut index = FeedIndex::default();
+        index.add_feed("openphish", "bad.example\nhttps://files.example/payload/\n", None).unwrap();
+
+        let exact = index.lookup("bad.example", "https://bad.example/");
+        assert_eq!((exact[0].matched.clone(), exact[0].level.clone()), (FeedMatch::Domain, ThreatLevel::Phishing));
+        let parent = index.lookup("login.bad.example", "https://login.bad.example/");
+        assert_eq!(parent[0].matched, FeedMatch::ParentDomain("bad.example".to_string()));
+        assert!(index.lookup("notbad.example", "https://notbad.example/").is_empty());
+
+        // Any scheme, and only under the listed path
+        let url = index.lookup("files.example", "http://files.example/payload/run.sh");
+        assert_eq!(url[0].matched, FeedMatch::UrlPrefix("files.example/payload/".to_string()));
+        assert!(index.lookup("files.example", "https://files.example/docs/").is_empty());
+    }
+}
@@ -7,8 +7,18 @@
 use std::collections::HashSet;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-/// Threat classification levels
-#[derive(Debug, Clone, PartialEq)]
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+
+use super::confusables::{self, Protected, ProtectedSource, PROTECTED_BRANDS};
+use super::threat_feeds::{FeedIndex, FeedMatch};
+use crate::utils::domain;
+
+/// What browsing is checked against
+static INTEL: Lazy<RwLock<ThreatIntel>> = Lazy::new(|| RwLock::new(ThreatIntel::new()));
+
+/// Threat classification levels, least dangerous first
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ThreatLevel {
     Safe,
     Suspicious,
@@ -29,6 +39,13 @@ impl std::fmt::Display for ThreatLevel {
     }
 }
 
+impl ThreatLevel {
+    /// Navigations at this level are stopped by the marshall://blocked page
+    pub fn blocks(&self) -> bool {
+        matches!(self, ThreatLevel::Malicious | ThreatLevel::Phishing | ThreatLevel::Malware)
+    }
+}
+
 /// Result of a threat analysis
 #[derive(Debug, Clone)]
 pub struct ThreatAnalysis {
@@ -53,13 +70,22 @@ pub struct ThreatIntel {
     suspicious_tlds: HashSet<String>,
     /// Known phishing patterns
     phishing_patterns: Vec<String>,
-    /// Homoglyph character mappings
-    homoglyphs: Vec<(char, char)>,
+    /// Domains whose lookalikes are phishing
+    protected: Vec<Protected>,
     /// Trusted domains
     trusted_domains: HashSet<String>,
+    /// Domains the user chose to visit despite a warning, until Marshall exits
+    session_allowed: HashSet<String>,
+    /// Local threat feeds
+    feeds: FeedIndex,
 }
 
 impl ThreatIntel {
+    /// The instance navigations are checked against
+    pub fn global() -> &'static RwLock<ThreatIntel> {
+        &INTEL
+    }
+
     pub fn new() -> Self {
         let suspicious_tlds: HashSet<String> = [
             "tk", "ml", "ga", "cf", "gq",  // Free TLDs abused by phishing
@@ -82,11 +108,10 @@ impl ThreatIntel {
             "amazon-security".to_string(),
         ];
 
-        let homoglyphs = vec![
-            ('o', '0'), ('l', '1'), ('i', '1'),
-            ('a', 'а'), ('e', 'е'), ('o', 'о'),  // Cyrillic
-            ('c', 'с'), ('p', 'р'), ('x', 'х'),
-        ];
+        let protected = PROTECTED_BRANDS
+            .iter()
+            .filter_map(|brand| Protected::new(brand, ProtectedSource::Brand))
+            .collect();
 
         let trusted_domains: HashSet<String> = [
             "google.com", "github.com", "microsoft.com", "apple.com",
@@ -97,11 +122,23 @@ impl ThreatIntel {
         Self {
             suspicious_tlds,
             phishing_patterns,
-            homoglyphs,
+            protected,
             trusted_domains,
+            session_allowed: HashSet::new(),
+            feeds: FeedIndex::default(),
         }
     }
 
+    /// Flag lookalikes of `protected` from now on
+    pub fn set_protected(&mut self, protected: Vec<Protected>) {
+        self.protected = protected;
+    }
+
+    /// Check against `feeds` from now on
+    pub fn set_feeds(&mut self, feeds: FeedIndex) {
+        self.feeds = feeds;
+    }
+
     /// Analyze a URL for threats
     pub fn analyze_url(&self, url: &str) -> ThreatAnalysis {
         let domain = self.extract_domain(url);
@@ -109,8 +146,8 @@ impl ThreatIntel {
         let mut score: u32 = 0;
 
         // Check suspicious TLD
-        if let Some(tld) = domain.split('.').last() {
-            if self.suspicious_tlds.contains(tld) {
+        if let Some(tld) = domain::effective_tld(&domain) {
+            if self.suspicious_tlds.contains(&tld) {
                 indicators.push(ThreatIndicator {
                     category: "TLD".to_string(),
                     description: format!("Suspicious TLD: .{}", tld),
@@ -133,20 +170,52 @@ impl ThreatIntel {
             }
         }
 
-        // Check for homoglyph attacks
-        for &(real, fake) in &self.homoglyphs {
-            if domain.contains(fake) {
+        // Internationalized domains are judged as they are displayed
+        let displayed = match confusables::decode(&domain_lower) {
+            Ok(displayed) => {
+                if displayed != domain_lower {
+                    indicators.push(ThreatIndicator {
+                        category: "IDN".to_string(),
+                        description: format!("Internationalized domain, displayed as {}", displayed),
+                        weight: 10,
+                    });
+                    score += 10;
+                }
+                displayed
+            }
+            Err(_) => {
                 indicators.push(ThreatIndicator {
-                    category: "HOMOGLYPH".to_string(),
-                    description: format!("Possible homoglyph: '{}' may impersonate '{}'", fake, real),
+                    category: "IDN".to_string(),
+                    description: "Malformed internationalized domain".to_string(),
                     weight: 30,
                 });
                 score += 30;
+                domain_lower.clone()
             }
+        };
+
+        // Check for labels mixing scripts, like Latin with Cyrillic
+        for (label, scripts) in confusables::mixed_script_labels(&displayed) {
+            indicators.push(ThreatIndicator {
+                category: "MIXED_SCRIPT".to_string(),
+                description: format!("'{}' mixes {} characters", label, scripts.join(" and ")),
+                weight: 40,
+            });
+            score += 40;
+        }
+
+        // Check for lookalikes of protected domains
+        if let Some(target) = confusables::impersonated(&displayed, &self.protected) {
+            indicators.push(ThreatIndicator {
+                category: "CONFUSABLE".to_string(),
+                description: format!("Looks like {} ({})", target.domain, target.source.label()),
+                weight: 70,
+            });
+            score += 70;
         }
 
         // Check for IP address instead of domain
-        if domain.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        if domain::is_ip_literal(&domain) {
             indicators.push(ThreatIndicator {
                 category: "IP_URL".to_string(),
                 description: "URL uses IP address instead of domain name".to_string(),
@@ -156,11 +225,11 @@ impl ThreatIntel {
         }
 
         // Check for excessive subdomains (common in phishing)
-        let subdomain_count = domain.matches('.').count();
-        if subdomain_count > 3 {
+        let subdomain_count = domain::subdomain(&domain).map_or(0, |sub| sub.split('.').count());
+        if subdomain_count > 2 {
             indicators.push(ThreatIndicator {
                 category: "SUBDOMAIN".to_string(),
-                description: format!("Excessive subdomains: {} levels", subdomain_count + 1),
+                description: format!("Excessive subdomains: {} levels", subdomain_count),
                 weight: 15,
             });
             score += 15;
@@ -217,14 +286,32 @@ impl ThreatIntel {
             score += 15;
         }
 
+        // Check the local threat feeds
+        let hits = self.feeds.lookup(&domain, url);
+        for hit in &hits {
+            let matched = match &hit.matched {
+                FeedMatch::Domain => "domain".to_string(),
+                FeedMatch::ParentDomain(parent) => format!("parent domain {}", parent),
+                FeedMatch::UrlPrefix(prefix) => format!("URL {}", prefix),
+            };
+            indicators.push(ThreatIndicator {
+                category: "FEED".to_string(),
+                description: format!("Listed in {} by {}, {}", hit.feed, matched, hit.age()),
+                weight: 100,
+            });
+            score += 100;
+        }
+
         // Cap score at 100
         score = score.min(100);
 
-        // Determine threat level
-        let level = if self.trusted_domains.contains(&domain) {
+        // Determine threat level; feeds overrule everything
+        let level = if let Some(level) = hits.iter().map(|hit| hit.level.clone()).max() {
+            level
+        } else if self.trusted_domains.contains(&domain) {
             ThreatLevel::Safe
         } else if score >= 70 {
-            if indicators.iter().any(|i| i.category == "PHISHING" || i.category == "HOMOGLYPH") {
+            if indicators.iter().any(|i| matches!(i.category.as_str(), "PHISHING" | "CONFUSABLE" | "MIXED_SCRIPT")) {
                 ThreatLevel::Phishing
             } else {
                 ThreatLevel::Malicious
@@ -251,23 +338,7 @@ impl ThreatIntel {
     }
 
     fn extract_domain(&self, url: &str) -> String {
-        let url = url.trim();
-        let without_scheme = if let Some(pos) = url.find("://") {
-            &url[pos + 3..]
-        } else {
-            url
-        };
-        let without_path = if let Some(pos) = without_scheme.find('/') {
-            &without_scheme[..pos]
-        } else {
-            without_scheme
-        };
-        let without_port = if let Some(pos) = without_path.rfind(':') {
-            &without_path[..pos]
-        } else {
-            without_path
-        };
-        without_port.to_string()
+        domain::host(url).unwrap_or_default()
     }
 
     /// Check if a domain is trusted
@@ -280,9 +351,22 @@ impl ThreatIntel {
         self.trusted_domains.insert(domain.to_string());
     }
 
-    /// Block a domain
+    /// Block a domain, for this session
     pub fn block_domain(&mut self, domain: &str) {
         self.trusted_domains.remove(domain);
+        self.session_allowed.remove(domain);
+        self.feeds.block_domain("blocked domains", domain);
+    }
+
+    /// Let navigations to `domain` through for the rest of the session
+    pub fn allow_for_session(&mut self, domain: &str) {
+        self.session_allowed.insert(domain.to_string());
+    }
+
+    /// Whether navigating to `url` should be stopped, and why
+    pub fn check_navigation(&self, url: &str) -> Option<ThreatAnalysis> {
+        let analysis = self.analyze_url(url);
+        (analysis.level.blocks() && !self.session_allowed.contains(&analysis.domain)).then_some(analysis)
     }
 }
 
@@ -318,6 +402,47 @@ mod tests {
         assert!(result.indicators.iter().any(|i| i.category == "IP_URL"));
     }
 
+    #[test]
+    fn test_session_allowance() {
+        let mut intel = ThreatIntel::new();
+        let url = "http://paypal-login-verify.secure-account.tk/signin";
+        let blocked = intel.check_navigation(url).expect("should be blocked");
+        assert_eq!(blocked.level, ThreatLevel::Phishing);
+        assert!(intel.check_navigation("https://github.com/bad-antics").is_none());
+
+        intel.allow_for_session(&blocked.domain);
+        assert!(intel.check_navigation(url).is_none());
+        intel.block_domain(&blocked.domain);
+        assert!(intel.check_navigation(url).is_some());
+    }
+
+    #[test]
+    fn test_feed_hits() {
+        let mut intel = ThreatIntel::new();
+        let mut feeds = FeedIndex::default();
+        feeds.add_feed("urlhaus", "https://github.com/someone/dropper/\n", None).unwrap();
+        intel.set_feeds(feeds);
+
+        let result = intel.analyze_url("https://github.com/someone/dropper/raw/main/x.exe");
+        assert_eq!((result.level, result.score), (ThreatLevel::Malware, 100));
+        assert!(result.indicators[0].description.starts_with("Listed in urlhaus by URL github.com/someone/dropper/"));
+        assert_eq!(intel.analyze_url("https://github.com/bad-antics").level, ThreatLevel::Safe);
+
+        intel.block_domain("example.com");
+        assert_eq!(intel.analyze_url("https://www.example.com/").level, ThreatLevel::Malicious);
+    }
+
+    #[test]
+    fn test_lookalike_domains() {
+        let intel = ThreatIntel::new();
+        assert_eq!(intel.analyze_url("https://web1.example.com/").level, ThreatLevel::Safe);
+
+        let result = intel.analyze_url("https://xn--pypal-4ve.com/signin");
+        assert_eq!(result.level, ThreatLevel::Phishing);
+        let confusable = result.indicators.iter().find(|i| i.category == "CONFUSABLE").unwrap();
+        assert_eq!(confusable.description, "Looks like paypal.com (protected brand)");
+    }
+
     #[test]
     fn test_extract_domain() {
         let intel = ThreatIntel::new();
@@ -0,0 +1,254 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Container tabs — named identities, each with its own cookie jar and
+//! website data, and optionally its own user agent and proxy
+//!
+//! Containers are listed in `containers.json` in the data directory; each
+//! one keeps its website data under `containers/<id>/`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::Config;
+
+static STORE: Lazy<Containers> = Lazy::new(Containers::open);
+
+/// Colours a container can be tagged with
+pub const CONTAINER_COLORS: &[(&str, &str)] = &[
+    ("blue", "#37adff"),
+    ("turquoise", "#00c79a"),
+    ("green", "#51cd00"),
+    ("yellow", "#ffcb00"),
+    ("orange", "#ff9f00"),
+    ("red", "#ff613d"),
+    ("pink", "#ff4bda"),
+    ("purple", "#af51f5"),
+];
+
+/// Proxy schemes WebKit accepts
+const PROXY_SCHEMES: &[&str] = &["http", "https", "socks", "socks4", "socks4a", "socks5"];
+
+/// A named identity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Container {
+    /// Lower-case slug of the name, used for the data directory
+    pub id: String,
+    pub name: String,
+    /// Colour name from `CONTAINER_COLORS`
+    pub color: String,
+    pub user_agent: Option<String>,
+    /// Proxy URI, e.g. `socks5://127.0.0.1:9050`
+    pub proxy: Option<String>,
+}
+
+impl Container {
+    pub fn new(name: &str, color: &str) -> Self {
+        Self {
+            id: slug(name),
+            name: name.trim().to_string(),
+            color: color.to_string(),
+            user_agent: None,
+            proxy: None,
+        }
+    }
+
+    /// CSS colour for the container's tag
+    pub fn css_color(&self) -> &'static str {
+        CONTAINER_COLORS
+            .iter()
+            .find(|(name, _)| *name == self.color)
+            .map(|(_, css)| *css)
+            .unwrap_or(CONTAINER_COLORS[0].1)
+    }
+
+    /// Where the container's cookies, storage and databases live
+    pub fn data_dir(&self) -> Result<PathBuf, String> {
+        Config::data_dir()
+            .map(|dir| dir.join("containers").join(&self.id))
+            .map_err(|e| format!("No data directory: {}", e))
+    }
+
+    pub fn cache_dir(&self) -> Result<PathBuf, String> {
+        Config::cache_dir()
+            .map(|dir| dir.join("containers").join(&self.id))
+            .map_err(|e| format!("No cache directory: {}", e))
+    }
+}
+
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// All containers
+pub struct Containers {
+    path: Option<PathBuf>,
+    containers: RwLock<Vec<Container>>,
+}
+
+impl Containers {
+    /// The list shared by every window
+    pub fn global() -> &'static Containers {
+        &STORE
+    }
+
+    /// Load containers from `path`; without a path they last for the session only
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let containers = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| {
+                serde_json::from_str(&json)
+                    .map_err(|e| warn!("Ignoring unreadable containers.json: {}", e))
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        Self {
+            path,
+            containers: RwLock::new(containers),
+        }
+    }
+
+    fn open() -> Self {
+        let path = Config::data_dir()
+            .map_err(|e| warn!("Containers will not persist: {}", e))
+            .ok()
+            .map(|dir| dir.join("containers.json"));
+        Self::new(path)
+    }
+
+    pub fn all(&self) -> Vec<Container> {
+        self.containers.read().clone()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Container> {
+        self.containers.read().iter().find(|c| c.id == id).cloned()
+    }
+
+    /// Add a container, or update the one with the same id
+    pub fn save(&self, container: Container) -> Result<(), String> {
+        if container.id.is_empty() {
+            return Err("A container needs a name".to_string());
+        }
+        if !CONTAINER_COLORS.iter().any(|(name, _)| *name == container.color) {
+            return Err(format!("Unknown colour: {}", container.color));
+        }
+        if let Some(proxy) = &container.proxy {
+            let scheme = url::Url::parse(proxy).map(|u| u.scheme().to_string()).unwrap_or_default();
+            if !PROXY_SCHEMES.contains(&scheme.as_str()) {
+                return Err(format!("Not a proxy URI: {}", proxy));
+            }
+        }
+
+        let mut containers = self.containers.write();
+        match containers.iter_mut().find(|c| c.id == container.id) {
+            Some(existing) => *existing = container,
+            None => containers.push(container),
+        }
+        self.persist(&containers)
+    }
+
+    /// Remove a container and everything it stored. Nothing may still have
+    /// its website data open: its tabs must be closed and its data cleared.
+    pub fn remove(&self, id: &str) -> Result<(), String> {
+        let mut containers = self.containers.write();
+        let container = match containers.iter().position(|c| c.id == id) {
+            Some(pos) => containers.remove(pos),
+            None => return Ok(()),
+        };
+        self.persist(&containers)?;
+
+        if self.path.is_some() {
+            for dir in [container.data_dir(), container.cache_dir()].into_iter().flatten() {
+                if dir.exists() {
+                    fs::remove_dir_all(&dir)
+                        .map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The container a submitted form asks to delete, if it does. The
+    /// browser closes the container's tabs before calling `remove`.
+    pub fn deleted_by(query: &str) -> Option<String> {
+        let fields: HashMap<String, String> = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        if fields.get("action").map(String::as_str) != Some("delete") {
+            return None;
+        }
+        fields.get("name").map(|name| slug(name.trim())).filter(|id| !id.is_empty())
+    }
+
+    /// Apply a submitted `marshall://containers/save?...` form: save the
+    /// container it describes, or remove it when `action=delete`
+    pub fn apply_form(&self, query: &str) -> Result<(), String> {
+        let fields: HashMap<String, String> = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        let field = |name: &str| fields.get(name).map(|v| v.trim()).filter(|v| !v.is_empty());
+
+        let name = field("name").ok_or("No container name given")?;
+        if field("action") == Some("delete") {
+            return self.remove(&slug(name));
+        }
+
+        let mut container = Container::new(name, field("color").unwrap_or(CONTAINER_COLORS[0].0));
+        container.user_agent = field("user_agent").map(str::to_string);
+        container.proxy = field("proxy").map(str::to_string);
+        self.save(container)
+    }
+
+    fn persist(&self, containers: &[Container]) -> Result<(), String> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(containers)
+            .map_err(|e| format!("Failed to serialize containers: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn form_creates_and_validates_containers() {
+        let store = Containers::new(None);
+        store.apply_form("name=Research+Persona%201&color=red&proxy=socks5%3A%2F%2F127.0.0.1%3A9050").unwrap();
+
+        let container = store.get("research-persona-1").unwrap();
+        assert_eq!(container.name, "Research Persona 1");
+        assert_eq!(container.css_color(), "#ff613d");
+        assert_eq!(container.proxy.as_deref(), Some("socks5://127.0.0.1:9050"));
+
+        assert!(store.apply_form("name=Bad&color=red&proxy=ftp%3A%2F%2Fhost").is_err());
+        assert!(store.apply_form("name=%21%21&color=red").is_err());
+        assert_eq!(Containers::deleted_by("name=Research+Persona+1&action=delete").as_deref(), Some("research-persona-1"));
+        assert_eq!(Containers::deleted_by("name=Research+Persona+1"), None);
+        store.apply_form("name=Research+Persona+1&action=delete").unwrap();
+        assert!(store.all().is_empty());
+    }
+}
@@ -18,6 +18,12 @@ pub struct TabData {
     pub favicon: Option<String>,
     pub is_loading: bool,
     pub is_private: bool,
+    /// Id of the container the tab belongs to
+    pub container: Option<String>,
+    /// Back/forward entries, oldest first
+    pub history: Vec<String>,
+    /// Position of the current page in `history`
+    pub history_index: usize,
 }
 
 impl TabData {
@@ -29,6 +35,9 @@ impl TabData {
             favicon: None,
             is_loading: false,
             is_private,
+            container: None,
+            history: Vec::new(),
+            history_index: 0,
         }
     }
 }
@@ -60,6 +69,15 @@ impl TabManager {
         id
     }
 
+    /// Create a tab in a container; links it opens stay in the same container
+    pub fn create_container_tab(&self, url: &str, container: &str) -> String {
+        let id = self.create_tab(url, false);
+        if let Some(tab) = self.tabs.write().get_mut(&id) {
+            tab.container = Some(container.to_string());
+        }
+        id
+    }
+
     pub fn close_tab(&self, id: &str) -> bool {
         if self.tabs.write().remove(id).is_some() {
             let mut order = self.tab_order.write();
@@ -106,6 +124,35 @@ impl TabManager {
         }
     }
 
+    pub fn update_tab_favicon(&self, id: &str, favicon: Option<&str>) {
+        if let Some(tab) = self.tabs.write().get_mut(id) {
+            tab.favicon = favicon.map(str::to_string);
+        }
+    }
+
+    pub fn set_tab_loading(&self, id: &str, is_loading: bool) {
+        if let Some(tab) = self.tabs.write().get_mut(id) {
+            tab.is_loading = is_loading;
+        }
+    }
+
+    pub fn update_tab_history(&self, id: &str, entries: &[String], index: usize) {
+        if let Some(tab) = self.tabs.write().get_mut(id) {
+            tab.history = entries.to_vec();
+            tab.history_index = index;
+        }
+    }
+
+    /// Move a tab to `index` in the tab order
+    pub fn move_tab(&self, id: &str, index: usize) {
+        let mut order = self.tab_order.write();
+        if let Some(pos) = order.iter().position(|x| x == id) {
+            let id = order.remove(pos);
+            let index = index.min(order.len());
+            order.insert(index, id);
+        }
+    }
+
     pub fn tab_count(&self) -> usize {
         self.tabs.read().len()
     }
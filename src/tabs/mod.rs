@@ -4,6 +4,10 @@
 
 //! Tab management
 
+mod containers;
 mod manager;
+mod session;
 
-pub use manager::TabManager;
+pub use containers::{Container, Containers, CONTAINER_COLORS};
+pub use manager::{TabData, TabManager};
+pub use session::{SavedSession, Session, SessionStore, SessionTab, WindowSession};
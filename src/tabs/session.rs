@@ -0,0 +1,301 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Session persistence — open windows and their tabs, order and history,
+//! saved as JSON
+//!
+//! Every window's tabs go into the one `session.json`, written with
+//! `clean_exit: false` while the browser runs. Closing a window drops it from
+//! the file unless it is the last one, which is kept and marked clean, so a
+//! session still marked running at launch means the last run crashed.
+
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::TabManager;
+use crate::config::Config;
+
+static STORE: Lazy<Option<SessionStore>> = Lazy::new(|| {
+    SessionStore::open()
+        .map_err(|e| warn!("Session will not be saved: {}", e))
+        .ok()
+});
+
+/// One saved tab
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionTab {
+    pub url: String,
+    pub title: String,
+    /// Id of the tab's container
+    #[serde(default)]
+    pub container: Option<String>,
+    /// Back/forward entries, oldest first
+    #[serde(default)]
+    pub history: Vec<String>,
+    /// Position of the current page in `history`
+    #[serde(default)]
+    pub history_index: usize,
+}
+
+/// The tabs of a window at one point in time
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub tabs: Vec<SessionTab>,
+    /// Index of the active tab
+    pub active: usize,
+}
+
+impl Session {
+    /// Snapshot the tabs of `manager` in tab order. Private tabs are left out.
+    pub fn capture(manager: &TabManager) -> Self {
+        let active_id = manager.get_active_tab().map(|t| t.id);
+        let tabs: Vec<_> = manager
+            .get_all_tabs()
+            .into_iter()
+            .filter(|t| !t.is_private)
+            .collect();
+
+        Self {
+            active: tabs
+                .iter()
+                .position(|t| Some(&t.id) == active_id.as_ref())
+                .unwrap_or(0),
+            tabs: tabs
+                .into_iter()
+                .map(|t| SessionTab {
+                    url: t.url,
+                    title: t.title,
+                    container: t.container,
+                    history: t.history,
+                    history_index: t.history_index,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+}
+
+/// Everything in `session.json`: the open windows, in the order they opened
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SavedSession {
+    pub windows: Vec<Session>,
+    pub clean_exit: bool,
+    pub saved_at: Option<DateTime<Utc>>,
+}
+
+impl SavedSession {
+    pub fn is_empty(&self) -> bool {
+        self.windows.iter().all(Session::is_empty)
+    }
+
+    /// Whether the run that saved it ended without closing its last window
+    pub fn crashed(&self) -> bool {
+        !self.clean_exit
+    }
+
+    pub fn tab_count(&self) -> usize {
+        self.windows.iter().map(|w| w.tabs.len()).sum()
+    }
+}
+
+/// Reads the previous session at launch and writes the windows of this one
+pub struct SessionStore {
+    path: PathBuf,
+    /// Left by the last run, until the first window takes it
+    previous: Mutex<Option<SavedSession>>,
+    /// Open windows by key, oldest first
+    windows: Mutex<Vec<(usize, Session)>>,
+    next_key: Mutex<usize>,
+}
+
+impl SessionStore {
+    pub fn new(path: PathBuf) -> Self {
+        let previous = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str::<SavedSession>(&json)
+                .map_err(|e| warn!("Ignoring unreadable session {}: {}", path.display(), e))
+                .ok(),
+            Err(_) => None,
+        };
+        Self {
+            path,
+            previous: Mutex::new(previous.filter(|s| !s.is_empty())),
+            windows: Mutex::new(Vec::new()),
+            next_key: Mutex::new(0),
+        }
+    }
+
+    /// The store in the data directory
+    pub fn open() -> Result<Self, String> {
+        let dir = Config::data_dir().map_err(|e| format!("No data directory: {}", e))?;
+        Ok(Self::new(dir.join("session.json")))
+    }
+
+    /// The store shared by every window; None when sessions can't be saved
+    pub fn global() -> Option<&'static SessionStore> {
+        STORE.as_ref()
+    }
+
+    /// The session left by the last run, if it had any tabs. Only the first
+    /// caller gets it, so it is restored once however many windows open.
+    pub fn take_previous(&self) -> Option<SavedSession> {
+        self.previous.lock().take()
+    }
+
+    /// Give a new window its place in the saved session
+    pub fn add_window(&'static self) -> WindowSession {
+        let mut next_key = self.next_key.lock();
+        let key = *next_key;
+        *next_key += 1;
+        self.windows.lock().push((key, Session::default()));
+        WindowSession { store: self, key }
+    }
+
+    fn update(&self, key: usize, session: Session) -> Result<(), String> {
+        let snapshot = {
+            let mut windows = self.windows.lock();
+            if let Some(entry) = windows.iter_mut().find(|(k, _)| *k == key) {
+                entry.1 = session;
+            }
+            Self::snapshot(&windows, false)
+        };
+        self.write(&snapshot)
+    }
+
+    fn close(&self, key: usize, session: Session) -> Result<(), String> {
+        let snapshot = {
+            let mut windows = self.windows.lock();
+            if windows.len() > 1 {
+                windows.retain(|(k, _)| *k != key);
+                Self::snapshot(&windows, false)
+            } else {
+                windows.clear();
+                SavedSession {
+                    windows: vec![session],
+                    clean_exit: true,
+                    saved_at: Some(Utc::now()),
+                }
+            }
+        };
+        self.write(&snapshot)
+    }
+
+    /// Windows that have tabs to restore
+    fn snapshot(windows: &[(usize, Session)], clean_exit: bool) -> SavedSession {
+        SavedSession {
+            windows: windows
+                .iter()
+                .map(|(_, session)| session)
+                .filter(|session| !session.is_empty())
+                .cloned()
+                .collect(),
+            clean_exit,
+            saved_at: Some(Utc::now()),
+        }
+    }
+
+    fn write(&self, saved: &SavedSession) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(saved)
+            .map_err(|e| format!("Failed to serialize session: {}", e))?;
+        // Write then rename so a crash mid-write keeps the last good session
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, json).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+        fs::rename(&tmp, &self.path)
+            .map_err(|e| format!("Failed to replace {}: {}", self.path.display(), e))
+    }
+}
+
+/// One window's entry in the saved session
+pub struct WindowSession {
+    store: &'static SessionStore,
+    key: usize,
+}
+
+impl WindowSession {
+    /// Record the window's tabs while it is open
+    pub fn save(&self, session: Session) -> Result<(), String> {
+        self.store.update(self.key, session)
+    }
+
+    /// Record that the window closed with `session` open. Only the last
+    /// window's tabs are kept, and only it marks the exit clean.
+    pub fn close(&self, session: Session) -> Result<(), String> {
+        self.store.close(self.key, session)
+    }
+
+    pub fn store(&self) -> &'static SessionStore {
+        self.store
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_skips_private_tabs() {
+        let manager = TabManager::new();
+        let first = manager.create_tab("https://example.com/", false);
+        manager.update_tab_history(&first, &["marshall://home".to_string(), "https://example.com/".to_string()], 1);
+        manager.create_tab("https://secret.example/", true);
+        let third = manager.create_tab("https://example.org/", false);
+        manager.set_active_tab(&third);
+
+        let session = Session::capture(&manager);
+        let urls: Vec<_> = session.tabs.iter().map(|t| t.url.as_str()).collect();
+        assert_eq!(urls, ["https://example.com/", "https://example.org/"]);
+        assert_eq!(session.active, 1);
+        assert_eq!(session.tabs[0].history.len(), 2);
+        assert_eq!(session.tabs[0].history_index, 1);
+    }
+
+    #[test]
+    fn windows_share_the_session_until_the_last_closes() {
+        let dir = std::env::temp_dir().join(format!("marshall-session-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("session.json");
+        let store: &'static SessionStore = Box::leak(Box::new(SessionStore::new(path.clone())));
+
+        let manager = TabManager::new();
+        manager.create_tab("https://example.com/", false);
+        let first_tabs = Session::capture(&manager);
+        let manager = TabManager::new();
+        manager.create_tab("https://example.org/", false);
+        let second_tabs = Session::capture(&manager);
+
+        let first = store.add_window();
+        let second = store.add_window();
+        first.save(first_tabs.clone()).unwrap();
+        second.save(second_tabs.clone()).unwrap();
+        let saved = SessionStore::new(path.clone()).take_previous().unwrap();
+        assert!(saved.crashed());
+        assert_eq!(saved.windows, [first_tabs.clone(), second_tabs.clone()]);
+
+        // Closing one of two windows drops it without marking a clean exit
+        first.close(first_tabs).unwrap();
+        let saved = SessionStore::new(path.clone()).take_previous().unwrap();
+        assert!(saved.crashed());
+        assert_eq!(saved.windows, std::slice::from_ref(&second_tabs));
+
+        second.close(second_tabs.clone()).unwrap();
+        let store = SessionStore::new(path);
+        let saved = store.take_previous().unwrap();
+        assert!(!saved.crashed());
+        assert_eq!(saved.windows, [second_tabs]);
+        assert!(store.take_previous().is_none());
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,418 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Bookmarks bar under the toolbar, showing the toolbar folder
+//!
+//! Folders open as menus. Items are reordered by dragging them along the bar
+//! or onto a folder, links dragged from a page are bookmarked where they are
+//! dropped, and a right click renames, deletes or adds a folder.
+
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Button, DestDefaults, EventBox, Image, Label, Menu, MenuItem, Orientation, SelectionData, TargetEntry, TargetFlags, Widget, Window};
+use gdk::{DragAction, ModifierType};
+use std::cell::RefCell;
+use std::rc::Rc;
+use tracing::warn;
+
+use crate::bookmarks::{Bookmark, BookmarkFolder, BookmarkManager, BookmarkNode, TOOLBAR_FOLDER};
+use super::dialogs;
+
+/// Items dragged within Marshall carry their id under this target
+const ITEM_TARGET: &str = "application/x-marshall-bookmark";
+const INFO_ITEM: u32 = 0;
+const INFO_URI: u32 = 1;
+/// Longer titles are shortened on the bar and in folder menus
+const MAX_TITLE_CHARS: usize = 28;
+
+/// Called with a URL and whether it should open in a new tab
+type OpenCallback = Rc<RefCell<Option<Box<dyn Fn(&str, bool)>>>>;
+
+thread_local! {
+    /// Every window's bar, so a change made in one window shows in all
+    static BARS: RefCell<Vec<BookmarksBar>> = RefCell::new(Vec::new());
+}
+
+#[derive(Clone)]
+pub struct BookmarksBar {
+    container: EventBox,
+    items: GtkBox,
+    on_open: OpenCallback,
+}
+
+impl BookmarksBar {
+    pub fn new() -> Self {
+        // The event box gives the bar a window, for right clicks between items
+        let container = EventBox::new();
+        container.style_context().add_class("marshall-bookmarksbar");
+        let items = GtkBox::new(Orientation::Horizontal, 2);
+        container.add(&items);
+
+        let bar = Self {
+            container,
+            items,
+            on_open: Rc::new(RefCell::new(None)),
+        };
+
+        bar.container.drag_dest_set(DestDefaults::ALL, &drop_targets(), DragAction::MOVE | DragAction::COPY);
+        let this = bar.clone();
+        bar.container.connect_drag_data_received(move |_, _, x, _, data, info, _| {
+            let index = this.index_at(x);
+            this.receive(data, info, TOOLBAR_FOLDER, index);
+        });
+
+        let this = bar.clone();
+        bar.container.connect_button_press_event(move |_, event| {
+            if event.button() == 3 {
+                this.show_menu(None, event);
+                gtk::Inhibit(true)
+            } else {
+                gtk::Inhibit(false)
+            }
+        });
+
+        BARS.with(|bars| bars.borrow_mut().push(bar.clone()));
+        bar.container.connect_destroy(|container| {
+            BARS.with(|bars| bars.borrow_mut().retain(|b| &b.container != container));
+        });
+
+        bar.rebuild();
+        bar
+    }
+
+    pub fn container(&self) -> &EventBox {
+        &self.container
+    }
+
+    pub fn connect_open<F: Fn(&str, bool) + 'static>(&self, f: F) {
+        *self.on_open.borrow_mut() = Some(Box::new(f));
+    }
+
+    /// Rebuild every window's bar after the bookmarks changed
+    pub fn refresh_all() {
+        // Cloned first: rebuilding may run destroy handlers that edit the list
+        let bars = BARS.with(|bars| bars.borrow().clone());
+        for bar in bars {
+            bar.rebuild();
+        }
+    }
+
+    fn rebuild(&self) {
+        for child in self.items.children() {
+            self.items.remove(&child);
+        }
+
+        let manager = BookmarkManager::global().read();
+        let nodes = manager.children(Some(TOOLBAR_FOLDER));
+        if nodes.is_empty() {
+            let hint = Label::new(Some("Drag links here to bookmark them"));
+            hint.style_context().add_class("bookmarks-hint");
+            self.items.pack_start(&hint, false, false, 8);
+        }
+        for node in nodes {
+            let button = match node {
+                BookmarkNode::Bookmark(bookmark) => self.bookmark_button(bookmark),
+                BookmarkNode::Folder(folder) => self.folder_button(folder),
+            };
+            self.items.pack_start(&button, false, false, 0);
+        }
+        self.items.show_all();
+    }
+
+    fn bookmark_button(&self, bookmark: &Bookmark) -> Button {
+        let button = item_button(&title_of(bookmark), "text-html-symbolic");
+        button.set_tooltip_text(Some(&format!("{}\n{}", bookmark.title, bookmark.url)));
+
+        let this = self.clone();
+        let url = bookmark.url.clone();
+        button.connect_clicked(move |_| this.open(&url, false));
+
+        // Middle click opens in a new tab, right click edits
+        let this = self.clone();
+        let id = bookmark.id.clone();
+        let url = bookmark.url.clone();
+        button.connect_button_press_event(move |_, event| match event.button() {
+            2 => {
+                this.open(&url, true);
+                gtk::Inhibit(true)
+            }
+            3 => {
+                this.show_menu(Some(&id), event);
+                gtk::Inhibit(true)
+            }
+            _ => gtk::Inhibit(false),
+        });
+
+        make_draggable(&button, &bookmark.id, Some(&bookmark.url));
+        button
+    }
+
+    fn folder_button(&self, folder: &BookmarkFolder) -> Button {
+        let button = item_button(&shorten(&folder.name), "folder-symbolic");
+        button.set_tooltip_text(Some(&folder.name));
+
+        let this = self.clone();
+        let id = folder.id.clone();
+        button.connect_clicked(move |button| {
+            let menu = this.folder_menu(&BookmarkManager::global().read(), &id);
+            menu.popup_at_widget(button, gdk::Gravity::SouthWest, gdk::Gravity::NorthWest, None);
+        });
+
+        let this = self.clone();
+        let id = folder.id.clone();
+        button.connect_button_press_event(move |_, event| {
+            if event.button() == 3 {
+                this.show_menu(Some(&id), event);
+                gtk::Inhibit(true)
+            } else {
+                gtk::Inhibit(false)
+            }
+        });
+
+        // Whatever is dropped on a folder goes to its end
+        button.drag_dest_set(DestDefaults::ALL, &drop_targets(), DragAction::MOVE | DragAction::COPY);
+        let this = self.clone();
+        let id = folder.id.clone();
+        button.connect_drag_data_received(move |_, _, _, _, data, info, _| {
+            this.receive(data, info, &id, usize::MAX);
+        });
+
+        make_draggable(&button, &folder.id, None);
+        button
+    }
+
+    /// The contents of a folder, subfolders as submenus
+    fn folder_menu(&self, manager: &BookmarkManager, folder_id: &str) -> Menu {
+        let menu = Menu::new();
+        let nodes = manager.children(Some(folder_id));
+        let mut urls = Vec::new();
+        for node in &nodes {
+            match node {
+                BookmarkNode::Folder(folder) => {
+                    let item = MenuItem::with_label(&shorten(&folder.name));
+                    item.set_submenu(Some(&self.folder_menu(manager, &folder.id)));
+                    menu.append(&item);
+                }
+                BookmarkNode::Bookmark(bookmark) => {
+                    let item = MenuItem::with_label(&title_of(bookmark));
+                    item.set_tooltip_text(Some(&bookmark.url));
+                    let this = self.clone();
+                    let url = bookmark.url.clone();
+                    item.connect_activate(move |_| this.open(&url, false));
+                    menu.append(&item);
+                    urls.push(bookmark.url.clone());
+                }
+            }
+        }
+
+        if nodes.is_empty() {
+            let empty = MenuItem::with_label("(Empty)");
+            empty.set_sensitive(false);
+            menu.append(&empty);
+        } else if !urls.is_empty() {
+            menu.append(&gtk::SeparatorMenuItem::new());
+            let open_all = MenuItem::with_label("Open All in Tabs");
+            let this = self.clone();
+            open_all.connect_activate(move |_| {
+                for url in &urls {
+                    this.open(url, true);
+                }
+            });
+            menu.append(&open_all);
+        }
+        menu.show_all();
+        menu
+    }
+
+    /// Right-click menu for an item, or for the bar itself
+    fn show_menu(&self, id: Option<&str>, event: &gdk::EventButton) {
+        let menu = Menu::new();
+        let manager = BookmarkManager::global().read();
+
+        if let Some(bookmark) = id.and_then(|id| manager.get_bookmark(id)) {
+            let open = MenuItem::with_label("Open in New Tab");
+            let this = self.clone();
+            let url = bookmark.url.clone();
+            open.connect_activate(move |_| this.open(&url, true));
+            menu.append(&open);
+            menu.append(&gtk::SeparatorMenuItem::new());
+
+            let rename = MenuItem::with_label("Rename…");
+            let this = self.clone();
+            let (id, title) = (bookmark.id.clone(), bookmark.title.clone());
+            rename.connect_activate(move |_| {
+                if let Some(title) = this.prompt("Rename Bookmark", "Name", &title) {
+                    let result = BookmarkManager::global().write().rename_bookmark(&id, &title);
+                    finish("Bookmark not renamed", result);
+                }
+            });
+            menu.append(&rename);
+
+            let delete = MenuItem::with_label("Delete");
+            let id = bookmark.id.clone();
+            delete.connect_activate(move |_| {
+                BookmarkManager::global().write().remove_bookmark(&id);
+                Self::refresh_all();
+            });
+            menu.append(&delete);
+            menu.append(&gtk::SeparatorMenuItem::new());
+        } else if let Some(folder) = id.and_then(|id| manager.get_folder(id)) {
+            let rename = MenuItem::with_label("Rename…");
+            let this = self.clone();
+            let (id, name) = (folder.id.clone(), folder.name.clone());
+            rename.connect_activate(move |_| {
+                if let Some(name) = this.prompt("Rename Folder", "Name", &name) {
+                    let result = BookmarkManager::global().write().rename_folder(&id, &name);
+                    finish("Folder not renamed", result);
+                }
+            });
+            menu.append(&rename);
+
+            let delete = MenuItem::with_label("Delete Folder");
+            let id = folder.id.clone();
+            delete.connect_activate(move |_| {
+                let result = BookmarkManager::global().write().delete_folder(&id).map(|_| ());
+                finish("Folder not deleted", result);
+            });
+            menu.append(&delete);
+            menu.append(&gtk::SeparatorMenuItem::new());
+        }
+        drop(manager);
+
+        let new_folder = MenuItem::with_label("New Folder…");
+        let this = self.clone();
+        new_folder.connect_activate(move |_| {
+            if let Some(name) = this.prompt("New Folder", "Name", "") {
+                let result = BookmarkManager::global().write().create_folder(&name, TOOLBAR_FOLDER).map(|_| ());
+                finish("Folder not created", result);
+            }
+        });
+        menu.append(&new_folder);
+
+        menu.show_all();
+        menu.popup_at_pointer(Some(&**event));
+    }
+
+    /// Move a dragged item, or bookmark dropped links, to `index` in
+    /// `folder_id`
+    fn receive(&self, data: &SelectionData, info: u32, folder_id: &str, index: usize) {
+        let mut manager = BookmarkManager::global().write();
+        let result = if info == INFO_ITEM {
+            let id = String::from_utf8_lossy(&data.data()).to_string();
+            // The index counts the item itself when it moves along its folder
+            let current = manager.children(Some(folder_id)).iter().position(|node| node.id() == id);
+            let index = match current {
+                Some(current) if current < index => index - 1,
+                _ => index,
+            };
+            if manager.get_folder(&id).is_some() {
+                manager.move_folder(&id, folder_id, index)
+            } else {
+                manager.move_bookmark(&id, folder_id, index)
+            }
+        } else {
+            data.uris()
+                .iter()
+                .enumerate()
+                .map(|(offset, uri)| {
+                    let id = manager.add_bookmark(uri, uri, Some(folder_id));
+                    manager.move_bookmark(&id, folder_id, index.saturating_add(offset))
+                })
+                .collect()
+        };
+        drop(manager);
+        finish("Bookmark not moved", result);
+    }
+
+    /// Position on the bar for a drop at `x`
+    fn index_at(&self, x: i32) -> usize {
+        self.items
+            .children()
+            .iter()
+            .filter(|child| child.is::<Button>())
+            .take_while(|child| {
+                let left = child.translate_coordinates(&self.container, 0, 0).map(|(x, _)| x).unwrap_or(0);
+                left + child.allocated_width() / 2 < x
+            })
+            .count()
+    }
+
+    fn open(&self, url: &str, new_tab: bool) {
+        if let Some(f) = self.on_open.borrow().as_ref() {
+            f(url, new_tab);
+        }
+    }
+
+    fn prompt(&self, title: &str, label: &str, initial: &str) -> Option<String> {
+        let window = self.container.toplevel()?.downcast::<Window>().ok()?;
+        dialogs::prompt_text(&window, title, label, initial)
+    }
+}
+
+impl Default for BookmarksBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Log a failed edit; either way every bar shows the current bookmarks
+fn finish(what: &str, result: Result<(), String>) {
+    if let Err(e) = result {
+        warn!("{}: {}", what, e);
+    }
+    BookmarksBar::refresh_all();
+}
+
+fn drop_targets() -> [TargetEntry; 2] {
+    [
+        TargetEntry::new(ITEM_TARGET, TargetFlags::SAME_APP, INFO_ITEM),
+        TargetEntry::new("text/uri-list", TargetFlags::empty(), INFO_URI),
+    ]
+}
+
+/// Let an item be dragged along the bar; bookmarks can also be dropped on
+/// a page or another application as a link
+fn make_draggable(widget: &impl IsA<Widget>, id: &str, url: Option<&str>) {
+    let mut targets = vec![TargetEntry::new(ITEM_TARGET, TargetFlags::SAME_APP, INFO_ITEM)];
+    if url.is_some() {
+        targets.push(TargetEntry::new("text/uri-list", TargetFlags::empty(), INFO_URI));
+    }
+    widget.drag_source_set(ModifierType::BUTTON1_MASK, &targets, DragAction::MOVE | DragAction::COPY);
+    widget.drag_source_set_icon_name(if url.is_some() { "text-html-symbolic" } else { "folder-symbolic" });
+
+    let id = id.to_string();
+    let url = url.map(str::to_string);
+    widget.connect_drag_data_get(move |_, _, data, info, _| match (info, &url) {
+        (INFO_URI, Some(url)) => {
+            data.set_uris(&[url.as_str()]);
+        }
+        _ => data.set(&data.target(), 8, id.as_bytes()),
+    });
+}
+
+fn item_button(label: &str, icon: &str) -> Button {
+    let button = Button::with_label(label);
+    button.set_relief(gtk::ReliefStyle::None);
+    button.set_image(Some(&Image::from_icon_name(Some(icon), gtk::IconSize::Menu)));
+    button.set_always_show_image(true);
+    button.set_focus_on_click(false);
+    button.style_context().add_class("bookmark-item");
+    button
+}
+
+fn title_of(bookmark: &Bookmark) -> String {
+    if bookmark.title.is_empty() {
+        shorten(&bookmark.url)
+    } else {
+        shorten(&bookmark.title)
+    }
+}
+
+fn shorten(text: &str) -> String {
+    if text.chars().count() > MAX_TITLE_CHARS {
+        let short: String = text.chars().take(MAX_TITLE_CHARS - 1).collect();
+        format!("{}…", short.trim_end())
+    } else {
+        text.to_string()
+    }
+}
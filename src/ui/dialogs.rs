@@ -6,6 +6,7 @@
 
 use gtk::prelude::*;
 use gtk::{Dialog, Label, Box as GtkBox, Orientation, Window, ResponseType, ButtonsType, MessageType};
+use std::path::PathBuf;
 
 /// About dialog
 pub fn show_about_dialog<W: IsA<Window>>(parent: &W) {
@@ -39,3 +40,113 @@ pub fn show_settings_dialog<W: IsA<Window>>(parent: &W) {
     dialog.run();
     dialog.close();
 }
+
+/// Ask for a bookmark file exported by another browser
+pub fn choose_bookmark_import<W: IsA<Window>>(parent: &W) -> Option<PathBuf> {
+    let dialog = gtk::FileChooserNative::new(
+        Some("Import Bookmarks"),
+        Some(parent),
+        gtk::FileChooserAction::Open,
+        Some("Import"),
+        Some("Cancel"),
+    );
+    let filter = gtk::FileFilter::new();
+    filter.set_name(Some("Bookmark files (HTML, JSON, Chromium Bookmarks)"));
+    filter.add_pattern("*.html");
+    filter.add_pattern("*.htm");
+    filter.add_pattern("*.json");
+    filter.add_pattern("Bookmarks");
+    dialog.add_filter(&filter);
+
+    let path = match dialog.run() {
+        ResponseType::Accept => dialog.filename(),
+        _ => None,
+    };
+    dialog.destroy();
+    path
+}
+
+/// Ask where to save the bookmarks as Netscape HTML
+pub fn choose_bookmark_export<W: IsA<Window>>(parent: &W) -> Option<PathBuf> {
+    let dialog = gtk::FileChooserNative::new(
+        Some("Export Bookmarks"),
+        Some(parent),
+        gtk::FileChooserAction::Save,
+        Some("Export"),
+        Some("Cancel"),
+    );
+    dialog.set_do_overwrite_confirmation(true);
+    dialog.set_current_name("bookmarks.html");
+
+    let path = match dialog.run() {
+        ResponseType::Accept => dialog.filename(),
+        _ => None,
+    };
+    dialog.destroy();
+    path
+}
+
+/// A one-line message with an OK button
+pub fn show_message<W: IsA<Window>>(parent: &W, kind: MessageType, text: &str) {
+    let dialog = gtk::MessageDialog::new(
+        Some(parent),
+        gtk::DialogFlags::MODAL,
+        kind,
+        ButtonsType::Ok,
+        text,
+    );
+    dialog.run();
+    dialog.close();
+}
+
+/// Ask for a line of text, such as a folder name; None when cancelled
+pub fn prompt_text<W: IsA<Window>>(parent: &W, title: &str, label: &str, initial: &str) -> Option<String> {
+    let dialog = Dialog::with_buttons(
+        Some(title),
+        Some(parent),
+        gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+        &[("Cancel", ResponseType::Cancel), ("OK", ResponseType::Accept)],
+    );
+    dialog.set_default_response(ResponseType::Accept);
+
+    let content = GtkBox::new(Orientation::Vertical, 6);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+    let entry = gtk::Entry::new();
+    entry.set_text(initial);
+    entry.set_activates_default(true);
+    let label = Label::new(Some(label));
+    label.set_halign(gtk::Align::Start);
+    content.pack_start(&label, false, false, 0);
+    content.pack_start(&entry, false, false, 0);
+    dialog.content_area().add(&content);
+    dialog.show_all();
+
+    let text = match dialog.run() {
+        ResponseType::Accept => Some(entry.text().trim().to_string()).filter(|t| !t.is_empty()),
+        _ => None,
+    };
+    dialog.close();
+    text
+}
+
+/// Warn before saving a file that looks unsafe; true to save it anyway
+pub fn confirm_download(parent: Option<&Window>, filename: &str, warnings: &[String]) -> bool {
+    let dialog = gtk::MessageDialog::new(
+        parent,
+        gtk::DialogFlags::MODAL,
+        MessageType::Warning,
+        ButtonsType::None,
+        &format!("“{}” may be dangerous", filename),
+    );
+    dialog.set_secondary_text(Some(&warnings.iter().map(|w| format!("• {}", w)).collect::<Vec<_>>().join("\n")));
+    dialog.add_button("Don't Save", ResponseType::Cancel);
+    dialog.add_button("Save Anyway", ResponseType::Accept);
+    dialog.set_default_response(ResponseType::Cancel);
+
+    let keep = dialog.run() == ResponseType::Accept;
+    dialog.close();
+    keep
+}
@@ -0,0 +1,217 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! WebKit downloads, fed into the download manager
+//!
+//! Every context a window uses is watched once. A download is checked when
+//! its file name is known and the user is asked before anything flagged by
+//! `downloads::assess` is saved. WebKit can't resume a transfer, so pausing
+//! stops it and resuming starts it over.
+
+use gtk::prelude::*;
+use gtk::Window;
+use webkit2gtk::{Download, DownloadExt, URIRequestExt, URIResponseExt, WebContext, WebContextExt, WebViewExt};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+use crate::downloads::{self, DownloadManager, DownloadState};
+use super::dialogs;
+
+thread_local! {
+    /// Contexts whose downloads are already captured
+    static WATCHED: RefCell<Vec<glib::WeakRef<WebContext>>> = RefCell::new(Vec::new());
+    /// Transfers running now, by record id
+    static ACTIVE: RefCell<HashMap<String, Download>> = RefCell::new(HashMap::new());
+    /// The context each download came from, to start it again in
+    static SOURCES: RefCell<HashMap<String, glib::WeakRef<WebContext>>> = RefCell::new(HashMap::new());
+    /// URLs being restarted, and the record they belong to
+    static RESTARTING: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+}
+
+/// Capture the downloads of `context` into the download manager, saving
+/// files in `dir`. Downloads of private contexts aren't recorded on disk.
+pub fn watch(context: &WebContext, private: bool, dir: PathBuf) {
+    let watched = WATCHED.with(|watched| {
+        let mut watched = watched.borrow_mut();
+        watched.retain(|w| w.upgrade().is_some());
+        if watched.iter().any(|w| w.upgrade().as_ref() == Some(context)) {
+            return true;
+        }
+        watched.push(context.downgrade());
+        false
+    });
+    if !watched {
+        context.connect_download_started(move |context, download| track(context, download, private, &dir));
+    }
+}
+
+fn track(context: &WebContext, download: &Download, private: bool, dir: &std::path::Path) {
+    let url = download
+        .request()
+        .and_then(|r| r.uri())
+        .map(|u| u.to_string())
+        .unwrap_or_default();
+    let id = match RESTARTING.with(|r| r.borrow_mut().remove(&url)) {
+        Some(id) => {
+            DownloadManager::global().write().restart(&id);
+            id
+        }
+        None => DownloadManager::global().write().start(&url, private),
+    };
+    ACTIVE.with(|active| active.borrow_mut().insert(id.clone(), download.clone()));
+    SOURCES.with(|sources| sources.borrow_mut().insert(id.clone(), context.downgrade()));
+
+    let dir = dir.to_path_buf();
+    let record_id = id.clone();
+    download.connect_decide_destination(move |download, suggested| {
+        let path = downloads::unique_path(&dir, suggested);
+        let mut manager = DownloadManager::global().write();
+        manager.set_destination(&record_id, &path);
+        let warnings = manager.get(&record_id).map(|r| r.warnings.clone()).unwrap_or_default();
+        drop(manager);
+
+        let parent = download
+            .web_view()
+            .and_then(|view| view.toplevel())
+            .and_then(|top| top.downcast::<Window>().ok());
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if !warnings.is_empty() && !dialogs::confirm_download(parent.as_ref(), &name, &warnings) {
+            DownloadManager::global()
+                .write()
+                .set_state(&record_id, DownloadState::Cancelled, Some("Not saved after warning".to_string()));
+            download.cancel();
+            return true;
+        }
+
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Failed to create {}: {}", dir.display(), e);
+        }
+        match glib::filename_to_uri(&path, None) {
+            Ok(uri) => download.set_destination(&uri),
+            Err(e) => warn!("Bad download path {}: {}", path.display(), e),
+        }
+        true
+    });
+
+    let record_id = id.clone();
+    download.connect_received_data(move |download, _| {
+        let total = download.response().map(|r| r.content_length()).unwrap_or(0);
+        DownloadManager::global()
+            .write()
+            .set_progress(&record_id, download.received_data_length(), total);
+    });
+
+    // Pausing and cancelling set the state first; anything else is an error
+    let record_id = id.clone();
+    download.connect_failed(move |_, error| {
+        let mut manager = DownloadManager::global().write();
+        if manager.get(&record_id).is_some_and(|r| r.state == DownloadState::InProgress) {
+            manager.set_state(&record_id, DownloadState::Failed, Some(error.to_string()));
+        }
+    });
+
+    // Emitted after `failed` too
+    download.connect_finished(move |_| {
+        ACTIVE.with(|active| active.borrow_mut().remove(&id));
+        let path = {
+            let mut manager = DownloadManager::global().write();
+            match manager.get(&id) {
+                Some(record) if record.state == DownloadState::InProgress => {
+                    manager.set_state(&id, DownloadState::Completed, None);
+                    manager.get(&id).and_then(|r| r.path.clone())
+                }
+                _ => None,
+            }
+        };
+        if let Some(path) = path {
+            info!("Downloaded {}", path.display());
+            hash_in_background(id.clone(), path);
+        }
+    });
+}
+
+/// Hash a finished file off the main thread
+fn hash_in_background(id: String, path: PathBuf) {
+    let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+    std::thread::spawn(move || {
+        let _ = sender.send(downloads::sha256_file(&path));
+    });
+    receiver.attach(None, move |result| {
+        match result {
+            Ok(digest) => DownloadManager::global().write().set_sha256(&id, &digest),
+            Err(e) => warn!("{}", e),
+        }
+        glib::Continue(false)
+    });
+}
+
+/// Run an action from the downloads page: `pause`, `resume`, `retry`,
+/// `cancel`, `remove`, `show` or `clear`
+pub fn perform(action: &str, id: &str, private: bool) {
+    match action {
+        "pause" => stop(id, DownloadState::Paused),
+        "cancel" => stop(id, DownloadState::Cancelled),
+        "resume" | "retry" => restart(id),
+        "remove" => {
+            stop(id, DownloadState::Cancelled);
+            DownloadManager::global().write().remove(id);
+        }
+        "show" => show_in_folder(id),
+        "clear" => DownloadManager::global().write().clear_finished(private),
+        _ => warn!("Unknown download action {}", action),
+    }
+}
+
+/// Cancel the downloads of private windows and forget them
+pub fn end_private_session() {
+    let ids: Vec<String> = DownloadManager::global()
+        .read()
+        .all(true)
+        .iter()
+        .map(|r| r.id.clone())
+        .collect();
+    for id in &ids {
+        stop(id, DownloadState::Cancelled);
+    }
+    DownloadManager::global().write().forget_private();
+}
+
+fn stop(id: &str, state: DownloadState) {
+    let download = ACTIVE.with(|active| active.borrow().get(id).cloned());
+    if let Some(download) = download {
+        DownloadManager::global().write().set_state(id, state, None);
+        download.cancel();
+    }
+}
+
+fn restart(id: &str) {
+    let url = match DownloadManager::global().read().get(id) {
+        Some(record) if record.state != DownloadState::InProgress => record.url.clone(),
+        _ => return,
+    };
+    let context = SOURCES
+        .with(|sources| sources.borrow().get(id).and_then(|w| w.upgrade()))
+        .or_else(WebContext::default);
+    match context {
+        Some(context) => {
+            RESTARTING.with(|r| r.borrow_mut().insert(url.clone(), id.to_string()));
+            context.download_uri(&url);
+        }
+        None => warn!("No context to download {} in", url),
+    }
+}
+
+fn show_in_folder(id: &str) {
+    let dir = DownloadManager::global()
+        .read()
+        .get(id)
+        .and_then(|r| r.path.as_ref()?.parent().map(|p| p.to_path_buf()));
+    if let Some(uri) = dir.and_then(|dir| glib::filename_to_uri(&dir, None).ok()) {
+        if let Err(e) = gio::AppInfo::launch_default_for_uri(&uri, None::<&gio::AppLaunchContext>) {
+            warn!("Failed to open {}: {}", uri, e);
+        }
+    }
+}
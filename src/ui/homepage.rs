@@ -5,6 +5,33 @@
 //! Marshall Browser - Custom Homepage and Internal Pages
 //! Fully custom privacy-focused browser experience
 
+use once_cell::sync::Lazy;
+
+use crate::adblock::ListInfo;
+use crate::database::SiteProfile;
+use crate::downloads::{DownloadRecord, DownloadState};
+use crate::privacy::REFERRER_POLICIES;
+use crate::search::typosquat::{Permutation, Technique};
+use crate::security::leaktest::{LeakCheck, LeakPromises, Verdict};
+use crate::security::threat_intel::ThreatAnalysis;
+use crate::tabs::{Container, CONTAINER_COLORS};
+
+/// Secret that internal pages put in the links and forms that change
+/// something. Any web page can navigate to a `marshall://` URL, but none can
+/// read an internal page to learn it.
+static ACTION_TOKEN: Lazy<String> = Lazy::new(|| uuid::Uuid::new_v4().simple().to_string());
+
+/// Whether `uri` carries the action token, i.e. an internal page issued it
+pub fn has_action_token(uri: &str) -> bool {
+    let query = uri.split_once('?').map(|(_, q)| q).unwrap_or("");
+    url::form_urlencoded::parse(query.as_bytes()).any(|(key, value)| key == "token" && value == ACTION_TOKEN.as_str())
+}
+
+/// Hidden form field with the action token
+fn token_field() -> String {
+    format!("<input type=\"hidden\" name=\"token\" value=\"{}\">", ACTION_TOKEN.as_str())
+}
+
 /// Generate the main Marshall homepage
 pub fn generate_homepage() -> String {
     r##"<!DOCTYPE html>
@@ -1178,17 +1205,35 @@ pub fn generate_assistant_page() -> String {
 </html>"##.to_string()
 }
 
-/// Generate OSINT Tools page
-pub fn generate_osint_page() -> String {
-    r##"<!DOCTYPE html>
+/// Generate OSINT Tools page. `monitored` domains get one-click typosquat
+/// reports.
+pub fn generate_osint_page(monitored: &[String]) -> String {
+    let token = ACTION_TOKEN.as_str();
+    let monitored = if monitored.is_empty() {
+        String::new()
+    } else {
+        let links: String = monitored
+            .iter()
+            .map(|domain| {
+                format!(
+                    r#"<a href="marshall://osint/typosquat?domain={}&amp;token={}" class="tool-btn">{}</a>"#,
+                    urlencoding::encode(domain),
+                    token,
+                    html_escape(domain)
+                )
+            })
+            .collect();
+        format!(r#"<div class="tool-actions monitored">{}</div>"#, links)
+    };
+    format!(r##"<!DOCTYPE html>
 <html lang="en">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>Marshall OSINT Tools</title>
     <style>
-        * { box-sizing: border-box; margin: 0; padding: 0; }
-        :root {
+        * {{ box-sizing: border-box; margin: 0; padding: 0; }}
+        :root {{
             --red: #ff0040;
             --green: #00ff88;
             --bg: #0d0d0d;
@@ -1197,41 +1242,41 @@ pub fn generate_osint_page() -> String {
             --fg: #e0e0e0;
             --fg-dim: #808080;
             --border: #333;
-        }
-        html, body {
+        }}
+        html, body {{
             background: var(--bg);
             color: var(--fg);
             font-family: 'Segoe UI', -apple-system, sans-serif;
             min-height: 100vh;
-        }
-        a { color: var(--red); text-decoration: none; }
-        a:hover { text-decoration: underline; }
-        .back-link {
+        }}
+        a {{ color: var(--red); text-decoration: none; }}
+        a:hover {{ text-decoration: underline; }}
+        .back-link {{
             display: inline-flex;
             align-items: center;
             gap: 8px;
             color: var(--fg-dim);
             font-size: 14px;
             margin: 1rem 2rem;
-        }
-        .header {
+        }}
+        .header {{
             background: linear-gradient(135deg, var(--bg2) 0%, var(--bg) 100%);
             border-bottom: 2px solid var(--red);
             padding: 2rem;
             text-align: center;
-        }
-        .header h1 { font-size: 28px; color: var(--red); margin-bottom: 0.5rem; }
-        .header p { color: var(--fg-dim); }
-        .search-section {
+        }}
+        .header h1 {{ font-size: 28px; color: var(--red); margin-bottom: 0.5rem; }}
+        .header p {{ color: var(--fg-dim); }}
+        .search-section {{
             max-width: 600px;
             margin: 2rem auto;
             padding: 0 2rem;
-        }
-        .search-form {
+        }}
+        .search-form {{
             display: flex;
             gap: 12px;
-        }
-        .search-form input {
+        }}
+        .search-form input {{
             flex: 1;
             padding: 14px 18px;
             background: var(--bg2);
@@ -1240,9 +1285,9 @@ pub fn generate_osint_page() -> String {
             color: var(--fg);
             font-size: 15px;
             outline: none;
-        }
-        .search-form input:focus { border-color: var(--red); }
-        .search-form button {
+        }}
+        .search-form input:focus {{ border-color: var(--red); }}
+        .search-form button {{
             padding: 14px 28px;
             background: var(--red);
             border: none;
@@ -1250,31 +1295,31 @@ pub fn generate_osint_page() -> String {
             color: white;
             font-weight: 600;
             cursor: pointer;
-        }
-        .tools-grid {
+        }}
+        .tools-grid {{
             display: grid;
             grid-template-columns: repeat(auto-fit, minmax(280px, 1fr));
             gap: 1.5rem;
             padding: 2rem;
             max-width: 1200px;
             margin: 0 auto;
-        }
-        .tool-card {
+        }}
+        .tool-card {{
             background: var(--bg2);
             border: 1px solid var(--border);
             border-radius: 12px;
             padding: 1.5rem;
             transition: all 0.2s ease;
-        }
-        .tool-card:hover {
+        }}
+        .tool-card:hover {{
             border-color: var(--red);
             transform: translateY(-2px);
-        }
-        .tool-icon { font-size: 36px; margin-bottom: 1rem; }
-        .tool-title { font-size: 18px; font-weight: 600; color: var(--red); margin-bottom: 0.5rem; }
-        .tool-desc { color: var(--fg-dim); font-size: 13px; margin-bottom: 1rem; line-height: 1.5; }
-        .tool-actions { display: flex; gap: 8px; flex-wrap: wrap; }
-        .tool-btn {
+        }}
+        .tool-icon {{ font-size: 36px; margin-bottom: 1rem; }}
+        .tool-title {{ font-size: 18px; font-weight: 600; color: var(--red); margin-bottom: 0.5rem; }}
+        .tool-desc {{ color: var(--fg-dim); font-size: 13px; margin-bottom: 1rem; line-height: 1.5; }}
+        .tool-actions {{ display: flex; gap: 8px; flex-wrap: wrap; }}
+        .tool-btn {{
             padding: 8px 16px;
             background: var(--bg3);
             border: 1px solid var(--border);
@@ -1283,12 +1328,25 @@ pub fn generate_osint_page() -> String {
             font-size: 12px;
             cursor: pointer;
             text-decoration: none;
-        }
-        .tool-btn:hover {
+        }}
+        .tool-input {{
+            flex: 1;
+            min-width: 0;
+            padding: 8px 12px;
+            background: var(--bg);
+            border: 1px solid var(--border);
+            border-radius: 6px;
+            color: var(--fg);
+            font-size: 12px;
+            outline: none;
+        }}
+        .tool-input:focus {{ border-color: var(--red); }}
+        .monitored {{ margin-top: 0.75rem; }}
+        .tool-btn:hover {{
             border-color: var(--red);
             color: var(--red);
             text-decoration: none;
-        }
+        }}
     </style>
 </head>
 <body>
@@ -1331,6 +1389,16 @@ pub fn generate_osint_page() -> String {
                 <a href="https://censys.io/" target="_blank" class="tool-btn">Censys</a>
             </div>
         </div>
+        <div class="tool-card">
+            <div class="tool-icon">&#127907;</div>
+            <div class="tool-title">Typosquat Monitor</div>
+            <div class="tool-desc">Generate lookalikes of a domain and see which are registered, live, and since when.</div>
+            <form class="tool-actions" onsubmit="event.preventDefault(); if(this.domain.value) location.href='marshall://osint/typosquat?domain='+encodeURIComponent(this.domain.value)+'&token={token}';">
+                <input type="text" name="domain" placeholder="example.com" class="tool-input">
+                <button type="submit" class="tool-btn">Check</button>
+            </form>
+            {monitored}
+        </div>
         <div class="tool-card">
             <div class="tool-icon">&#128274;</div>
             <div class="tool-title">SSL/TLS Analysis</div>
@@ -1378,20 +1446,22 @@ pub fn generate_osint_page() -> String {
         </div>
     </div>
 </body>
-</html>"##.to_string()
+</html>"##)
 }
 
-/// Generate Settings page
-pub fn generate_settings_page() -> String {
-    r##"<!DOCTYPE html>
+/// Generate Settings page, with how pages reach the network from the leak
+/// test's network checks; failed ones are shown as warnings
+pub fn generate_settings_page(network: &[LeakCheck]) -> String {
+    let network = network_rows(network);
+    format!(r##"<!DOCTYPE html>
 <html lang="en">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>Marshall Settings</title>
     <style>
-        * { box-sizing: border-box; margin: 0; padding: 0; }
-        :root {
+        * {{ box-sizing: border-box; margin: 0; padding: 0; }}
+        :root {{
             --red: #ff0040;
             --green: #00ff88;
             --bg: #0d0d0d;
@@ -1400,58 +1470,59 @@ pub fn generate_settings_page() -> String {
             --fg: #e0e0e0;
             --fg-dim: #808080;
             --border: #333;
-        }
-        html, body {
+        }}
+        html, body {{
             background: var(--bg);
             color: var(--fg);
             font-family: 'Segoe UI', -apple-system, sans-serif;
             min-height: 100vh;
-        }
-        a { color: var(--red); text-decoration: none; }
-        .back-link {
+        }}
+        a {{ color: var(--red); text-decoration: none; }}
+        .back-link {{
             display: inline-flex;
             align-items: center;
             gap: 8px;
             color: var(--fg-dim);
             font-size: 14px;
             margin: 1rem 2rem;
-        }
-        .header {
+        }}
+        .header {{
             background: var(--bg2);
             border-bottom: 1px solid var(--border);
             padding: 2rem;
-        }
-        .header h1 { font-size: 24px; color: var(--red); }
-        .settings-container {
+        }}
+        .header h1 {{ font-size: 24px; color: var(--red); }}
+        .settings-container {{
             max-width: 700px;
             margin: 2rem auto;
             padding: 0 2rem;
-        }
-        .settings-section {
+        }}
+        .settings-section {{
             background: var(--bg2);
             border: 1px solid var(--border);
             border-radius: 12px;
             margin-bottom: 1.5rem;
             overflow: hidden;
-        }
-        .section-title {
+        }}
+        .section-title {{
             padding: 1rem 1.5rem;
             background: var(--bg3);
             border-bottom: 1px solid var(--border);
             font-weight: 600;
             color: var(--red);
-        }
-        .setting-item {
+        }}
+        .setting-item {{
             padding: 1rem 1.5rem;
             display: flex;
             align-items: center;
             justify-content: space-between;
             border-bottom: 1px solid var(--border);
-        }
-        .setting-item:last-child { border-bottom: none; }
-        .setting-info h4 { margin-bottom: 4px; }
-        .setting-info p { font-size: 12px; color: var(--fg-dim); }
-        .toggle {
+        }}
+        .setting-item:last-child {{ border-bottom: none; }}
+        .setting-info h4 {{ margin-bottom: 4px; }}
+        .setting-info p {{ font-size: 12px; color: var(--fg-dim); }}
+        .setting-info h4.warning {{ color: var(--red); }}
+        .toggle {{
             width: 48px;
             height: 26px;
             background: var(--bg3);
@@ -1459,9 +1530,9 @@ pub fn generate_settings_page() -> String {
             position: relative;
             cursor: pointer;
             transition: background 0.2s ease;
-        }
-        .toggle.active { background: var(--green); }
-        .toggle::after {
+        }}
+        .toggle.active {{ background: var(--green); }}
+        .toggle::after {{
             content: '';
             position: absolute;
             width: 22px;
@@ -1471,9 +1542,9 @@ pub fn generate_settings_page() -> String {
             top: 2px;
             left: 2px;
             transition: transform 0.2s ease;
-        }
-        .toggle.active::after { transform: translateX(22px); }
-        .config-btn {
+        }}
+        .toggle.active::after {{ transform: translateX(22px); }}
+        .config-btn {{
             padding: 8px 16px;
             background: var(--bg3);
             border: 1px solid var(--border);
@@ -1481,16 +1552,16 @@ pub fn generate_settings_page() -> String {
             color: var(--fg);
             font-size: 12px;
             cursor: pointer;
-        }
-        .config-btn:hover {
+        }}
+        .config-btn:hover {{
             border-color: var(--red);
             color: var(--red);
-        }
-        .provider-list {
+        }}
+        .provider-list {{
             display: grid;
             gap: 8px;
-        }
-        .provider-item {
+        }}
+        .provider-item {{
             display: flex;
             justify-content: space-between;
             align-items: center;
@@ -1498,14 +1569,14 @@ pub fn generate_settings_page() -> String {
             background: var(--bg3);
             border-radius: 8px;
             font-size: 13px;
-        }
-        .provider-name {
+        }}
+        .provider-name {{
             font-weight: 600;
-        }
-        .provider-desc {
+        }}
+        .provider-desc {{
             color: var(--fg-dim);
             font-size: 11px;
-        }
+        }}
     </style>
 </head>
 <body>
@@ -1538,6 +1609,17 @@ pub fn generate_settings_page() -> String {
                 <div class="toggle active" onclick="this.classList.toggle('active')"></div>
             </div>
         </div>
+        <div class="settings-section">
+            <div class="section-title">Network</div>
+{network}
+            <div class="setting-item">
+                <div class="setting-info">
+                    <h4>Leak Test</h4>
+                    <p>Check what pages can learn about this machine</p>
+                </div>
+                <button class="config-btn" onclick="window.location.href='marshall://leaktest'">Run</button>
+            </div>
+        </div>
         <div class="settings-section">
             <div class="section-title">Dr Marshall AI</div>
             <div class="setting-item">
@@ -1591,20 +1673,48 @@ pub fn generate_settings_page() -> String {
         </div>
     </div>
 </body>
-</html>"##.to_string()
+</html>"##)
 }
 
-/// Generate Privacy info page
-pub fn generate_privacy_page() -> String {
-    r##"<!DOCTYPE html>
+fn network_rows(checks: &[LeakCheck]) -> String {
+    checks
+        .iter()
+        .map(|check| {
+            let (class, mark) = match check.verdict {
+                Verdict::Fail => (" class=\"warning\"", "⚠ "),
+                _ => ("", ""),
+            };
+            format!(
+                "            <div class=\"setting-item\">\
+                <div class=\"setting-info\"><h4{class}>{mark}{name}</h4><p>{detail}</p></div>\
+            </div>\n",
+                class = class,
+                mark = mark,
+                name = html_escape(check.name),
+                detail = html_escape(&check.detail),
+            )
+        })
+        .collect()
+}
+
+/// Generate Privacy info page, including the state of the filter-list cache
+/// and the editable per-site profiles
+pub fn generate_privacy_page(lists: &[ListInfo], profiles: &[SiteProfile]) -> String {
+    let filter_lists = filter_list_table(lists);
+    let site_profiles: String = profiles
+        .iter()
+        .map(site_profile_form)
+        .chain(std::iter::once(site_profile_form(&SiteProfile::default())))
+        .collect();
+    format!(r##"<!DOCTYPE html>
 <html lang="en">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>Marshall Privacy</title>
     <style>
-        * { box-sizing: border-box; margin: 0; padding: 0; }
-        :root {
+        * {{ box-sizing: border-box; margin: 0; padding: 0; }}
+        :root {{
             --red: #ff0040;
             --green: #00ff88;
             --bg: #0d0d0d;
@@ -1613,60 +1723,69 @@ pub fn generate_privacy_page() -> String {
             --fg: #e0e0e0;
             --fg-dim: #808080;
             --border: #333;
-        }
-        html, body {
+        }}
+        html, body {{
             background: var(--bg);
             color: var(--fg);
             font-family: 'Segoe UI', -apple-system, sans-serif;
             min-height: 100vh;
-        }
-        a { color: var(--red); text-decoration: none; }
-        .back-link {
+        }}
+        a {{ color: var(--red); text-decoration: none; }}
+        .back-link {{
             display: inline-flex;
             align-items: center;
             gap: 8px;
             color: var(--fg-dim);
             font-size: 14px;
             margin: 1rem 2rem;
-        }
-        .header {
+        }}
+        .header {{
             background: linear-gradient(135deg, var(--bg2) 0%, var(--bg) 100%);
             border-bottom: 2px solid var(--green);
             padding: 3rem 2rem;
             text-align: center;
-        }
-        .header h1 { font-size: 32px; color: var(--green); margin-bottom: 0.5rem; }
-        .content {
+        }}
+        .header h1 {{ font-size: 32px; color: var(--green); margin-bottom: 0.5rem; }}
+        .content {{
             max-width: 700px;
             margin: 2rem auto;
             padding: 0 2rem;
-        }
-        .status-grid {
+        }}
+        .status-grid {{
             display: grid;
             grid-template-columns: repeat(auto-fit, minmax(150px, 1fr));
             gap: 1rem;
             margin: 2rem 0;
-        }
-        .status-item {
+        }}
+        .status-item {{
             background: var(--bg2);
             border: 1px solid var(--border);
             border-radius: 8px;
             padding: 1rem;
             text-align: center;
-        }
-        .status-icon { font-size: 24px; margin-bottom: 0.5rem; }
-        .status-label { font-size: 12px; color: var(--fg-dim); }
-        .status-value { color: var(--green); font-weight: 600; }
-        .privacy-card {
+        }}
+        .status-icon {{ font-size: 24px; margin-bottom: 0.5rem; }}
+        .status-label {{ font-size: 12px; color: var(--fg-dim); }}
+        .status-value {{ color: var(--green); font-weight: 600; }}
+        .privacy-card {{
             background: var(--bg2);
             border: 1px solid var(--border);
             border-left: 3px solid var(--green);
             border-radius: 8px;
             padding: 1.5rem;
             margin-bottom: 1.5rem;
-        }
-        .privacy-card h3 { color: var(--green); margin-bottom: 1rem; }
-        .privacy-card p { color: var(--fg-dim); line-height: 1.7; }
+        }}
+        .privacy-card h3 {{ color: var(--green); margin-bottom: 1rem; }}
+        .privacy-card p {{ color: var(--fg-dim); line-height: 1.7; }}
+        .list-table {{ width: 100%; border-collapse: collapse; font-size: 13px; }}
+        .list-table th {{ text-align: left; color: var(--fg-dim); font-weight: 500; padding: 0.4rem 0.5rem; border-bottom: 1px solid var(--border); }}
+        .list-table td {{ padding: 0.4rem 0.5rem; border-bottom: 1px solid var(--bg3); word-break: break-all; }}
+        .list-error {{ color: var(--red); }}
+        .site-form {{ display: flex; flex-wrap: wrap; gap: 0.5rem; align-items: center; padding: 0.75rem 0; border-bottom: 1px solid var(--bg3); font-size: 13px; }}
+        .site-form input, .site-form select, .site-form button {{ background: var(--bg3); color: var(--fg); border: 1px solid var(--border); border-radius: 4px; padding: 0.3rem 0.4rem; font-size: 12px; }}
+        .site-form input[name=origin] {{ flex: 1 1 100%; }}
+        .site-form label {{ color: var(--fg-dim); }}
+        .site-form button {{ cursor: pointer; }}
     </style>
 </head>
 <body>
@@ -1693,6 +1812,15 @@ pub fn generate_privacy_page() -> String {
                 <div class="status-label">Auto Clear</div>
             </div>
         </div>
+        <div class="privacy-card">
+            <h3>Filter Lists</h3>
+            {filter_lists}
+        </div>
+        <div class="privacy-card">
+            <h3>Site Profiles</h3>
+            <p>Override the global privacy settings for individual sites.</p>
+            {site_profiles}
+        </div>
         <div class="privacy-card">
             <h3>No Tracking</h3>
             <p>Marshall blocks all known tracking scripts and fingerprinting attempts. We do not collect any data about your browsing habits.</p>
@@ -1711,17 +1839,112 @@ pub fn generate_privacy_page() -> String {
         </div>
     </div>
 </body>
-</html>"##.to_string()
+</html>"##)
 }
 
-/// Generate OSINT results page for a specific domain
-pub fn generate_osint_results(domain: &str) -> String {
+/// Per-list rule counts and update times for the privacy page
+fn filter_list_table(lists: &[ListInfo]) -> String {
+    if lists.is_empty() {
+        return "<p>No remote filter lists configured.</p>".to_string();
+    }
+
+    let rows: String = lists
+        .iter()
+        .map(|list| {
+            let updated = list
+                .last_updated
+                .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "never".to_string());
+            let status = match &list.last_error {
+                Some(e) => format!("<span class=\"list-error\">{}</span>", html_escape(e)),
+                None => "OK".to_string(),
+            };
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                html_escape(&list.url), list.rule_count, updated, status
+            )
+        })
+        .collect();
+
+    format!(
+        "<table class=\"list-table\"><tr><th>List</th><th>Rules</th><th>Updated</th><th>Status</th></tr>{}</table>",
+        rows
+    )
+}
+
+/// One editable site profile; an empty profile renders the "add site" form
+fn site_profile_form(profile: &SiteProfile) -> String {
+    fn toggle(name: &str, label: &str, value: Option<bool>) -> String {
+        let opt = |v: &str, text: &str, selected: bool| {
+            format!("<option value=\"{}\"{}>{}</option>", v, if selected { " selected" } else { "" }, text)
+        };
+        format!(
+            "<label>{} <select name=\"{}\">{}{}{}</select></label>",
+            label, name,
+            opt("default", "Default", value.is_none()),
+            opt("on", "On", value == Some(true)),
+            opt("off", "Off", value == Some(false)),
+        )
+    }
+
+    let cookies: String = [("default", "Default"), ("all", "All"), ("no-third-party", "No third-party"), ("none", "None")]
+        .iter()
+        .map(|(value, text)| {
+            let selected = profile.cookies.map(|c| c.as_str()).unwrap_or("default") == *value;
+            format!("<option value=\"{}\"{}>{}</option>", value, if selected { " selected" } else { "" }, text)
+        })
+        .collect();
+
+    let referrer: String = std::iter::once("default")
+        .chain(REFERRER_POLICIES.iter().copied())
+        .map(|policy| {
+            let selected = profile.referrer_policy.as_deref().unwrap_or("default") == policy;
+            format!("<option value=\"{0}\"{1}>{0}</option>", policy, if selected { " selected" } else { "" })
+        })
+        .collect();
+
+    let is_new = profile.origin.is_empty();
+    format!(
+        "<form class=\"site-form\" action=\"marshall://privacy/site\" method=\"get\">\
+            {token_field}\
+            <input name=\"origin\" placeholder=\"example.com\" value=\"{origin}\"{readonly}>\
+            {js}{images}{webgl}{fp}\
+            <label>Cookies <select name=\"cookies\">{cookies}</select></label>\
+            <label>Referrer <select name=\"referrer_policy\">{referrer}</select></label>\
+            <input name=\"user_agent\" placeholder=\"User agent (default)\" value=\"{ua}\">\
+            <label><input type=\"checkbox\" name=\"adblock_exempt\"{exempt}> Allow ads</label>\
+            <button type=\"submit\" name=\"action\" value=\"save\">{save}</button>{delete}\
+        </form>",
+        origin = html_escape(&profile.origin),
+        readonly = if is_new { "" } else { " readonly" },
+        js = toggle("javascript", "JavaScript", profile.javascript),
+        images = toggle("images", "Images", profile.images),
+        webgl = toggle("webgl", "WebGL", profile.webgl),
+        fp = toggle("fingerprint_protection", "Anti-fingerprinting", profile.fingerprint_protection),
+        cookies = cookies,
+        referrer = referrer,
+        ua = html_escape(profile.user_agent.as_deref().unwrap_or("")),
+        exempt = if profile.adblock_exempt { " checked" } else { "" },
+        save = if is_new { "Add site" } else { "Save" },
+        delete = if is_new { "" } else { "<button type=\"submit\" name=\"action\" value=\"delete\">Remove</button>" },
+        token_field = token_field(),
+    )
+}
+
+/// Generate the container management page
+pub fn generate_containers_page(containers: &[Container]) -> String {
+    let rows: String = containers.iter().map(container_form).collect();
+    let colors: String = CONTAINER_COLORS
+        .iter()
+        .map(|(name, _)| format!("<option value=\"{0}\">{0}</option>", name))
+        .collect();
+    let token_field = token_field();
     format!(r##"<!DOCTYPE html>
 <html lang="en">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>OSINT Report - {domain}</title>
+    <title>Marshall Containers</title>
     <style>
         * {{ box-sizing: border-box; margin: 0; padding: 0; }}
         :root {{
@@ -1750,108 +1973,985 @@ pub fn generate_osint_results(domain: &str) -> String {
             margin: 1rem 2rem;
         }}
         .header {{
-            background: var(--bg2);
-            border-bottom: 2px solid var(--red);
-            padding: 2rem;
+            background: linear-gradient(135deg, var(--bg2) 0%, var(--bg) 100%);
+            border-bottom: 2px solid var(--green);
+            padding: 3rem 2rem;
+            text-align: center;
         }}
-        .header h1 {{ color: var(--red); margin-bottom: 0.5rem; }}
-        .header .target {{ font-family: monospace; color: var(--green); font-size: 18px; }}
-        .container {{ max-width: 900px; margin: 2rem auto; padding: 0 2rem; }}
-        .report-section {{
+        .header h1 {{ font-size: 32px; color: var(--green); margin-bottom: 0.5rem; }}
+        .content {{
+            max-width: 700px;
+            margin: 2rem auto;
+            padding: 0 2rem;
+        }}
+        .privacy-card {{
             background: var(--bg2);
             border: 1px solid var(--border);
+            border-left: 3px solid var(--green);
             border-radius: 8px;
+            padding: 1.5rem;
             margin-bottom: 1.5rem;
-            overflow: hidden;
-        }}
-        .section-header {{
-            background: var(--bg3);
-            padding: 1rem 1.5rem;
-            border-bottom: 1px solid var(--border);
-            font-weight: 600;
-            color: var(--red);
-        }}
-        .section-content {{ padding: 1.5rem; }}
-        .external-links {{ display: flex; flex-wrap: wrap; gap: 8px; margin-top: 1rem; }}
-        .ext-link {{
-            padding: 8px 16px;
-            background: var(--bg3);
-            border: 1px solid var(--border);
-            border-radius: 6px;
-            color: var(--fg);
-            font-size: 12px;
-            text-decoration: none;
         }}
-        .ext-link:hover {{ border-color: var(--red); color: var(--red); }}
-        .ext-link.primary {{ background: var(--red); color: white; border-color: var(--red); }}
+        .privacy-card h3 {{ color: var(--green); margin-bottom: 1rem; }}
+        .privacy-card p {{ color: var(--fg-dim); line-height: 1.7; }}
+        .container-dot {{ display: inline-block; width: 12px; height: 12px; border-radius: 50%; }}
+        .site-form {{ display: flex; flex-wrap: wrap; gap: 0.5rem; align-items: center; padding: 0.75rem 0; border-bottom: 1px solid var(--bg3); font-size: 13px; }}
+        .site-form input, .site-form select, .site-form button {{ background: var(--bg3); color: var(--fg); border: 1px solid var(--border); border-radius: 4px; padding: 0.3rem 0.4rem; font-size: 12px; }}
+        .site-form input[name=name] {{ flex: 1 1 40%; }}
+        .site-form input[name=user_agent] {{ flex: 1 1 100%; }}
+        .site-form label {{ color: var(--fg-dim); }}
+        .site-form button {{ cursor: pointer; }}
     </style>
 </head>
 <body>
-    <a href="javascript:void(0)" onclick="window.location.href='marshall://osint'" class="back-link">&#8592; Back to OSINT Tools</a>
+    <a href="javascript:void(0)" onclick="window.location.href='marshall://home'" class="back-link">&#8592; Back to Home</a>
     <div class="header">
-        <h1>OSINT Report</h1>
-        <div class="target">{domain}</div>
+        <h1>Containers</h1>
+        <p>Separate identities, separate cookie jars</p>
     </div>
-    <div class="container">
-        <div class="report-section">
-            <div class="section-header">Domain Information</div>
-            <div class="section-content">
-                <p>Target: <strong>{domain}</strong></p>
-                <p style="color: var(--green); margin-top: 0.5rem;">&#9679; Analysis Ready</p>
-                <div class="external-links">
-                    <a href="https://who.is/{domain}" target="_blank" class="ext-link">WHOIS Lookup</a>
-                    <a href="https://dnsdumpster.com/?search={domain}" target="_blank" class="ext-link">DNS Records</a>
-                    <a href="https://www.shodan.io/search?query={domain}" target="_blank" class="ext-link">Shodan</a>
-                    <a href="https://crt.sh/?q={domain}" target="_blank" class="ext-link">Certificates</a>
-                    <a href="https://www.ssllabs.com/ssltest/analyze.html?d={domain}" target="_blank" class="ext-link">SSL Test</a>
-                </div>
-            </div>
+    <div class="content">
+        <div class="privacy-card">
+            <h3>Your Containers</h3>
+            <p>Each container keeps its own cookies, storage and cache. Links open in the container of the tab they are clicked in; right-click a tab to move it.</p>
+            {rows}
         </div>
-        <div class="report-section">
-            <div class="section-header">Quick Actions</div>
-            <div class="section-content">
-                <p style="color: var(--fg-dim); margin-bottom: 1rem;">Click any tool above to perform detailed analysis.</p>
-                <a href="javascript:void(0)" onclick="window.location.href='marshall://assistant'" class="ext-link primary">Ask Dr Marshall about this target</a>
-            </div>
+        <div class="privacy-card">
+            <h3>New Container</h3>
+            <form class="site-form" action="marshall://containers/save" method="get">
+                {token_field}
+                <input name="name" placeholder="Name">
+                <label>Colour <select name="color">{colors}</select></label>
+                <input name="proxy" placeholder="Proxy, e.g. socks5://127.0.0.1:9050">
+                <input name="user_agent" placeholder="User agent (default)">
+                <button type="submit">Add container</button>
+            </form>
         </div>
     </div>
 </body>
-</html>"##, domain = domain)
+</html>"##)
 }
 
-/// Generate Menu/Dashboard page with settings links
-pub fn generate_menu_page() -> String {
-    r##"<!DOCTYPE html>
+/// One container with its settings and open/remove actions
+fn container_form(container: &Container) -> String {
+    let colors: String = CONTAINER_COLORS
+        .iter()
+        .map(|(name, _)| {
+            let selected = if *name == container.color { " selected" } else { "" };
+            format!("<option value=\"{0}\"{1}>{0}</option>", name, selected)
+        })
+        .collect();
+    format!(
+        "<form class=\"site-form\" action=\"marshall://containers/save\" method=\"get\">\
+            {token_field}\
+            <span class=\"container-dot\" style=\"background:{css}\"></span>\
+            <input name=\"name\" value=\"{name}\" readonly>\
+            <label>Colour <select name=\"color\">{colors}</select></label>\
+            <input name=\"proxy\" placeholder=\"Proxy (none)\" value=\"{proxy}\">\
+            <input name=\"user_agent\" placeholder=\"User agent (default)\" value=\"{ua}\">\
+            <a href=\"marshall://containers/open/{id}?token={token}\">Open tab</a>\
+            <button type=\"submit\" name=\"action\" value=\"save\">Save</button>\
+            <button type=\"submit\" name=\"action\" value=\"delete\">Remove</button>\
+        </form>",
+        css = container.css_color(),
+        name = html_escape(&container.name),
+        colors = colors,
+        proxy = html_escape(container.proxy.as_deref().unwrap_or("")),
+        ua = html_escape(container.user_agent.as_deref().unwrap_or("")),
+        id = container.id,
+        token_field = token_field(),
+        token = ACTION_TOKEN.as_str(),
+    )
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Generate the downloads page. Actions are
+/// `marshall://downloads/<action>/<id>?token=<token>` links that the window
+/// carries out.
+pub fn generate_downloads_page(downloads: &[&DownloadRecord], private: bool) -> String {
+    let rows: String = if downloads.is_empty() {
+        "<p>Nothing downloaded yet.</p>".to_string()
+    } else {
+        downloads.iter().map(|d| download_row(d)).collect()
+    };
+    let note = if private {
+        "Downloads from private windows are listed here until the last private window closes. The files stay on disk."
+    } else {
+        "Files are checked for dangerous types and suspicious sources before they are saved, and fingerprinted with SHA-256 when they finish."
+    };
+    let token = ACTION_TOKEN.as_str();
+    format!(r##"<!DOCTYPE html>
 <html lang="en">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>Marshall Menu</title>
+    <title>Marshall Downloads</title>
     <style>
-        * { box-sizing: border-box; margin: 0; padding: 0; }
-        :root {
+        * {{ box-sizing: border-box; margin: 0; padding: 0; }}
+        :root {{
             --red: #ff0040;
             --green: #00ff88;
+            --orange: #ffaa00;
             --bg: #0d0d0d;
             --bg2: #1a1a1a;
             --bg3: #252525;
             --fg: #e0e0e0;
             --fg-dim: #808080;
             --border: #333;
-        }
-        html, body {
+        }}
+        html, body {{
             background: var(--bg);
             color: var(--fg);
             font-family: 'Segoe UI', -apple-system, sans-serif;
             min-height: 100vh;
-        }
-        .menu-container {
-            max-width: 400px;
-            margin: 0 auto;
-            padding: 2rem;
-        }
-        .menu-header {
-            text-align: center;
+        }}
+        a {{ color: var(--red); text-decoration: none; }}
+        .back-link {{
+            display: inline-flex;
+            align-items: center;
+            gap: 8px;
+            color: var(--fg-dim);
+            font-size: 14px;
+            margin: 1rem 2rem;
+        }}
+        .header {{
+            background: linear-gradient(135deg, var(--bg2) 0%, var(--bg) 100%);
+            border-bottom: 2px solid var(--green);
+            padding: 3rem 2rem;
+            text-align: center;
+        }}
+        .header h1 {{ font-size: 32px; color: var(--green); margin-bottom: 0.5rem; }}
+        .content {{
+            max-width: 800px;
+            margin: 2rem auto;
+            padding: 0 2rem;
+        }}
+        .privacy-card {{
+            background: var(--bg2);
+            border: 1px solid var(--border);
+            border-left: 3px solid var(--green);
+            border-radius: 8px;
+            padding: 1.5rem;
+            margin-bottom: 1.5rem;
+        }}
+        .privacy-card h3 {{ color: var(--green); margin-bottom: 1rem; }}
+        .privacy-card p {{ color: var(--fg-dim); line-height: 1.7; }}
+        .download {{ padding: 0.75rem 0; border-bottom: 1px solid var(--bg3); font-size: 13px; }}
+        .download .name {{ font-weight: 600; word-break: break-all; }}
+        .download .meta {{ color: var(--fg-dim); font-size: 12px; margin: 0.25rem 0; word-break: break-all; }}
+        .download .hash {{ font-family: 'JetBrains Mono', monospace; font-size: 11px; color: var(--fg-dim); word-break: break-all; }}
+        .download .warning {{ color: var(--orange); font-size: 12px; }}
+        .download .error {{ color: var(--red); font-size: 12px; }}
+        .download .actions a {{ margin-right: 1rem; font-size: 12px; }}
+        progress {{ width: 100%; height: 6px; accent-color: var(--green); }}
+    </style>
+</head>
+<body>
+    <a href="javascript:void(0)" onclick="window.location.href='marshall://home'" class="back-link">&#8592; Back to Home</a>
+    <div class="header">
+        <h1>Downloads</h1>
+        <p>{note}</p>
+    </div>
+    <div class="content">
+        <div class="privacy-card">
+            <h3>Recent Downloads</h3>
+            {rows}
+            <p class="actions"><a href="marshall://downloads/clear?token={token}">Clear finished downloads</a></p>
+        </div>
+    </div>
+</body>
+</html>"##)
+}
+
+/// One download with its progress and the actions its state allows
+fn download_row(download: &DownloadRecord) -> String {
+    let size = match (download.state, download.total_bytes) {
+        (DownloadState::Completed, _) => format_bytes(download.received_bytes),
+        (_, 0) => format!("{} of unknown size", format_bytes(download.received_bytes)),
+        (_, total) => format!("{} of {}", format_bytes(download.received_bytes), format_bytes(total)),
+    };
+    let progress = match (download.state, download.progress()) {
+        (DownloadState::InProgress, Some(progress)) => format!("<progress value=\"{:.3}\" max=\"1\"></progress>", progress),
+        (DownloadState::InProgress, None) => "<progress></progress>".to_string(),
+        _ => String::new(),
+    };
+    let warnings: String = download
+        .warnings
+        .iter()
+        .map(|w| format!("<div class=\"warning\">&#9888; {}</div>", html_escape(w)))
+        .collect();
+    let error = download
+        .error
+        .as_deref()
+        .map(|e| format!("<div class=\"error\">{}</div>", html_escape(e)))
+        .unwrap_or_default();
+    let hash = download
+        .sha256
+        .as_deref()
+        .map(|h| format!("<div class=\"hash\">SHA-256 {}</div>", h))
+        .unwrap_or_default();
+
+    let actions: &[(&str, &str)] = match download.state {
+        DownloadState::InProgress => &[("pause", "Pause"), ("cancel", "Cancel")],
+        DownloadState::Paused => &[("resume", "Resume"), ("cancel", "Cancel"), ("remove", "Remove")],
+        DownloadState::Completed => &[("show", "Show in folder"), ("remove", "Remove from list")],
+        DownloadState::Failed | DownloadState::Cancelled => &[("retry", "Retry"), ("remove", "Remove from list")],
+    };
+    let actions: String = actions
+        .iter()
+        .map(|(action, label)| {
+            format!("<a href=\"marshall://downloads/{}/{}?token={}\">{}</a>", action, download.id, ACTION_TOKEN.as_str(), label)
+        })
+        .collect();
+
+    format!(
+        "<div class=\"download\">\
+            <div class=\"name\">{name}</div>\
+            <div class=\"meta\">{state} &middot; {size} &middot; {started} &middot; {url}</div>\
+            {progress}{warnings}{error}{hash}\
+            <div class=\"actions\">{actions}</div>\
+        </div>",
+        name = html_escape(&download.filename),
+        state = download.state.label(),
+        size = size,
+        started = download.started_at.format("%Y-%m-%d %H:%M"),
+        url = html_escape(&download.url),
+        progress = progress,
+        warnings = warnings,
+        error = error,
+        hash = hash,
+        actions = actions,
+    )
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Generate the leak test page. Network checks come from Rust; the rest run
+/// in the page against `promises`, and "Run again" carries the canvas hash
+/// to the next run as `marshall://leaktest?canvas=<hash>`.
+pub fn generate_leaktest_page(promises: &LeakPromises, network: &[LeakCheck]) -> String {
+    let promises = script_json(promises);
+    let network = script_json(network);
+    format!(r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Marshall Leak Test</title>
+    <style>
+        * {{ box-sizing: border-box; margin: 0; padding: 0; }}
+        :root {{
+            --red: #ff0040;
+            --green: #00ff88;
+            --orange: #ffaa00;
+            --bg: #0d0d0d;
+            --bg2: #1a1a1a;
+            --bg3: #252525;
+            --fg: #e0e0e0;
+            --fg-dim: #808080;
+            --border: #333;
+        }}
+        html, body {{
+            background: var(--bg);
+            color: var(--fg);
+            font-family: 'Segoe UI', -apple-system, sans-serif;
+            min-height: 100vh;
+        }}
+        a {{ color: var(--red); text-decoration: none; }}
+        .back-link {{
+            display: inline-flex;
+            align-items: center;
+            gap: 8px;
+            color: var(--fg-dim);
+            font-size: 14px;
+            margin: 1rem 2rem;
+        }}
+        .header {{
+            background: linear-gradient(135deg, var(--bg2) 0%, var(--bg) 100%);
+            border-bottom: 2px solid var(--green);
+            padding: 3rem 2rem;
+            text-align: center;
+        }}
+        .header h1 {{ font-size: 32px; color: var(--green); margin-bottom: 0.5rem; }}
+        .content {{
+            max-width: 900px;
+            margin: 2rem auto;
+            padding: 0 2rem;
+        }}
+        .privacy-card {{
+            background: var(--bg2);
+            border: 1px solid var(--border);
+            border-left: 3px solid var(--green);
+            border-radius: 8px;
+            padding: 1.5rem;
+            margin-bottom: 1.5rem;
+        }}
+        .privacy-card h3 {{ color: var(--green); margin-bottom: 1rem; }}
+        .privacy-card p {{ color: var(--fg-dim); line-height: 1.7; }}
+        table {{ width: 100%; border-collapse: collapse; font-size: 13px; margin: 1rem 0; }}
+        td {{ padding: 0.6rem 0.5rem; border-bottom: 1px solid var(--bg3); vertical-align: top; word-break: break-word; }}
+        td:first-child {{ font-weight: 600; white-space: nowrap; }}
+        td.pass {{ color: var(--green); font-weight: 600; }}
+        td.fail {{ color: var(--red); font-weight: 600; }}
+        td.info {{ color: var(--fg-dim); font-weight: 600; }}
+    </style>
+</head>
+<body>
+    <a href="javascript:void(0)" onclick="window.location.href='marshall://home'" class="back-link">&#8592; Back to Home</a>
+    <div class="header">
+        <h1>Leak Test</h1>
+        <p>What pages can learn about you, checked against what your privacy and security settings promise. Nothing leaves this machine.</p>
+    </div>
+    <div class="content">
+        <div class="privacy-card">
+            <h3>Results</h3>
+            <p id="summary">Running checks&hellip;</p>
+            <table id="results"></table>
+            <p><a id="again" href="marshall://leaktest">Run again</a> to compare the canvas hash across reloads.</p>
+        </div>
+    </div>
+    <script>
+        const promises = {promises};
+        const rows = {network};
+
+        function record(name, promised, ok, detail) {{
+            rows.push({{ name: name, verdict: promised ? (ok ? 'pass' : 'fail') : 'info', detail: detail }});
+        }}
+
+        // FNV-1a, enough to tell two canvas readings apart
+        function hash(text) {{
+            let h = 0x811c9dc5;
+            for (let i = 0; i < text.length; i++) {{
+                h ^= text.charCodeAt(i);
+                h = Math.imul(h, 0x01000193) >>> 0;
+            }}
+            return h.toString(16).padStart(8, '0');
+        }}
+
+        async function checkWebRTC() {{
+            if (typeof window.RTCPeerConnection !== 'function') {{
+                return record('WebRTC', promises.block_webrtc, true, 'RTCPeerConnection is unavailable');
+            }}
+            let pc;
+            try {{
+                pc = new RTCPeerConnection({{ iceServers: [] }});
+            }} catch (e) {{
+                return record('WebRTC', promises.block_webrtc, true, 'RTCPeerConnection is blocked: ' + e.message);
+            }}
+            // Host candidates only; no STUN server is asked
+            const addresses = new Set();
+            try {{
+                pc.createDataChannel('leaktest');
+                const gathered = new Promise(resolve => {{
+                    pc.onicecandidate = e => {{
+                        if (!e.candidate) return resolve();
+                        const address = e.candidate.candidate.split(' ')[4];
+                        if (address) addresses.add(address);
+                    }};
+                    setTimeout(resolve, 2000);
+                }});
+                await pc.setLocalDescription(await pc.createOffer());
+                await gathered;
+            }} catch (e) {{}}
+            pc.close();
+            const found = addresses.size ? 'local candidates ' + Array.from(addresses).join(', ') : 'no local addresses exposed';
+            record('WebRTC', promises.block_webrtc, false, 'RTCPeerConnection is available; ' + found);
+        }}
+
+        function checkUserAgent() {{
+            const ua = navigator.userAgent;
+            const family = /Windows/.test(ua) ? 'Win' : /Macintosh|Mac OS X/.test(ua) ? 'Mac' : /Linux|X11|CrOS/.test(ua) ? 'Linux' : '';
+            const problems = [];
+            if (ua !== promises.user_agent) {{
+                problems.push('scripts see "' + ua + '" but requests send "' + promises.user_agent + '"');
+            }}
+            if (family && !navigator.platform.startsWith(family)) {{
+                problems.push('navigator.platform "' + navigator.platform + '" contradicts the user agent');
+            }}
+            record('User agent', true, problems.length === 0, problems.length ? problems.join('; ') : 'Scripts see the user agent requests send: ' + ua);
+        }}
+
+        function checkScreen() {{
+            const common = promises.screens.some(s => s[0] === screen.width && s[1] === screen.height);
+            let detail = 'Pages see ' + screen.width + 'x' + screen.height;
+            if (promises.real_screen) detail += ' (this display is ' + promises.real_screen.join('x') + ')';
+            record('Screen', promises.common_hardware, common, detail);
+        }}
+
+        function checkCores() {{
+            const cores = navigator.hardwareConcurrency;
+            record('CPU cores', promises.common_hardware, promises.core_counts.includes(cores),
+                'Pages see ' + cores + ' (this machine has ' + promises.real_cores + ')');
+        }}
+
+        function checkTimezone() {{
+            const offset = new Date().getTimezoneOffset();
+            const zone = Intl.DateTimeFormat().resolvedOptions().timeZone || 'an unnamed timezone';
+            const utc = offset === 0 && ['UTC', 'Etc/UTC', 'GMT', 'Etc/GMT'].includes(zone);
+            const hours = Math.abs(offset) / 60;
+            record('Timezone', promises.utc_timezone, utc, 'Pages see ' + zone + ', UTC' + (offset > 0 ? '-' : '+') + hours);
+        }}
+
+        // A solid fill reads back exactly unless noise is added; the hash is
+        // compared with the previous run's
+        function checkCanvas() {{
+            const canvas = document.createElement('canvas');
+            canvas.width = 240;
+            canvas.height = 60;
+            const ctx = canvas.getContext('2d');
+            ctx.fillStyle = '#ff0040';
+            ctx.fillRect(0, 0, 240, 20);
+            ctx.fillStyle = '#00ff88';
+            ctx.font = '16px serif';
+            ctx.fillText('Marshall leak test ☺', 4, 44);
+
+            const fill = ctx.getImageData(0, 0, 240, 20).data;
+            let noisy = false;
+            for (let i = 0; i < fill.length && !noisy; i += 4) {{
+                noisy = fill[i] !== 255 || fill[i + 1] !== 0 || fill[i + 2] !== 64 || fill[i + 3] !== 255;
+            }}
+            const current = hash(canvas.toDataURL());
+            const previous = promises.previous_canvas;
+            let detail = 'Hash ' + current;
+            if (!previous) detail += '; run the test again to compare across reloads';
+            else if (previous === current) detail += ', the same as the last run';
+            else detail += ', changed since the last run (' + previous + ')';
+            detail += noisy ? '; noise found in a solid fill' : '; solid fills read back exactly';
+            record('Canvas', promises.canvas_noise, noisy || (!!previous && previous !== current), detail);
+            return current;
+        }}
+
+        // A font is installed when text set in it measures differently
+        // from the generic family it falls back to
+        function checkFonts() {{
+            const probes = ['Arial', 'Helvetica', 'Times New Roman', 'Courier New', 'Verdana', 'Georgia',
+                'Comic Sans MS', 'Segoe UI', 'Calibri', 'DejaVu Sans', 'Liberation Sans', 'Noto Sans',
+                'Ubuntu', 'Cantarell', 'Fira Sans', 'Roboto', 'JetBrains Mono', 'Source Code Pro'];
+            const generics = ['monospace', 'serif', 'sans-serif'];
+            const ctx = document.createElement('canvas').getContext('2d');
+            const width = font => {{
+                ctx.font = '72px ' + font;
+                return ctx.measureText('mmmmmmmmmmlli WMwm 0123').width;
+            }};
+            const baseline = generics.map(width);
+            const found = probes.filter(font => generics.some((generic, i) => width('"' + font + '", ' + generic) !== baseline[i]));
+            const detail = found.length
+                ? found.length + ' of ' + probes.length + ' probed fonts can be detected: ' + found.join(', ')
+                : 'None of ' + probes.length + ' probed fonts can be detected';
+            record('Fonts', promises.hide_fonts, found.length === 0, detail);
+        }}
+
+        function checkDoNotTrack() {{
+            const dnt = navigator.doNotTrack;
+            record('Do Not Track', promises.do_not_track, dnt === '1',
+                'navigator.doNotTrack is ' + (dnt === undefined ? 'missing' : JSON.stringify(dnt)));
+        }}
+
+        function render() {{
+            const counts = {{ pass: 0, fail: 0, info: 0 }};
+            const table = document.getElementById('results');
+            table.textContent = '';
+            rows.forEach(row => {{
+                counts[row.verdict]++;
+                const tr = document.createElement('tr');
+                [row.name, row.verdict.toUpperCase(), row.detail].forEach((text, i) => {{
+                    const td = document.createElement('td');
+                    td.textContent = text;
+                    if (i === 1) td.className = row.verdict;
+                    tr.appendChild(td);
+                }});
+                table.appendChild(tr);
+            }});
+            document.getElementById('summary').textContent =
+                counts.pass + ' passed, ' + counts.fail + ' failed, ' + counts.info + ' not promised by your settings';
+        }}
+
+        // A check that throws, perhaps from a half-working spoof, fails
+        // without taking the others down
+        async function run(name, check) {{
+            try {{
+                return await check();
+            }} catch (e) {{
+                rows.push({{ name: name, verdict: 'fail', detail: 'The check itself failed: ' + e }});
+            }}
+        }}
+
+        (async () => {{
+            await run('User agent', checkUserAgent);
+            await run('Screen', checkScreen);
+            await run('CPU cores', checkCores);
+            await run('Timezone', checkTimezone);
+            const canvas = await run('Canvas', checkCanvas);
+            await run('Fonts', checkFonts);
+            await run('Do Not Track', checkDoNotTrack);
+            await run('WebRTC', checkWebRTC);
+            render();
+            if (canvas) document.getElementById('again').href = 'marshall://leaktest?canvas=' + canvas;
+        }})();
+    </script>
+</body>
+</html>"##)
+}
+
+/// Generate the page shown instead of a dangerous site. "Go back" and
+/// "Proceed anyway" are `marshall://blocked/back` and
+/// `marshall://blocked/proceed?url=<url>&token=<token>` links the window
+/// carries out.
+pub fn generate_blocked_page(analysis: &ThreatAnalysis) -> String {
+    let indicators: String = analysis
+        .indicators
+        .iter()
+        .map(|indicator| {
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>+{}</td></tr>",
+                html_escape(&indicator.category),
+                html_escape(&indicator.description),
+                indicator.weight
+            )
+        })
+        .collect();
+    format!(r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Dangerous site blocked</title>
+    <style>
+        * {{ box-sizing: border-box; margin: 0; padding: 0; }}
+        :root {{
+            --red: #ff0040;
+            --bg: #0d0d0d;
+            --bg2: #1a1a1a;
+            --bg3: #252525;
+            --fg: #e0e0e0;
+            --fg-dim: #808080;
+            --border: #333;
+        }}
+        html, body {{
+            background: var(--bg);
+            color: var(--fg);
+            font-family: 'Segoe UI', -apple-system, sans-serif;
+            min-height: 100vh;
+        }}
+        .header {{
+            background: linear-gradient(135deg, var(--bg2) 0%, var(--bg) 100%);
+            border-bottom: 2px solid var(--red);
+            padding: 3rem 2rem;
+            text-align: center;
+        }}
+        .header h1 {{ font-size: 32px; color: var(--red); margin-bottom: 0.5rem; }}
+        .header p {{ color: var(--fg-dim); word-break: break-all; }}
+        .content {{
+            max-width: 900px;
+            margin: 2rem auto;
+            padding: 0 2rem;
+        }}
+        .privacy-card {{
+            background: var(--bg2);
+            border: 1px solid var(--border);
+            border-left: 3px solid var(--red);
+            border-radius: 8px;
+            padding: 1.5rem;
+            margin-bottom: 1.5rem;
+        }}
+        .privacy-card h3 {{ color: var(--red); margin-bottom: 1rem; }}
+        .privacy-card p {{ color: var(--fg-dim); line-height: 1.7; }}
+        table {{ width: 100%; border-collapse: collapse; font-size: 13px; margin: 1rem 0; }}
+        td {{ padding: 0.6rem 0.5rem; border-bottom: 1px solid var(--bg3); vertical-align: top; word-break: break-word; }}
+        td:first-child {{ font-weight: 600; white-space: nowrap; }}
+        .actions {{ display: flex; gap: 1rem; justify-content: space-between; align-items: center; }}
+        .button {{
+            background: var(--red);
+            color: var(--bg);
+            border-radius: 6px;
+            padding: 0.7rem 1.4rem;
+            font-weight: 600;
+            text-decoration: none;
+        }}
+        .proceed {{ color: var(--fg-dim); font-size: 13px; }}
+    </style>
+</head>
+<body>
+    <div class="header">
+        <h1>Dangerous site blocked</h1>
+        <p>Marshall rated <strong>{domain}</strong> {level} (score {score}/100) and stopped the page from loading.</p>
+    </div>
+    <div class="content">
+        <div class="privacy-card">
+            <h3>Why</h3>
+            <p>{url}</p>
+            <table>{indicators}</table>
+        </div>
+        <div class="actions">
+            <a class="button" href="marshall://blocked/back">&#8592; Go back</a>
+            <a class="proceed" href="marshall://blocked/proceed?url={encoded}&amp;token={token}">Proceed anyway (this session)</a>
+        </div>
+    </div>
+</body>
+</html>"##,
+        domain = html_escape(&analysis.domain),
+        level = analysis.level,
+        score = analysis.score,
+        url = html_escape(&analysis.url),
+        indicators = indicators,
+        encoded = urlencoding::encode(&analysis.url),
+        token = ACTION_TOKEN.as_str(),
+    )
+}
+
+/// `value` as JSON that can sit inside a `<script>` element
+fn script_json<T: serde::Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string(value)
+        .unwrap_or_else(|_| "null".to_string())
+        .replace("</", "<\\/")
+}
+
+/// Generate OSINT results page for a specific domain
+pub fn generate_osint_results(domain: &str) -> String {
+    format!(r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>OSINT Report - {domain}</title>
+    <style>
+        * {{ box-sizing: border-box; margin: 0; padding: 0; }}
+        :root {{
+            --red: #ff0040;
+            --green: #00ff88;
+            --bg: #0d0d0d;
+            --bg2: #1a1a1a;
+            --bg3: #252525;
+            --fg: #e0e0e0;
+            --fg-dim: #808080;
+            --border: #333;
+        }}
+        html, body {{
+            background: var(--bg);
+            color: var(--fg);
+            font-family: 'Segoe UI', -apple-system, sans-serif;
+            min-height: 100vh;
+        }}
+        a {{ color: var(--red); text-decoration: none; }}
+        .back-link {{
+            display: inline-flex;
+            align-items: center;
+            gap: 8px;
+            color: var(--fg-dim);
+            font-size: 14px;
+            margin: 1rem 2rem;
+        }}
+        .header {{
+            background: var(--bg2);
+            border-bottom: 2px solid var(--red);
+            padding: 2rem;
+        }}
+        .header h1 {{ color: var(--red); margin-bottom: 0.5rem; }}
+        .header .target {{ font-family: monospace; color: var(--green); font-size: 18px; }}
+        .container {{ max-width: 900px; margin: 2rem auto; padding: 0 2rem; }}
+        .report-section {{
+            background: var(--bg2);
+            border: 1px solid var(--border);
+            border-radius: 8px;
+            margin-bottom: 1.5rem;
+            overflow: hidden;
+        }}
+        .section-header {{
+            background: var(--bg3);
+            padding: 1rem 1.5rem;
+            border-bottom: 1px solid var(--border);
+            font-weight: 600;
+            color: var(--red);
+        }}
+        .section-content {{ padding: 1.5rem; }}
+        .external-links {{ display: flex; flex-wrap: wrap; gap: 8px; margin-top: 1rem; }}
+        .ext-link {{
+            padding: 8px 16px;
+            background: var(--bg3);
+            border: 1px solid var(--border);
+            border-radius: 6px;
+            color: var(--fg);
+            font-size: 12px;
+            text-decoration: none;
+        }}
+        .ext-link:hover {{ border-color: var(--red); color: var(--red); }}
+        .ext-link.primary {{ background: var(--red); color: white; border-color: var(--red); }}
+    </style>
+</head>
+<body>
+    <a href="javascript:void(0)" onclick="window.location.href='marshall://osint'" class="back-link">&#8592; Back to OSINT Tools</a>
+    <div class="header">
+        <h1>OSINT Report</h1>
+        <div class="target">{domain}</div>
+    </div>
+    <div class="container">
+        <div class="report-section">
+            <div class="section-header">Domain Information</div>
+            <div class="section-content">
+                <p>Target: <strong>{domain}</strong></p>
+                <p style="color: var(--green); margin-top: 0.5rem;">&#9679; Analysis Ready</p>
+                <div class="external-links">
+                    <a href="https://who.is/{domain}" target="_blank" class="ext-link">WHOIS Lookup</a>
+                    <a href="https://dnsdumpster.com/?search={domain}" target="_blank" class="ext-link">DNS Records</a>
+                    <a href="https://www.shodan.io/search?query={domain}" target="_blank" class="ext-link">Shodan</a>
+                    <a href="https://crt.sh/?q={domain}" target="_blank" class="ext-link">Certificates</a>
+                    <a href="https://www.ssllabs.com/ssltest/analyze.html?d={domain}" target="_blank" class="ext-link">SSL Test</a>
+                    <a href="marshall://osint/typosquat?domain={encoded}&amp;token={token}" class="ext-link">Lookalike Domains</a>
+                </div>
+            </div>
+        </div>
+        <div class="report-section">
+            <div class="section-header">Quick Actions</div>
+            <div class="section-content">
+                <p style="color: var(--fg-dim); margin-bottom: 1rem;">Click any tool above to perform detailed analysis.</p>
+                <a href="javascript:void(0)" onclick="window.location.href='marshall://assistant'" class="ext-link primary">Ask Dr Marshall about this target</a>
+            </div>
+        </div>
+    </div>
+</body>
+</html>"##, domain = domain, encoded = urlencoding::encode(domain), token = ACTION_TOKEN.as_str())
+}
+
+/// Generate the typosquat report for `domain`. Rows start out unchecked;
+/// the window fills them in with `lookalikeChecked(lookalike)` as DNS and
+/// WHOIS answer, then calls `lookalikesDone()`. A `notice` explains why
+/// they won't be.
+pub fn generate_typosquat_page(domain: &str, permutations: &[Permutation], notice: Option<&str>) -> String {
+    let techniques: Vec<(Technique, &str)> = Technique::ALL.iter().map(|t| (*t, t.label())).collect();
+    let notice = notice
+        .map(|notice| format!(r#"<p class="notice">{}</p>"#, html_escape(notice)))
+        .unwrap_or_default();
+    format!(r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Lookalikes - {title}</title>
+    <style>
+        * {{ box-sizing: border-box; margin: 0; padding: 0; }}
+        :root {{
+            --red: #ff0040;
+            --green: #00ff88;
+            --orange: #ffaa00;
+            --bg: #0d0d0d;
+            --bg2: #1a1a1a;
+            --bg3: #252525;
+            --fg: #e0e0e0;
+            --fg-dim: #808080;
+            --border: #333;
+        }}
+        html, body {{
+            background: var(--bg);
+            color: var(--fg);
+            font-family: 'Segoe UI', -apple-system, sans-serif;
+            min-height: 100vh;
+        }}
+        a {{ color: var(--red); text-decoration: none; }}
+        .back-link {{
+            display: inline-flex;
+            align-items: center;
+            gap: 8px;
+            color: var(--fg-dim);
+            font-size: 14px;
+            margin: 1rem 2rem;
+        }}
+        .header {{
+            background: var(--bg2);
+            border-bottom: 2px solid var(--red);
+            padding: 2rem;
+        }}
+        .header h1 {{ color: var(--red); margin-bottom: 0.5rem; }}
+        .header .target {{ font-family: monospace; color: var(--green); font-size: 18px; }}
+        .container {{ max-width: 1100px; margin: 2rem auto; padding: 0 2rem; }}
+        .summary {{ color: var(--fg-dim); margin-bottom: 1rem; }}
+        .notice {{ color: var(--orange); margin-bottom: 1rem; }}
+        table {{ width: 100%; border-collapse: collapse; font-size: 13px; }}
+        th {{
+            text-align: left;
+            padding: 0.6rem 0.5rem;
+            background: var(--bg3);
+            color: var(--red);
+            cursor: pointer;
+            user-select: none;
+            white-space: nowrap;
+        }}
+        th.sorted::after {{ content: ' \25B4'; }}
+        th.sorted.descending::after {{ content: ' \25BE'; }}
+        td {{ padding: 0.5rem; border-bottom: 1px solid var(--bg3); vertical-align: top; word-break: break-word; }}
+        td .ascii {{ display: block; color: var(--fg-dim); font-size: 11px; font-family: monospace; }}
+        .domain {{ font-family: monospace; }}
+        .live {{ color: var(--red); font-weight: 600; }}
+        .pending, .quiet {{ color: var(--fg-dim); }}
+    </style>
+</head>
+<body>
+    <a href="javascript:void(0)" onclick="window.location.href='marshall://osint'" class="back-link">&#8592; Back to OSINT Tools</a>
+    <div class="header">
+        <h1>Lookalike Domains</h1>
+        <div class="target">{title}</div>
+    </div>
+    <div class="container">
+        {notice}
+        <p class="summary" id="summary"></p>
+        <table>
+            <thead>
+                <tr>
+                    <th data-key="domain">Domain</th>
+                    <th data-key="technique">Technique</th>
+                    <th data-key="status">Status</th>
+                    <th data-key="addresses">Addresses</th>
+                    <th data-key="created">Registered</th>
+                    <th data-key="registrar">Registrar</th>
+                </tr>
+            </thead>
+            <tbody id="lookalikes"></tbody>
+        </table>
+    </div>
+    <script>
+        window.typosquatDomain = {domain};
+        const techniques = Object.fromEntries({techniques});
+        const rows = {permutations}.map(p => Object.assign(p, {{
+            checked: false, addresses: [], created: null, registrar: null, error: null
+        }}));
+        let done = false;
+
+        // Live domains first, then the ones still being checked
+        const keys = {{
+            domain: row => row.display,
+            technique: row => techniques[row.technique],
+            status: row => row.addresses.length ? 0 : row.checked ? 2 : 1,
+            addresses: row => row.addresses.join(', '),
+            created: row => row.created || '',
+            registrar: row => row.registrar || '',
+        }};
+        let sortKey = 'status';
+        let descending = false;
+
+        function status(row) {{
+            if (row.addresses.length) return ['Live', 'live'];
+            return row.checked ? ['No address', 'quiet'] : ['Checking…', 'pending'];
+        }}
+
+        function cell(tr, text, className) {{
+            const td = document.createElement('td');
+            td.textContent = text;
+            if (className) td.className = className;
+            tr.appendChild(td);
+            return td;
+        }}
+
+        function render() {{
+            const key = keys[sortKey];
+            rows.sort((a, b) => {{
+                const x = key(a), y = key(b);
+                const order = x < y ? -1 : x > y ? 1 : a.display.localeCompare(b.display);
+                return descending ? -order : order;
+            }});
+            const body = document.getElementById('lookalikes');
+            body.textContent = '';
+            rows.forEach(row => {{
+                const tr = document.createElement('tr');
+                const name = cell(tr, row.display, 'domain');
+                if (row.display !== row.domain) {{
+                    const ascii = document.createElement('span');
+                    ascii.className = 'ascii';
+                    ascii.textContent = row.domain;
+                    name.appendChild(ascii);
+                }}
+                cell(tr, techniques[row.technique]);
+                cell(tr, ...status(row));
+                cell(tr, row.addresses.join(', '));
+                cell(tr, row.created ? row.created.slice(0, 10) : '');
+                cell(tr, row.registrar || (row.error ? 'WHOIS: ' + row.error : ''), row.registrar ? '' : 'quiet');
+                body.appendChild(tr);
+            }});
+            document.querySelectorAll('th').forEach(th => {{
+                th.className = th.dataset.key === sortKey ? (descending ? 'sorted descending' : 'sorted') : '';
+            }});
+            const checked = rows.filter(row => row.checked).length;
+            const live = rows.filter(row => row.addresses.length).length;
+            document.getElementById('summary').textContent = rows.length + ' lookalikes, ' + live + ' live' +
+                (done || checked === rows.length ? '' : ', ' + checked + ' checked so far');
+        }}
+
+        function lookalikeChecked(lookalike) {{
+            const row = rows.find(row => row.domain === lookalike.domain);
+            if (row) Object.assign(row, lookalike, {{ checked: true }});
+            render();
+        }}
+
+        function lookalikesDone() {{
+            done = true;
+            render();
+        }}
+
+        document.querySelectorAll('th').forEach(th => th.addEventListener('click', () => {{
+            descending = th.dataset.key === sortKey && !descending;
+            sortKey = th.dataset.key;
+            render();
+        }}));
+        render();
+    </script>
+</body>
+</html>"##,
+        title = html_escape(domain),
+        notice = notice,
+        domain = script_json(domain),
+        techniques = script_json(&techniques),
+        permutations = script_json(permutations),
+    )
+}
+
+/// Generate Menu/Dashboard page with settings links
+pub fn generate_menu_page() -> String {
+    r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Marshall Menu</title>
+    <style>
+        * { box-sizing: border-box; margin: 0; padding: 0; }
+        :root {
+            --red: #ff0040;
+            --green: #00ff88;
+            --bg: #0d0d0d;
+            --bg2: #1a1a1a;
+            --bg3: #252525;
+            --fg: #e0e0e0;
+            --fg-dim: #808080;
+            --border: #333;
+        }
+        html, body {
+            background: var(--bg);
+            color: var(--fg);
+            font-family: 'Segoe UI', -apple-system, sans-serif;
+            min-height: 100vh;
+        }
+        .menu-container {
+            max-width: 400px;
+            margin: 0 auto;
+            padding: 2rem;
+        }
+        .menu-header {
+            text-align: center;
             margin-bottom: 2rem;
             padding-bottom: 1rem;
             border-bottom: 2px solid var(--red);
@@ -1989,6 +3089,51 @@ pub fn generate_menu_page() -> String {
                     </span>
                 </a>
             </li>
+            <li class="menu-item">
+                <a href="javascript:void(0)" onclick="window.location.href='marshall://containers'" class="menu-link">
+                    <span class="menu-icon">&#128450;</span>
+                    <span class="menu-text">
+                        <strong>Containers</strong>
+                        <small>Separate identities and cookie jars</small>
+                    </span>
+                </a>
+            </li>
+            <li class="menu-item">
+                <a href="javascript:void(0)" onclick="window.location.href='marshall://leaktest'" class="menu-link">
+                    <span class="menu-icon">&#128269;</span>
+                    <span class="menu-text">
+                        <strong>Leak Test</strong>
+                        <small>Check what pages can learn about you</small>
+                    </span>
+                </a>
+            </li>
+            <li class="menu-item">
+                <a href="javascript:void(0)" onclick="window.location.href='marshall://downloads'" class="menu-link">
+                    <span class="menu-icon">&#11015;</span>
+                    <span class="menu-text">
+                        <strong>Downloads</strong>
+                        <small>Progress, warnings and checksums</small>
+                    </span>
+                </a>
+            </li>
+            <li class="menu-item">
+                <a href="javascript:void(0)" onclick="window.location.href='marshall://bookmarks/import'" class="menu-link">
+                    <span class="menu-icon">&#128229;</span>
+                    <span class="menu-text">
+                        <strong>Import Bookmarks</strong>
+                        <small>From Firefox, Chrome or an HTML file</small>
+                    </span>
+                </a>
+            </li>
+            <li class="menu-item">
+                <a href="javascript:void(0)" onclick="window.location.href='marshall://bookmarks/export'" class="menu-link">
+                    <span class="menu-icon">&#128228;</span>
+                    <span class="menu-text">
+                        <strong>Export Bookmarks</strong>
+                        <small>Save as HTML for any browser</small>
+                    </span>
+                </a>
+            </li>
         </ul>
         <div class="version">
             Marshall v2.0.0 | <span style="color: var(--green);">&#9679;</span> Secure
@@ -3010,3 +4155,50 @@ pub fn generate_voip_page() -> String {
 </body>
 </html>"##.to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_internal_pages_hold_the_action_token() {
+        let token = format!("&token={}", ACTION_TOKEN.as_str());
+        assert!(has_action_token(&format!("marshall://blocked/proceed?url=https%3A%2F%2Fphish.tk%2F{}", token)));
+        assert!(!has_action_token("marshall://blocked/proceed?url=https%3A%2F%2Fphish.tk%2F"));
+        assert!(!has_action_token("marshall://blocked/proceed?url=https%3A%2F%2Fphish.tk%2F&token=guess"));
+    }
+
+    #[test]
+    fn container_actions_carry_the_token() {
+        let html = generate_containers_page(&[Container::new("Work", "red")]);
+        // The new container form, the Work form and its Open tab link
+        assert_eq!(html.matches(ACTION_TOKEN.as_str()).count(), 3);
+        assert!(has_action_token(&format!("marshall://containers/open/work?token={}", ACTION_TOKEN.as_str())));
+    }
+
+    #[test]
+    fn site_profile_forms_carry_the_token() {
+        let html = generate_privacy_page(&[], &[SiteProfile::new("https://example.com")]);
+        // The example.com form and the new site form
+        assert_eq!(html.matches(ACTION_TOKEN.as_str()).count(), 2);
+    }
+
+    #[test]
+    fn typosquat_links_carry_the_token() {
+        let html = generate_osint_page(&["example.com".to_string()]);
+        // The check form and the example.com link
+        assert_eq!(html.matches(ACTION_TOKEN.as_str()).count(), 2);
+        assert!(generate_osint_results("example.com").contains(ACTION_TOKEN.as_str()));
+    }
+
+    #[test]
+    fn settings_warn_of_failed_network_checks() {
+        let checks = [
+            LeakCheck { name: "Proxy", verdict: Verdict::Info, detail: "No proxy".to_string() },
+            LeakCheck { name: "DNS", verdict: Verdict::Fail, detail: "System resolver <leaks>".to_string() },
+        ];
+        let html = generate_settings_page(&checks);
+        assert!(html.contains("<h4>Proxy</h4>"));
+        assert!(html.contains("<h4 class=\"warning\">⚠ DNS</h4><p>System resolver &lt;leaks&gt;</p>"));
+    }
+}
@@ -7,16 +7,19 @@
 
 mod window;
 mod toolbar;
+mod bookmarksbar;
 mod tabbar;
 mod urlbar;
 mod statusbar;
 mod theme;
 mod dialogs;
+mod downloads;
 pub mod dashboard;
 pub mod search_results;
 
 pub use window::BrowserWindow;
 pub use toolbar::Toolbar;
+pub use bookmarksbar::BookmarksBar;
 pub use tabbar::TabBar;
 pub use urlbar::UrlBar;
 pub use statusbar::StatusBar;
@@ -8,6 +8,8 @@ use gtk::prelude::*;
 use gtk::{Box as GtkBox, Label, ProgressBar, Orientation};
 
 use crate::config::Config;
+#[cfg(feature = "tor")]
+use crate::network::tor::TorStatus;
 
 /// Status bar at bottom of window
 #[derive(Clone)]
@@ -17,6 +19,9 @@ pub struct StatusBar {
     progress_bar: ProgressBar,
     privacy_label: Label,
     blocked_label: Label,
+    /// Shown in tor mode only
+    #[cfg(feature = "tor")]
+    tor_label: Label,
 }
 
 impl StatusBar {
@@ -48,9 +53,20 @@ impl StatusBar {
         let blocked_label = Label::new(Some("🛡️ 0 blocked"));
         blocked_label.style_context().add_class("blocked-counter");
 
+        // Tor indicator, until set_tor_status shows it
+        #[cfg(feature = "tor")]
+        let tor_label = Label::new(None);
+        #[cfg(feature = "tor")]
+        {
+            tor_label.style_context().add_class("tor-indicator");
+            tor_label.set_no_show_all(true);
+        }
+
         container.pack_start(&status_label, true, true, 0);
         container.pack_start(&progress_bar, false, false, 4);
         container.pack_start(&blocked_label, false, false, 4);
+        #[cfg(feature = "tor")]
+        container.pack_start(&tor_label, false, false, 4);
         container.pack_start(&privacy_label, false, false, 4);
 
         Self {
@@ -59,6 +75,8 @@ impl StatusBar {
             progress_bar,
             privacy_label,
             blocked_label,
+            #[cfg(feature = "tor")]
+            tor_label,
         }
     }
 
@@ -87,4 +105,20 @@ impl StatusBar {
         let mode = if strict { "STRICT" } else { "Standard" };
         self.privacy_label.set_text(&format!("🔒 {}", mode));
     }
+
+    /// Show how far Tor is from carrying traffic
+    #[cfg(feature = "tor")]
+    pub fn set_tor_status(&self, status: TorStatus) {
+        let context = self.tor_label.style_context();
+        for class in ["tor-connected", "tor-bootstrapping", "tor-unreachable"] {
+            context.remove_class(class);
+        }
+        context.add_class(match status {
+            TorStatus::Connected => "tor-connected",
+            TorStatus::Starting | TorStatus::Bootstrapping => "tor-bootstrapping",
+            TorStatus::Unreachable => "tor-unreachable",
+        });
+        self.tor_label.set_text(&format!("🧅 Tor: {}", status.label()));
+        self.tor_label.show();
+    }
 }
@@ -5,9 +5,13 @@
 //! Tab bar widget
 
 use gtk::prelude::*;
-use gtk::{Box as GtkBox, Button, Label, Notebook, Orientation, Image};
+use gtk::{Box as GtkBox, Button, CssProvider, EventBox, Label, Notebook, Orientation, Image, Widget};
+use gdk::gdk_pixbuf::{InterpType, Pixbuf};
 
-/// Tab bar with tabs
+const FAVICON_SIZE: i32 = 16;
+
+/// Tab bar with tabs. Pages live in the notebook, so the bar is also the
+/// content area of the window.
 #[derive(Clone)]
 pub struct TabBar {
     container: GtkBox,
@@ -17,7 +21,7 @@ pub struct TabBar {
 
 impl TabBar {
     pub fn new() -> Self {
-        let container = GtkBox::new(Orientation::Horizontal, 0);
+        let container = GtkBox::new(Orientation::Vertical, 0);
         container.style_context().add_class("marshall-tabbar");
 
         let notebook = Notebook::new();
@@ -25,13 +29,15 @@ impl TabBar {
         notebook.set_show_border(false);
         notebook.style_context().add_class("marshall-notebook");
         notebook.set_hexpand(true);
+        notebook.set_vexpand(true);
 
         let new_tab_btn = Button::from_icon_name(Some("list-add-symbolic"), gtk::IconSize::Button);
         new_tab_btn.set_tooltip_text(Some("New Tab (Ctrl+T)"));
         new_tab_btn.style_context().add_class("new-tab-btn");
+        new_tab_btn.show();
+        notebook.set_action_widget(&new_tab_btn, gtk::PackType::End);
 
         container.pack_start(&notebook, true, true, 0);
-        container.pack_start(&new_tab_btn, false, false, 4);
 
         Self {
             container,
@@ -47,6 +53,54 @@ impl TabBar {
     pub fn notebook(&self) -> &Notebook {
         &self.notebook
     }
+
+    /// Add `page` with `tab` as its label; returns the page index
+    pub fn add_tab(&self, page: &impl IsA<Widget>, tab: &Tab) -> u32 {
+        page.show_all();
+        let index = self.notebook.append_page(page, Some(tab.widget()));
+        self.notebook.set_tab_reorderable(page, true);
+        index
+    }
+
+    /// Insert `page` right after the current one, as browsers do for links
+    /// opened in a new tab
+    pub fn insert_tab_after_current(&self, page: &impl IsA<Widget>, tab: &Tab) -> u32 {
+        page.show_all();
+        let position = self.notebook.current_page().map(|p| p + 1);
+        let index = self.notebook.insert_page(page, Some(tab.widget()), position);
+        self.notebook.set_tab_reorderable(page, true);
+        index
+    }
+
+    pub fn remove_tab(&self, page: &impl IsA<Widget>) {
+        if let Some(index) = self.notebook.page_num(page) {
+            self.notebook.remove_page(Some(index));
+        }
+    }
+
+    pub fn select(&self, page: &impl IsA<Widget>) {
+        if let Some(index) = self.notebook.page_num(page) {
+            self.notebook.set_current_page(Some(index));
+        }
+    }
+
+    pub fn current_page(&self) -> Option<Widget> {
+        self.notebook.current_page().and_then(|i| self.notebook.nth_page(Some(i)))
+    }
+
+    pub fn connect_new_tab<F: Fn() + 'static>(&self, f: F) {
+        self.new_tab_btn.connect_clicked(move |_| f());
+    }
+
+    /// Called with the page that became current
+    pub fn connect_switch<F: Fn(&Widget) + 'static>(&self, f: F) {
+        self.notebook.connect_switch_page(move |_, page, _| f(page));
+    }
+
+    /// Called with the page order after the user drags a tab
+    pub fn connect_reordered<F: Fn(&Widget, u32) + 'static>(&self, f: F) {
+        self.notebook.connect_page_reordered(move |_, page, index| f(page, index));
+    }
 }
 
 impl Default for TabBar {
@@ -56,11 +110,15 @@ impl Default for TabBar {
 }
 
 /// Individual tab widget
+#[derive(Clone)]
 pub struct Tab {
+    event_box: EventBox,
     container: GtkBox,
     label: Label,
     close_btn: Button,
     favicon: Image,
+    /// Colours the tab with its container
+    container_style: CssProvider,
 }
 
 impl Tab {
@@ -83,19 +141,83 @@ impl Tab {
         container.pack_start(&label, true, true, 0);
         container.pack_start(&close_btn, false, false, 0);
 
+        let container_style = CssProvider::new();
+        container
+            .style_context()
+            .add_provider(&container_style, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+
+        // Box has no window of its own; the event box catches right-clicks
+        let event_box = EventBox::new();
+        event_box.set_visible_window(false);
+        event_box.add(&container);
+        event_box.show_all();
+
         Self {
+            event_box,
             container,
             label,
             close_btn,
             favicon,
+            container_style,
         }
     }
 
+    /// The widget to use as the notebook tab label
+    pub fn widget(&self) -> &EventBox {
+        &self.event_box
+    }
+
     pub fn container(&self) -> &GtkBox {
         &self.container
     }
 
     pub fn set_title(&self, title: &str) {
         self.label.set_text(title);
+        self.container.set_tooltip_text(Some(title));
+    }
+
+    /// Show the site's icon, or the generic one when it has none
+    pub fn set_favicon(&self, icon: Option<&Pixbuf>) {
+        match icon {
+            Some(icon) => {
+                let icon = icon
+                    .scale_simple(FAVICON_SIZE, FAVICON_SIZE, InterpType::Bilinear)
+                    .unwrap_or_else(|| icon.clone());
+                self.favicon.set_from_pixbuf(Some(&icon));
+            }
+            None => self
+                .favicon
+                .set_from_icon_name(Some("applications-internet-symbolic"), gtk::IconSize::Menu),
+        }
+    }
+
+    /// Underline the tab in its container's colour, or clear it
+    pub fn set_container(&self, container: Option<(&str, &str)>) {
+        let css = match container {
+            Some((name, color)) => {
+                self.favicon.set_tooltip_text(Some(&format!("Container: {}", name)));
+                format!(".marshall-tab {{ box-shadow: inset 0 -3px 0 {}; }}", color)
+            }
+            None => {
+                self.favicon.set_tooltip_text(None);
+                String::new()
+            }
+        };
+        self.container_style.load_from_data(css.as_bytes()).ok();
+    }
+
+    pub fn connect_close<F: Fn() + 'static>(&self, f: F) {
+        self.close_btn.connect_clicked(move |_| f());
+    }
+
+    /// Called with the button event when the tab is right-clicked
+    pub fn connect_context_menu<F: Fn(&gdk::EventButton) + 'static>(&self, f: F) {
+        self.event_box.connect_button_press_event(move |_, event| {
+            if event.button() == 3 {
+                f(event);
+                return gtk::Inhibit(true);
+            }
+            gtk::Inhibit(false)
+        });
     }
 }
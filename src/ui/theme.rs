@@ -49,6 +49,10 @@ window {
     background-color: @bg_color;
 }
 
+.marshall-private .marshall-toolbar {
+    border-bottom: 2px solid #8b5cf6;
+}
+
 /* Toolbar */
 .marshall-toolbar {
     background: linear-gradient(to bottom, @bg_secondary, @bg_color);
@@ -126,8 +130,59 @@ window {
     color: @error_color;
 }
 
+.security-icon.threat {
+    color: @warning_color;
+}
+
+/* Bookmarks Bar */
+.marshall-bookmarksbar {
+    background-color: @bg_color;
+    border-bottom: 1px solid @border_color;
+    padding: 2px 8px;
+}
+
+.bookmark-item {
+    padding: 2px 8px;
+    color: @fg_color;
+    font-size: 12px;
+}
+
+.bookmark-item:hover {
+    background-color: @bg_tertiary;
+}
+
+.bookmarks-hint {
+    color: @fg_secondary;
+    font-size: 12px;
+}
+
+/* URL bar suggestions */
+.marshall-suggestions {
+    background-color: @bg_secondary;
+    border: 1px solid @border_color;
+    padding: 4px 0;
+}
+
+.marshall-suggestions row {
+    padding: 6px 12px;
+}
+
+.marshall-suggestions row:selected {
+    background-color: alpha(@accent_color, 0.2);
+}
+
+.suggestion-url {
+    color: @fg_secondary;
+    font-size: 12px;
+}
+
+.suggestion-action {
+    color: @accent_color;
+    font-size: 12px;
+}
+
 /* Tab Bar */
-.marshall-tabbar {
+.marshall-notebook header {
     background-color: @bg_secondary;
     border-bottom: 1px solid @border_color;
     padding: 4px 4px 0 4px;
@@ -239,6 +294,27 @@ window {
     padding: 2px 8px;
 }
 
+.tor-indicator {
+    font-weight: bold;
+    padding: 2px 8px;
+    border-radius: 4px;
+}
+
+.tor-indicator.tor-connected {
+    color: @success_color;
+    background-color: alpha(@success_color, 0.1);
+}
+
+.tor-indicator.tor-bootstrapping {
+    color: @warning_color;
+    background-color: alpha(@warning_color, 0.1);
+}
+
+.tor-indicator.tor-unreachable {
+    color: @error_color;
+    background-color: alpha(@error_color, 0.1);
+}
+
 /* WebView */
 webview {
     background-color: @bg_color;
@@ -304,6 +380,11 @@ window {
     border-bottom: 1px solid #e0e0e0;
 }
 
+.marshall-bookmarksbar {
+    background-color: #fafafa;
+    border-bottom: 1px solid #e0e0e0;
+}
+
 .marshall-urlbar {
     background-color: #ffffff;
     border: 1px solid #d0d0d0;
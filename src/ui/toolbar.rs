@@ -10,6 +10,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::config::Config;
+use crate::engine::Suggestion;
 use super::UrlBar;
 
 /// Navigation toolbar
@@ -104,6 +105,10 @@ impl Toolbar {
         self.url_bar.set_url(url);
     }
 
+    pub fn set_threat_warning(&self, warning: Option<&str>) {
+        self.url_bar.set_threat_warning(warning);
+    }
+
     pub fn get_url(&self) -> String {
         self.url_bar.get_url()
     }
@@ -129,6 +134,13 @@ impl Toolbar {
         }
     }
 
+    /// Show how many requests were blocked on the current page
+    pub fn set_blocked_count(&self, count: u64) {
+        if self.shield_btn.style_context().has_class("shield-active") {
+            self.shield_btn.set_tooltip_text(Some(&format!("Privacy Protection: ON — {} blocked", count)));
+        }
+    }
+
     // Connect signal handlers
     pub fn connect_back<F: Fn() + 'static>(&self, f: F) {
         self.back_btn.connect_clicked(move |_| f());
@@ -150,6 +162,22 @@ impl Toolbar {
         self.url_bar.connect_activate(f);
     }
 
+    pub fn connect_url_suggest<F: Fn(&str) + 'static>(&self, f: F) {
+        self.url_bar.connect_suggest(f);
+    }
+
+    pub fn connect_switch_to_tab<F: Fn(&str) + 'static>(&self, f: F) {
+        self.url_bar.connect_switch_to_tab(f);
+    }
+
+    pub fn typed_url(&self) -> String {
+        self.url_bar.typed_text()
+    }
+
+    pub fn show_url_suggestions(&self, query: &str, suggestions: Vec<Suggestion>, completion: Option<&str>) {
+        self.url_bar.show_suggestions(query, suggestions, completion);
+    }
+
     pub fn connect_menu<F: Fn() + 'static>(&self, f: F) {
         self.menu_btn.connect_clicked(move |_| f());
     }
@@ -2,15 +2,165 @@
 // Licensed under the MIT License. See LICENSE file in the project root.
 // https://github.com/bad-antics/marshall
 
-//! URL/Address bar widget with Marshall branding
+//! URL/Address bar widget with Marshall branding, and the suggestion popup
+//! shown while typing
 
 use gtk::prelude::*;
-use gtk::{Box as GtkBox, Entry, Image, Orientation};
-use std::cell::RefCell;
+use gtk::{Box as GtkBox, Entry, Image, Label, ListBox, ListBoxRow, Orientation, Popover};
+use gdk::keys::constants as keys;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use glib::clone;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::engine::{Suggestion, SuggestionKind};
+
+type Callback = Rc<RefCell<Option<Box<dyn Fn(&str)>>>>;
+
+/// Suggestion list under the entry. It never takes focus; the entry keeps
+/// the keyboard and moves the selection.
+#[derive(Clone)]
+struct SuggestionPopup {
+    popover: Popover,
+    list: ListBox,
+    entry: Entry,
+    items: Rc<RefCell<Vec<Suggestion>>>,
+    on_switch_tab: Callback,
+}
+
+impl SuggestionPopup {
+    fn new(entry: &Entry) -> Self {
+        let popover = Popover::new(Some(entry));
+        popover.set_modal(false);
+        popover.set_position(gtk::PositionType::Bottom);
+        popover.style_context().add_class("marshall-suggestions");
+
+        // Clicking a row must not take focus from the entry, which would
+        // close the popup before the click lands
+        let list = ListBox::new();
+        list.set_can_focus(false);
+        list.set_selection_mode(gtk::SelectionMode::Single);
+        list.set_activate_on_single_click(true);
+        popover.add(&list);
+
+        let popup = Self {
+            popover,
+            list,
+            entry: entry.clone(),
+            items: Rc::new(RefCell::new(Vec::new())),
+            on_switch_tab: Rc::new(RefCell::new(None)),
+        };
+        let this = popup.clone();
+        popup.list.connect_row_activated(move |_, row| this.choose(row.index() as usize));
+        popup
+    }
+
+    fn is_visible(&self) -> bool {
+        self.popover.is_visible()
+    }
+
+    fn show(&self, suggestions: Vec<Suggestion>) {
+        for row in self.list.children() {
+            self.list.remove(&row);
+        }
+        if suggestions.is_empty() {
+            self.hide();
+            return;
+        }
+        for suggestion in &suggestions {
+            self.list.add(&Self::row(suggestion));
+        }
+        *self.items.borrow_mut() = suggestions;
+
+        self.list.unselect_all();
+        self.popover.set_size_request(self.entry.allocated_width(), -1);
+        self.popover.show_all();
+        self.popover.popup();
+    }
+
+    fn hide(&self) {
+        self.popover.popdown();
+        self.items.borrow_mut().clear();
+    }
+
+    fn row(suggestion: &Suggestion) -> ListBoxRow {
+        let icon = match suggestion.kind {
+            SuggestionKind::Typed if suggestion.title.starts_with("Search for") => "system-search-symbolic",
+            SuggestionKind::Typed => "go-jump-symbolic",
+            SuggestionKind::OpenTab => "tab-new-symbolic",
+            SuggestionKind::Bookmark => "user-bookmarks-symbolic",
+            SuggestionKind::History => "document-open-recent-symbolic",
+            SuggestionKind::Internal => "applications-internet-symbolic",
+            SuggestionKind::Search => "system-search-symbolic",
+        };
+
+        let line = GtkBox::new(Orientation::Horizontal, 8);
+        line.pack_start(&Image::from_icon_name(Some(icon), gtk::IconSize::Menu), false, false, 0);
+
+        let title = Label::new(Some(&suggestion.title));
+        title.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        title.set_xalign(0.0);
+        line.pack_start(&title, false, false, 0);
+
+        if !matches!(suggestion.kind, SuggestionKind::Typed | SuggestionKind::Search) {
+            let url = Label::new(Some(&suggestion.url));
+            url.set_ellipsize(gtk::pango::EllipsizeMode::Middle);
+            url.set_xalign(0.0);
+            url.style_context().add_class("suggestion-url");
+            line.pack_start(&url, true, true, 0);
+        }
+        if suggestion.kind == SuggestionKind::OpenTab {
+            let action = Label::new(Some("Switch to tab"));
+            action.style_context().add_class("suggestion-action");
+            line.pack_end(&action, false, false, 0);
+        }
+
+        let row = ListBoxRow::new();
+        row.set_can_focus(false);
+        row.add(&line);
+        row
+    }
+
+    /// Move the selection by `delta` rows, wrapping back to the entry
+    fn move_selection(&self, delta: i32) {
+        let count = self.items.borrow().len() as i32;
+        let current = self.list.selected_row().map(|row| row.index()).unwrap_or(-1);
+        let next = current + delta;
+        if next < 0 || next >= count {
+            self.list.unselect_all();
+        } else {
+            self.list.select_row(self.list.row_at_index(next).as_ref());
+        }
+    }
+
+    /// Activate the selected suggestion; false when none is selected
+    fn choose_selected(&self) -> bool {
+        match self.list.selected_row() {
+            Some(row) => {
+                self.choose(row.index() as usize);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn choose(&self, index: usize) {
+        let suggestion = match self.items.borrow().get(index) {
+            Some(suggestion) => suggestion.clone(),
+            None => return,
+        };
+        self.hide();
+
+        match (&suggestion.tab_id, self.on_switch_tab.borrow().as_ref()) {
+            (Some(tab_id), Some(switch)) => switch(tab_id),
+            _ => {
+                self.entry.set_text(&suggestion.url);
+                self.entry.emit_activate();
+            }
+        }
+    }
+}
+
 /// URL bar with security indicator - shows "Marshall" by default, real URL on focus
 #[derive(Clone)]
 pub struct UrlBar {
@@ -19,6 +169,16 @@ pub struct UrlBar {
     security_icon: Image,
     real_url: Rc<RefCell<String>>,
     is_focused: Rc<RefCell<bool>>,
+    popup: SuggestionPopup,
+    /// What the user typed, without the inline completion
+    typed: Rc<RefCell<String>>,
+    /// Whether the last edit inserted text
+    grew: Rc<Cell<bool>>,
+    /// Set while we change the text ourselves
+    updating: Rc<Cell<bool>>,
+    on_suggest: Callback,
+    /// Why the page looks suspicious, shown in place of the TLS state
+    threat: Rc<RefCell<Option<String>>>,
 }
 
 impl UrlBar {
@@ -45,13 +205,22 @@ impl UrlBar {
         
         let real_url = Rc::new(RefCell::new(String::from("marshall://home")));
         let is_focused = Rc::new(RefCell::new(false));
+        let popup = SuggestionPopup::new(&entry);
+        let typed = Rc::new(RefCell::new(String::new()));
+        let grew = Rc::new(Cell::new(false));
+        let updating = Rc::new(Cell::new(false));
+        let on_suggest: Callback = Rc::new(RefCell::new(None));
         
         // Show real URL when focused, Marshall branding when not
         let real_url_clone = real_url.clone();
         let is_focused_clone = is_focused.clone();
+        let typed_clone = typed.clone();
+        let updating_clone = updating.clone();
         entry.connect_focus_in_event(clone!(@weak entry => @default-return gtk::Inhibit(false), move |_, _| {
             *is_focused_clone.borrow_mut() = true;
             let url = real_url_clone.borrow().clone();
+            *typed_clone.borrow_mut() = url.clone();
+            let _guard = UpdateGuard::new(&updating_clone);
             entry.set_text(&url);
             entry.select_region(0, -1);
             gtk::Inhibit(false)
@@ -59,8 +228,12 @@ impl UrlBar {
         
         let real_url_clone = real_url.clone();
         let is_focused_clone = is_focused.clone();
+        let popup_clone = popup.clone();
+        let updating_clone = updating.clone();
         entry.connect_focus_out_event(clone!(@weak entry => @default-return gtk::Inhibit(false), move |_, _| {
             *is_focused_clone.borrow_mut() = false;
+            popup_clone.hide();
+            let _guard = UpdateGuard::new(&updating_clone);
             // Show Marshall branding unless it's an internal marshall:// URL
             let url = real_url_clone.borrow();
             if url.starts_with("marshall://") {
@@ -70,6 +243,61 @@ impl UrlBar {
             }
             gtk::Inhibit(false)
         }));
+
+        // Only typing completes inline; deleting the completion must stick
+        let grew_clone = grew.clone();
+        entry.connect_insert_text(move |_, _, _| grew_clone.set(true));
+        let grew_clone = grew.clone();
+        entry.connect_delete_text(move |_, _, _| grew_clone.set(false));
+
+        // Ask for suggestions as the user types
+        let is_focused_clone = is_focused.clone();
+        let typed_clone = typed.clone();
+        let updating_clone = updating.clone();
+        let popup_clone = popup.clone();
+        let on_suggest_clone = on_suggest.clone();
+        entry.connect_changed(move |entry| {
+            if updating_clone.get() || !*is_focused_clone.borrow() {
+                return;
+            }
+            let text = entry.text().to_string();
+            *typed_clone.borrow_mut() = text.clone();
+
+            if text.trim().is_empty() {
+                popup_clone.hide();
+            } else if let Some(suggest) = on_suggest_clone.borrow().as_ref() {
+                suggest(&text);
+            }
+        });
+
+        // Arrow keys move through the suggestions, Tab accepts the inline
+        // completion, Escape closes the popup
+        let popup_clone = popup.clone();
+        entry.connect_key_press_event(move |entry, event| {
+            let key = event.keyval();
+            let handled = if key == keys::Down || key == keys::Up {
+                if popup_clone.is_visible() {
+                    popup_clone.move_selection(if key == keys::Down { 1 } else { -1 });
+                }
+                true
+            } else if key == keys::Return || key == keys::KP_Enter {
+                popup_clone.is_visible() && popup_clone.choose_selected()
+            } else if key == keys::Tab {
+                match entry.selection_bounds() {
+                    Some((_, end)) if end == entry.text_length() as i32 => {
+                        entry.set_position(-1);
+                        true
+                    }
+                    _ => false,
+                }
+            } else if key == keys::Escape && popup_clone.is_visible() {
+                popup_clone.hide();
+                true
+            } else {
+                false
+            };
+            gtk::Inhibit(handled)
+        });
         
         // Intercept copy to scramble URLs
         let real_url_clone = real_url.clone();
@@ -87,6 +315,12 @@ impl UrlBar {
             security_icon,
             real_url,
             is_focused,
+            popup,
+            typed,
+            grew,
+            updating,
+            on_suggest,
+            threat: Rc::new(RefCell::new(None)),
         }
     }
     
@@ -109,6 +343,7 @@ impl UrlBar {
     pub fn set_url(&self, url: &str) {
         // Store the real URL
         *self.real_url.borrow_mut() = url.to_string();
+        let _guard = UpdateGuard::new(&self.updating);
         
         // Only show real URL if focused, otherwise show Marshall branding
         if *self.is_focused.borrow() {
@@ -126,8 +361,24 @@ impl UrlBar {
         self.real_url.borrow().clone()
     }
 
+    /// Warn that the page looks suspicious, or clear the warning
+    pub fn set_threat_warning(&self, warning: Option<&str>) {
+        *self.threat.borrow_mut() = warning.map(str::to_string);
+        let url = self.real_url.borrow().clone();
+        self.update_security_indicator(&url);
+    }
+
     fn update_security_indicator(&self, url: &str) {
         let ctx = self.security_icon.style_context();
+        self.security_icon.set_tooltip_text(self.threat.borrow().as_deref());
+        if self.threat.borrow().is_some() {
+            self.security_icon.set_from_icon_name(Some("dialog-warning-symbolic"), gtk::IconSize::Button);
+            ctx.remove_class("secure");
+            ctx.remove_class("insecure");
+            ctx.add_class("threat");
+            return;
+        }
+        ctx.remove_class("threat");
         if url.starts_with("https://") {
             self.security_icon.set_from_icon_name(Some("channel-secure-symbolic"), gtk::IconSize::Button);
             ctx.remove_class("insecure");
@@ -154,11 +405,63 @@ impl UrlBar {
     /// Connect to the activate signal (Enter pressed)
     pub fn connect_activate<F: Fn(&str) + 'static>(&self, f: F) {
         let entry = self.entry.clone();
+        let popup = self.popup.clone();
         self.entry.connect_activate(move |_| {
+            popup.hide();
             let text = entry.text();
             f(&text);
         });
     }
+
+    /// Called with the typed text whenever it changes; answer with
+    /// `show_suggestions`
+    pub fn connect_suggest<F: Fn(&str) + 'static>(&self, f: F) {
+        *self.on_suggest.borrow_mut() = Some(Box::new(f));
+    }
+
+    /// Called with the tab id when a "switch to tab" suggestion is chosen
+    pub fn connect_switch_to_tab<F: Fn(&str) + 'static>(&self, f: F) {
+        *self.popup.on_switch_tab.borrow_mut() = Some(Box::new(f));
+    }
+
+    /// What the user has typed, without the inline completion
+    pub fn typed_text(&self) -> String {
+        self.typed.borrow().clone()
+    }
+
+    /// Show suggestions for `query`, and complete it inline to `completion`
+    /// if the user is still on that text. Stale answers are dropped.
+    pub fn show_suggestions(&self, query: &str, suggestions: Vec<Suggestion>, completion: Option<&str>) {
+        if !*self.is_focused.borrow() || *self.typed.borrow() != query {
+            return;
+        }
+        self.popup.show(suggestions);
+
+        let completion = completion.filter(|c| c.len() > query.len() && c.starts_with(query));
+        if let Some(completion) = completion.filter(|_| self.grew.get()) {
+            if self.entry.text() == query {
+                let _guard = UpdateGuard::new(&self.updating);
+                self.entry.set_text(completion);
+                self.entry.select_region(query.chars().count() as i32, -1);
+            }
+        }
+    }
+}
+
+/// Marks the text as changed by us for as long as it lives
+struct UpdateGuard<'a>(&'a Cell<bool>);
+
+impl<'a> UpdateGuard<'a> {
+    fn new(updating: &'a Cell<bool>) -> Self {
+        updating.set(true);
+        Self(updating)
+    }
+}
+
+impl Drop for UpdateGuard<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
 }
 
 impl Default for UrlBar {
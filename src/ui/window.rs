@@ -5,22 +5,61 @@
 //! Main browser window implementation
 
 use gtk::prelude::*;
-use gtk::{Application, ApplicationWindow, Box as GtkBox, Orientation, CssProvider, StyleContext, Paned};
-use webkit2gtk::{WebView, WebViewExt, WebContext, WebContextExt, LoadEvent, PolicyDecisionType, NavigationPolicyDecision, PolicyDecisionExt, NavigationPolicyDecisionExt, URIRequestExt};
-use gdk::Screen;
-use tracing::info;
-use std::cell::RefCell;
-use std::rc::Rc;
-
-use crate::config::Config;
+use gtk::{Application, ApplicationWindow, Box as GtkBox, Orientation, CssProvider, StyleContext, Paned, AccelGroup, AccelFlags, InfoBar, Label, MessageType, ResponseType};
+use webkit2gtk::{WebView, WebViewExt, WebContext, WebContextExt, LoadEvent, PolicyDecisionType, NavigationPolicyDecision, PolicyDecisionExt, NavigationPolicyDecisionExt, NavigationType, URIRequestExt};
+use webkit2gtk::{UserContentManager, UserContentManagerExt, UserStyleSheet, UserContentInjectedFrames, UserStyleLevel, FaviconDatabaseExt};
+use gdk::{ModifierType, Screen};
+use gdk::gdk_pixbuf::Pixbuf;
+use tracing::{info, warn};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::{Config, PrivacyConfig};
+use crate::adblock::{AdBlocker, FilterListManager};
+use crate::engine::ContentFilter;
+use crate::database::{CookieSetting, SiteProfile};
+use crate::engine::{self, OpenTab, SuggestionSources, WebViewManager};
+use crate::bookmarks::{Bookmark, BookmarkManager, TOOLBAR_FOLDER};
+use crate::downloads::{download_dir, DownloadManager};
+use crate::history::{HistoryManager, Transition};
+use crate::network;
+use crate::privacy::{Shields, SiteProfiles, host_of};
+use crate::search::typosquat::{self, Lookalike, Permutation};
+use crate::security::SecurityManager;
+use crate::security::persona::Persona;
+use crate::security::leaktest::{self, LeakPromises};
+use crate::security::threat_intel::{ThreatIntel, ThreatLevel};
+use crate::tabs::{Container, Containers, SavedSession, Session, SessionStore, TabManager, WindowSession};
 use crate::assistant::ai_chat::AIChatPanel;
-use super::{Toolbar, TabBar, StatusBar, Theme, homepage};
+use super::{Toolbar, BookmarksBar, TabBar, StatusBar, Theme, dialogs, downloads, homepage};
+use super::tabbar::Tab;
 
 const WINDOW_WIDTH: i32 = 1400;
 const WINDOW_HEIGHT: i32 = 900;
+const WINDOW_TITLE: &str = "Marshall - NullSec Browser";
+const PRIVATE_WINDOW_TITLE: &str = "Marshall - Private Browsing";
+/// How often the filter-list cache checks for lists past their expiry
+const FILTER_LIST_CHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+/// How often open tabs are written out, so a crash loses little
+const SESSION_SAVE_INTERVAL_SECS: u32 = 15;
+/// Rows in the URL bar popup, and how many come from history and bookmarks
+const MAX_SUGGESTIONS: usize = 10;
+const MAX_HISTORY_SUGGESTIONS: usize = 6;
+const MAX_BOOKMARK_SUGGESTIONS: usize = 3;
+/// Pause in typing before the search engine is asked for suggestions
+const SEARCH_SUGGESTION_DELAY: Duration = Duration::from_millis(200);
+/// How often an open downloads page is redrawn while something changes
+const DOWNLOADS_PAGE_REFRESH_SECS: u32 = 1;
+/// How often the status bar's Tor indicator is refreshed
+#[cfg(feature = "tor")]
+const TOR_STATUS_REFRESH_SECS: u32 = 2;
 
-/// Internal navigation history for marshall:// pages
-/// WebKit's load_html doesn't create history entries, so we track them ourselves
+/// Per-tab navigation history, for marshall:// and web pages alike
+/// WebKit's load_html doesn't create history entries and a restored tab has no
+/// WebKit history, so we track them ourselves
 #[derive(Clone)]
 struct InternalHistory {
     entries: Vec<String>,
@@ -34,6 +73,12 @@ impl InternalHistory {
             current: -1,
         }
     }
+
+    /// History saved with a session, positioned at `index`
+    fn restore(entries: Vec<String>, index: usize) -> Self {
+        let current = if entries.is_empty() { -1 } else { index.min(entries.len() - 1) as i32 };
+        Self { entries, current }
+    }
     
     fn push(&mut self, uri: &str) {
         // If we're not at the end, truncate forward history
@@ -73,6 +118,115 @@ impl InternalHistory {
             None
         }
     }
+    
+    fn current(&self) -> Option<&str> {
+        usize::try_from(self.current).ok().and_then(|i| self.entries.get(i)).map(String::as_str)
+    }
+
+    fn index(&self) -> usize {
+        self.current.max(0) as usize
+    }
+}
+
+/// One tab: its view, its label in the tab bar and its navigation history
+struct BrowserTab {
+    id: String,
+    webview: WebView,
+    label: Tab,
+    /// Container whose context the view uses
+    container: Option<Container>,
+    history: RefCell<InternalHistory>,
+    /// Set while a history entry loads so it isn't recorded again
+    traversing: Cell<bool>,
+    /// How the page being loaded was reached, for the history database
+    transition: Cell<Transition>,
+    /// Subresources blocked on the current page
+    blocked: Cell<u64>,
+}
+
+impl BrowserTab {
+    /// Our history covers every page once one has loaded; WebKit's only
+    /// matters before that
+    fn can_go_back(&self) -> bool {
+        let history = self.history.borrow();
+        if history.current >= 0 {
+            history.can_go_back()
+        } else {
+            self.webview.can_go_back()
+        }
+    }
+
+    fn can_go_forward(&self) -> bool {
+        let history = self.history.borrow();
+        if history.current >= 0 {
+            history.can_go_forward()
+        } else {
+            self.webview.can_go_forward()
+        }
+    }
+
+    /// URL to show for the tab. Internal pages are loaded with load_html and
+    /// report about:blank, so they show their marshall:// address instead.
+    fn display_url(&self) -> String {
+        match self.webview.uri() {
+            Some(uri) if !uri.is_empty() && uri != "about:blank" && !uri.starts_with("marshall://") => uri.to_string(),
+            _ => self.history.borrow().current().unwrap_or("marshall://home").to_string(),
+        }
+    }
+}
+
+thread_local! {
+    /// Ephemeral context shared by the open private windows, and how many
+    /// windows use it
+    static PRIVATE_CONTEXT: RefCell<Option<(WebContext, usize)>> = RefCell::new(None);
+
+    /// One context per container, shared by every window; two contexts must
+    /// never open the same data directory
+    static CONTAINER_CONTEXTS: RefCell<HashMap<String, WebContext>> = RefCell::new(HashMap::new());
+
+    /// Every window built, held weakly so a closed window is dropped
+    static OPEN_WINDOWS: RefCell<Vec<Weak<WindowState>>> = RefCell::new(Vec::new());
+
+    /// Whether the one filter-list scheduler has been started
+    static FILTER_LIST_SCHEDULER: Cell<bool> = Cell::new(false);
+}
+
+/// Everything the window's signal handlers share
+struct WindowState {
+    window: ApplicationWindow,
+    /// Private windows use an ephemeral context and are never saved
+    private: bool,
+    config: Config,
+    context: WebContext,
+    toolbar: Toolbar,
+    /// None when `show_bookmarks_bar` is off
+    bookmarks_bar: Option<BookmarksBar>,
+    status_bar: StatusBar,
+    tab_bar: TabBar,
+    tab_manager: TabManager,
+    tabs: RefCell<Vec<Rc<BrowserTab>>>,
+    shields: Shields,
+    content_filter: RefCell<Option<ContentFilter>>,
+    /// The window's place in the saved session; None when it can't be persisted
+    session: Option<WindowSession>,
+}
+
+impl WindowState {
+    fn tab(&self, id: &str) -> Option<Rc<BrowserTab>> {
+        self.tabs.borrow().iter().find(|t| t.id == id).cloned()
+    }
+
+    fn tab_for_page(&self, page: &gtk::Widget) -> Option<Rc<BrowserTab>> {
+        self.tabs.borrow().iter().find(|t| t.webview.upcast_ref::<gtk::Widget>() == page).cloned()
+    }
+
+    fn active_tab(&self) -> Option<Rc<BrowserTab>> {
+        self.tab(&self.tab_manager.get_active_tab()?.id)
+    }
+
+    fn is_active(&self, id: &str) -> bool {
+        self.tab_manager.get_active_tab().is_some_and(|t| t.id == id)
+    }
 }
 
 /// Main browser window
@@ -80,13 +234,24 @@ pub struct BrowserWindow;
 
 impl BrowserWindow {
     pub fn new(app: &Application, config: &Config) -> ApplicationWindow {
+        Self::build(app, config, false, None)
+    }
+
+    /// A window whose cookies, cache, storage and history stay in memory and
+    /// are wiped when the last private window closes
+    pub fn new_private(app: &Application, config: &Config) -> ApplicationWindow {
+        Self::build(app, config, true, None)
+    }
+
+    /// A window, restoring `restore` into it if given
+    fn build(app: &Application, config: &Config, private: bool, restore: Option<&Session>) -> ApplicationWindow {
         // Apply CSS theme
         Self::apply_theme(config);
 
         // Create main window
         let window = ApplicationWindow::builder()
             .application(app)
-            .title("Marshall - NullSec Browser")
+            .title(if private { PRIVATE_WINDOW_TITLE } else { WINDOW_TITLE })
             .default_width(WINDOW_WIDTH)
             .default_height(WINDOW_HEIGHT)
             .build();
@@ -94,27 +259,36 @@ impl BrowserWindow {
         // Main vertical container
         let main_box = GtkBox::new(Orientation::Vertical, 0);
         main_box.style_context().add_class("marshall-main");
+        if private {
+            main_box.style_context().add_class("marshall-private");
+        }
 
         // Create toolbar
         let toolbar = Toolbar::new(config);
         main_box.pack_start(toolbar.container(), false, false, 0);
 
-        // Create tab bar
-        let tab_bar = TabBar::new();
-        main_box.pack_start(tab_bar.container(), false, false, 0);
+        // Bookmarks bar from the toolbar folder
+        let bookmarks_bar = config.appearance.show_bookmarks_bar.then(|| {
+            let bar = BookmarksBar::new();
+            main_box.pack_start(bar.container(), false, false, 0);
+            bar
+        });
 
         // Create WebKit context with privacy settings
-        let web_context = Self::create_private_context(config);
-        
-        // Create initial web view
-        let webview = Self::create_webview(&web_context, config);
-        webview.set_vexpand(true);
-        webview.set_hexpand(true);
+        let web_context = if private {
+            Self::acquire_ephemeral_context(config)
+        } else {
+            Self::create_private_context(config)
+        };
+        downloads::watch(&web_context, private, download_dir(&config.general));
+
+        // Tab bar - each tab's web view is a page of its notebook
+        let tab_bar = TabBar::new();
 
         // Create Dr. Marshall AI sidebar in a horizontal pane
         let paned = Paned::new(Orientation::Horizontal);
         paned.set_wide_handle(true);
-        paned.pack1(&webview, true, false);
+        paned.pack1(tab_bar.container(), true, false);
 
         // Build AI Chat Panel and wrap in a frame
         let ai_panel = AIChatPanel::new();
@@ -131,13 +305,37 @@ impl BrowserWindow {
         main_box.pack_start(status_bar.container(), false, false, 0);
 
         window.add(&main_box);
-        
-        // Create internal history tracker for marshall:// pages
-        let history = Rc::new(RefCell::new(InternalHistory::new()));
-        
-        // Connect all signals BEFORE loading homepage
-        Self::connect_toolbar_signals(&toolbar, &webview, config, history.clone());
-        Self::connect_webview_signals(&webview, &toolbar, &status_bar, history.clone());
+
+        let state = Rc::new(WindowState {
+            window: window.clone(),
+            private,
+            config: config.clone(),
+            context: web_context,
+            toolbar: toolbar.clone(),
+            bookmarks_bar,
+            status_bar,
+            tab_bar,
+            tab_manager: TabManager::new(),
+            tabs: RefCell::new(Vec::new()),
+            // Ad and tracker blocking with the per-site whitelist
+            shields: Shields::new(config),
+            content_filter: RefCell::new(None),
+            session: if private {
+                None
+            } else {
+                SessionStore::global().map(SessionStore::add_window)
+            },
+        });
+        OPEN_WINDOWS.with(|windows| windows.borrow_mut().push(Rc::downgrade(&state)));
+        Self::compile_content_filter(&state);
+        Self::schedule_filter_list_updates(&state);
+
+        // Connect all signals BEFORE opening the first tab
+        Self::connect_toolbar_signals(&state);
+        Self::connect_shield_signals(&state);
+        Self::connect_bookmarks_bar(&state);
+        Self::connect_tab_signals(&state);
+        Self::connect_keybindings(&state);
 
         // Connect AI sidebar toggle
         let ai_sidebar_toggle = ai_sidebar.clone();
@@ -148,17 +346,358 @@ impl BrowserWindow {
                 ai_sidebar_toggle.show_all();
             }
         });
-        
-        // Now load Marshall branded homepage (history is added in handle_internal_url)
-        Self::handle_internal_url(&webview, "marshall://home", &history, &toolbar);
-        
+
         window.show_all();
 
+        match restore {
+            Some(session) => Self::restore_session(&state, session),
+            None => Self::restore_previous(&state, &main_box),
+        }
+        Self::connect_session_saving(&state);
+        Self::connect_downloads_page(&state);
+        #[cfg(feature = "tor")]
+        Self::connect_tor_status(&state);
+
+        if private {
+            window.connect_destroy(|_| Self::release_ephemeral_context());
+        }
+
         info!("Browser window initialized with Marshall homepage and OSINT injection");
-        
+
         window
     }
 
+    /// Open a new tab and load `uri` into it. `related` is the view whose page
+    /// asked for the tab (target=_blank, window.open); WebKit loads the request
+    /// itself in that case. Background tabs open next to the current one
+    /// without taking focus. Private windows have no containers.
+    fn open_tab(state: &Rc<WindowState>, uri: Option<&str>, related: Option<&WebView>, background: bool, container: Option<&Container>) -> Rc<BrowserTab> {
+        let container = container.filter(|_| !state.private);
+        let previous = state.tab_manager.get_active_tab();
+        let id = match container {
+            Some(container) => state.tab_manager.create_container_tab(uri.unwrap_or(""), &container.id),
+            None => state.tab_manager.create_tab(uri.unwrap_or(""), state.private),
+        };
+        if background {
+            // create_tab made the new tab active; hand focus back
+            if let Some(previous) = &previous {
+                state.tab_manager.set_active_tab(&previous.id);
+            }
+        }
+
+        let context = match container {
+            Some(container) => Self::container_context(state, container),
+            None if related.is_none() => Self::tab_context(state),
+            // WebKit puts a related view in its opener's context, so popups
+            // share the opener's circuit in tor mode
+            None => related.and_then(|r| r.context()).unwrap_or_else(|| state.context.clone()),
+        };
+        let webview = Self::create_webview(&context, &state.config, related, container);
+        webview.set_vexpand(true);
+        webview.set_hexpand(true);
+
+        let label = Tab::new("New Tab");
+        label.set_container(container.map(|c| (c.name.as_str(), c.css_color())));
+
+        let tab = Rc::new(BrowserTab {
+            id,
+            webview,
+            label,
+            container: container.cloned(),
+            history: RefCell::new(InternalHistory::new()),
+            traversing: Cell::new(false),
+            transition: Cell::new(Transition::Link),
+            blocked: Cell::new(0),
+        });
+        state.tabs.borrow_mut().push(tab.clone());
+
+        let st = state.clone();
+        let id = tab.id.clone();
+        tab.label.connect_close(move || Self::close_tab(&st, &id));
+        let st = state.clone();
+        let id = tab.id.clone();
+        tab.label.connect_context_menu(move |event| Self::show_tab_menu(&st, &id, event));
+        Self::connect_webview_signals(state, &tab);
+        Self::apply_shields(&tab.webview, &state.shields, &state.content_filter);
+
+        // Tabs opened from a page go next to it, others at the end
+        let index = if previous.is_some() && (related.is_some() || background) {
+            state.tab_bar.insert_tab_after_current(&tab.webview, &tab.label)
+        } else {
+            state.tab_bar.add_tab(&tab.webview, &tab.label)
+        };
+        state.tab_manager.move_tab(&tab.id, index as usize);
+        if !background {
+            state.tab_bar.select(&tab.webview);
+        }
+
+        if let Some(uri) = uri {
+            Self::navigate(state, &tab, uri);
+        }
+        tab
+    }
+
+    /// Close a tab; closing the last one opens a fresh homepage tab
+    fn close_tab(state: &Rc<WindowState>, id: &str) {
+        let tab = match state.tab(id) {
+            Some(tab) => tab,
+            None => return,
+        };
+        state.tabs.borrow_mut().retain(|t| t.id != id);
+        state.tab_manager.close_tab(id);
+        // Removing the page switches the notebook to a neighbour
+        state.tab_bar.remove_tab(&tab.webview);
+
+        if state.tab_manager.tab_count() == 0 {
+            Self::open_tab(state, Some("marshall://home"), None, false, None);
+        }
+    }
+
+    /// Reopen the tabs of a saved session with their back/forward history
+    fn restore_session(state: &Rc<WindowState>, session: &Session) {
+        let mut restored = Vec::new();
+        for saved in &session.tabs {
+            let container = saved.container.as_deref().and_then(|id| Containers::global().get(id));
+            let tab = Self::open_tab(state, None, None, false, container.as_ref());
+            let mut history = InternalHistory::restore(saved.history.clone(), saved.history_index);
+            if history.current().is_none() && !saved.url.is_empty() {
+                history.push(&saved.url);
+            }
+
+            match history.current().map(str::to_string) {
+                Some(current) => {
+                    *tab.history.borrow_mut() = history;
+                    tab.label.set_title(&saved.title);
+                    state.tab_manager.update_tab_title(&tab.id, &saved.title);
+                    Self::history_changed(state, &tab);
+                    Self::load_history_entry(state, &tab, &current);
+                }
+                None => Self::navigate(state, &tab, "marshall://home"),
+            }
+            restored.push(tab);
+        }
+
+        if let Some(tab) = restored.get(session.active) {
+            state.tab_bar.select(&tab.webview);
+        }
+        info!("Restored {} tabs from the previous session", restored.len());
+    }
+
+    /// Restore the last session, or open the Marshall branded homepage
+    /// (history is added in handle_internal_url). The first window takes
+    /// every window the last run left.
+    fn restore_previous(state: &Rc<WindowState>, main_box: &GtkBox) {
+        let previous = state.session.as_ref().and_then(|s| s.store().take_previous());
+        match previous {
+            Some(previous) if state.config.general.restore_session => Self::restore_windows(state, &previous),
+            Some(previous) if previous.crashed() => {
+                Self::open_tab(state, Some("marshall://home"), None, false, None);
+                Self::offer_session_restore(state, main_box, previous);
+            }
+            _ => {
+                Self::open_tab(state, Some("marshall://home"), None, false, None);
+            }
+        }
+    }
+
+    /// Restore the first saved window into this one, and open a window for
+    /// each of the others
+    fn restore_windows(state: &Rc<WindowState>, previous: &SavedSession) {
+        let mut windows = previous.windows.iter();
+        if let Some(first) = windows.next() {
+            Self::restore_session(state, first);
+        }
+        if let Some(app) = state.window.application() {
+            for session in windows {
+                Self::build(&app, &state.config, false, Some(session)).present();
+            }
+        }
+    }
+
+    /// After a crash, offer to reopen the windows and tabs that were open
+    fn offer_session_restore(state: &Rc<WindowState>, container: &GtkBox, previous: SavedSession) {
+        let bar = InfoBar::new();
+        bar.set_message_type(gtk::MessageType::Question);
+        bar.set_show_close_button(true);
+        let message = match previous.windows.len() {
+            1 => format!("Marshall didn't shut down correctly. {} tab(s) were open.", previous.tab_count()),
+            windows => format!(
+                "Marshall didn't shut down correctly. {} tab(s) were open in {} windows.",
+                previous.tab_count(),
+                windows
+            ),
+        };
+        bar.content_area().add(&Label::new(Some(&message)));
+        bar.add_button("Restore previous session", ResponseType::Accept);
+
+        // Below the toolbar and bookmarks bar
+        container.pack_start(&bar, false, false, 0);
+        container.reorder_child(&bar, if state.bookmarks_bar.is_some() { 2 } else { 1 });
+        bar.show_all();
+
+        let st = state.clone();
+        bar.connect_response(move |bar, response| {
+            if response == ResponseType::Accept {
+                // The homepage tab opened at launch is replaced by the session
+                let untouched: Vec<String> = st
+                    .tabs
+                    .borrow()
+                    .iter()
+                    .filter(|t| t.history.borrow().entries == ["marshall://home"])
+                    .map(|t| t.id.clone())
+                    .collect();
+                Self::restore_windows(&st, &previous);
+                for id in untouched {
+                    Self::close_tab(&st, &id);
+                }
+            }
+            bar.hide();
+        });
+    }
+
+    /// Save the session periodically, and once more when the window closes;
+    /// the store marks the exit clean when it is the last window
+    fn connect_session_saving(state: &Rc<WindowState>) {
+        if state.session.is_none() {
+            return;
+        }
+
+        let st = state.clone();
+        glib::timeout_add_seconds_local(SESSION_SAVE_INTERVAL_SECS, move || {
+            if !st.window.is_visible() {
+                return glib::Continue(false);
+            }
+            Self::save_session(&st, false);
+            glib::Continue(true)
+        });
+
+        let st = state.clone();
+        state.window.connect_delete_event(move |_, _| {
+            Self::save_session(&st, true);
+            gtk::Inhibit(false)
+        });
+    }
+
+    /// Keep open downloads pages current while downloads progress
+    fn connect_downloads_page(state: &Rc<WindowState>) {
+        let st = state.clone();
+        let seen = Cell::new(DownloadManager::global().read().revision());
+        glib::timeout_add_seconds_local(DOWNLOADS_PAGE_REFRESH_SECS, move || {
+            if !st.window.is_visible() {
+                return glib::Continue(false);
+            }
+            let revision = DownloadManager::global().read().revision();
+            if seen.replace(revision) != revision {
+                for tab in st.tabs.borrow().iter() {
+                    if tab.history.borrow().current() == Some("marshall://downloads") {
+                        Self::load_internal_page(&st.config, &tab.webview, "marshall://downloads");
+                    }
+                }
+            }
+            glib::Continue(true)
+        });
+    }
+
+    /// Show Tor's state in the status bar while tor mode is on
+    #[cfg(feature = "tor")]
+    fn connect_tor_status(state: &Rc<WindowState>) {
+        if !state.config.network.tor_enabled {
+            return;
+        }
+        state.status_bar.set_tor_status(network::tor::status());
+        let st = state.clone();
+        glib::timeout_add_seconds_local(TOR_STATUS_REFRESH_SECS, move || {
+            if !st.window.is_visible() {
+                return glib::Continue(false);
+            }
+            st.status_bar.set_tor_status(network::tor::status());
+            glib::Continue(true)
+        });
+    }
+
+    fn save_session(state: &WindowState, closing: bool) {
+        if let Some(window) = &state.session {
+            let session = Session::capture(&state.tab_manager);
+            let saved = if closing { window.close(session) } else { window.save(session) };
+            if let Err(e) = saved {
+                warn!("Failed to save session: {}", e);
+            }
+        }
+    }
+
+    /// Add the page a tab just navigated to to its history, and outside
+    /// private windows record the visit. Internal pages report about:blank;
+    /// handle_internal_url records those.
+    fn record_page(state: &WindowState, tab: &BrowserTab) {
+        let uri = match tab.webview.uri() {
+            Some(uri) if !uri.is_empty() && uri != "about:blank" && !uri.starts_with("marshall://") => uri.to_string(),
+            _ => return,
+        };
+        let transition = tab.transition.replace(Transition::Link);
+        if tab.traversing.replace(false) {
+            return;
+        }
+
+        if !state.private {
+            let referrer = match transition {
+                Transition::Link | Transition::Form => tab
+                    .history
+                    .borrow()
+                    .current()
+                    .filter(|previous| !previous.starts_with("marshall://") && *previous != uri)
+                    .map(str::to_string),
+                _ => None,
+            };
+            let title = tab.webview.title().map(|t| t.to_string()).unwrap_or_default();
+            if let Err(e) = HistoryManager::global().add_visit(&uri, &title, transition, referrer.as_deref()) {
+                warn!("{}", e);
+            }
+        }
+
+        tab.history.borrow_mut().push(&uri);
+        Self::history_changed(state, tab);
+    }
+
+    /// Show the state of `tab` in the toolbar, status bar and window title
+    fn sync_toolbar(state: &WindowState, tab: &BrowserTab) {
+        let uri = tab.webview.uri().map(|u| u.to_string()).unwrap_or_default();
+        state.toolbar.set_threat_warning(Self::threat_warning(&uri).as_deref());
+        state.toolbar.set_url(&tab.display_url());
+        state.toolbar.set_can_go_back(tab.can_go_back());
+        state.toolbar.set_can_go_forward(tab.can_go_forward());
+
+        let host = tab.webview.uri().map(|uri| host_of(&uri)).unwrap_or_default();
+        state.toolbar.set_shield_active(state.shields.is_enabled_for(&host));
+        state.toolbar.set_blocked_count(tab.blocked.get());
+        state.status_bar.set_blocked_count(tab.blocked.get());
+
+        if tab.webview.is_loading() {
+            state.status_bar.set_progress(tab.webview.estimated_load_progress());
+        } else {
+            state.status_bar.set_progress(0.0);
+        }
+
+        let suffix = if state.private { "Marshall Private Browsing" } else { "Marshall" };
+        match tab.webview.title().filter(|t| !t.is_empty()) {
+            Some(title) => state.window.set_title(&format!("{} - {}", title, suffix)),
+            None if state.private => state.window.set_title(PRIVATE_WINDOW_TITLE),
+            None => state.window.set_title(WINDOW_TITLE),
+        }
+    }
+
+    /// URL bar tooltip for a page that looks suspicious but isn't blocked
+    fn threat_warning(uri: &str) -> Option<String> {
+        if !uri.starts_with("http://") && !uri.starts_with("https://") {
+            return None;
+        }
+        let analysis = ThreatIntel::global().read().analyze_url(uri);
+        if analysis.level != ThreatLevel::Suspicious {
+            return None;
+        }
+        let reasons: Vec<&str> = analysis.indicators.iter().map(|i| i.description.as_str()).collect();
+        Some(format!("{} looks suspicious (score {}/100): {}", analysis.domain, analysis.score, reasons.join("; ")))
+    }
+
     /// Load the Marshall branded homepage (legacy, kept for reference)
     #[allow(dead_code)]
     fn load_marshall_home(webview: &WebView) {
@@ -168,22 +707,350 @@ impl BrowserWindow {
     }
     
     /// Handle internal marshall:// URLs with history tracking
-    fn handle_internal_url(webview: &WebView, uri: &str, history: &Rc<RefCell<InternalHistory>>, toolbar: &Toolbar) {
+    fn handle_internal_url(state: &Rc<WindowState>, tab: &BrowserTab, uri: &str) {
+        // Opening a container tab leaves this tab where it is
+        if let Some(id) = uri.strip_prefix("marshall://containers/open/") {
+            if !Self::issued_internally(uri) {
+                return;
+            }
+            let id = id.split('?').next().unwrap_or(id);
+            match Containers::global().get(id.trim_end_matches('/')) {
+                Some(container) => {
+                    Self::open_tab(state, Some("marshall://home"), None, false, Some(&container));
+                }
+                None => warn!("No container named {}", id),
+            }
+            return;
+        }
+
+        // Saving a container redraws the page without a history entry, so
+        // going back never submits the form again
+        if uri.starts_with("marshall://containers/save") {
+            if Self::issued_internally(uri) {
+                let query = uri.split_once('?').map(|(_, q)| q).unwrap_or("");
+                match Containers::deleted_by(query) {
+                    Some(id) => Self::remove_container(&id),
+                    None => match Containers::global().apply_form(query) {
+                        Ok(()) => Self::refresh_container_proxies(),
+                        Err(e) => warn!("Container not saved: {}", e),
+                    },
+                }
+            }
+            Self::load_internal_page(&state.config, &tab.webview, "marshall://containers");
+            return;
+        }
+
+        // Site profiles likewise; a page that could save one could exempt
+        // itself from blocking
+        if uri.starts_with("marshall://privacy/site") {
+            if Self::issued_internally(uri) {
+                let query = uri.split_once('?').map(|(_, q)| q).unwrap_or("");
+                if let Err(e) = SiteProfiles::global().apply_form(query) {
+                    warn!("Site profile not saved: {}", e);
+                }
+            }
+            Self::load_internal_page(&state.config, &tab.webview, "marshall://privacy");
+            return;
+        }
+
+        // Download actions redraw the page without a history entry
+        if let Some(action) = uri.strip_prefix("marshall://downloads/") {
+            if Self::issued_internally(uri) {
+                let action = action.split('?').next().unwrap_or(action).trim_end_matches('/');
+                let (action, id) = action.split_once('/').unwrap_or((action, ""));
+                downloads::perform(action, id, state.private);
+            }
+            Self::load_internal_page(&state.config, &tab.webview, "marshall://downloads");
+            return;
+        }
+
+        // Leaving the dangerous-site page
+        if let Some(action) = uri.strip_prefix("marshall://blocked/") {
+            let (action, query) = action.split_once('?').unwrap_or((action, ""));
+            if action.trim_end_matches('/') == "proceed" {
+                if !Self::issued_internally(uri) {
+                    return;
+                }
+                let url = url::form_urlencoded::parse(query.as_bytes())
+                    .find(|(key, _)| key == "url")
+                    .map(|(_, url)| url.into_owned())
+                    .filter(|url| url.starts_with("http://") || url.starts_with("https://"));
+                if let Some(url) = url {
+                    let domain = ThreatIntel::global().read().analyze_url(&url).domain;
+                    warn!("Proceeding to {} despite the warning, for this session", domain);
+                    ThreatIntel::global().write().allow_for_session(&domain);
+                    Self::prepare_load(&state.config, &tab.webview, tab.container.as_ref(), &url);
+                    tab.webview.load_uri(&url);
+                }
+            } else {
+                let previous = tab.history.borrow_mut().go_back();
+                match previous {
+                    Some(previous) => {
+                        Self::history_changed(state, tab);
+                        Self::load_history_entry(state, tab, &previous);
+                    }
+                    None => Self::handle_internal_url(state, tab, "marshall://home"),
+                }
+            }
+            return;
+        }
+
+        // A typosquat report sets off lookups of every lookalike
+        if uri.starts_with("marshall://osint/typosquat") && !Self::issued_internally(uri) {
+            Self::handle_internal_url(state, tab, "marshall://osint");
+            return;
+        }
+
+        // Bookmark import and export only open a file chooser
+        match uri.trim_end_matches('/') {
+            "marshall://bookmarks/import" => {
+                Self::import_bookmarks(state);
+                return;
+            }
+            "marshall://bookmarks/export" => {
+                Self::export_bookmarks(state);
+                return;
+            }
+            _ => {}
+        }
+
         // Add to internal history
-        history.borrow_mut().push(uri);
-        
-        // Update toolbar back/forward buttons
-        let h = history.borrow();
-        toolbar.set_can_go_back(h.can_go_back());
-        toolbar.set_can_go_forward(h.can_go_forward());
-        drop(h);
-        
+        tab.history.borrow_mut().push(uri);
+        Self::history_changed(state, tab);
+
         // Load the appropriate page
-        Self::load_internal_page(webview, uri);
+        Self::load_internal_page(&state.config, &tab.webview, uri);
     }
-    
+
+    /// Whether an action URL came from one of Marshall's own pages. Web pages
+    /// can navigate to `marshall://` URLs too; what they send is logged and
+    /// dropped.
+    fn issued_internally(uri: &str) -> bool {
+        if homepage::has_action_token(uri) {
+            return true;
+        }
+        warn!("Ignoring {} from outside Marshall's pages", uri.split('?').next().unwrap_or(uri));
+        false
+    }
+
+    fn import_bookmarks(state: &WindowState) {
+        let path = match dialogs::choose_bookmark_import(&state.window) {
+            Some(path) => path,
+            None => return,
+        };
+        let result = BookmarkManager::global().write().import_file(&path);
+        match result {
+            Ok(summary) => {
+                BookmarksBar::refresh_all();
+                info!("Imported {} bookmarks from {}", summary.bookmarks, path.display());
+                let mut text = format!("Imported {} bookmarks in {} folders.", summary.bookmarks, summary.folders);
+                if summary.duplicates > 0 {
+                    text.push_str(&format!(" Skipped {} already bookmarked.", summary.duplicates));
+                }
+                dialogs::show_message(&state.window, MessageType::Info, &text);
+            }
+            Err(e) => {
+                warn!("Bookmark import failed: {}", e);
+                dialogs::show_message(&state.window, MessageType::Error, &e);
+            }
+        }
+    }
+
+    fn export_bookmarks(state: &WindowState) {
+        let path = match dialogs::choose_bookmark_export(&state.window) {
+            Some(path) => path,
+            None => return,
+        };
+        let result = BookmarkManager::global().read().export_file(&path);
+        match result {
+            Ok(()) => state.status_bar.set_status(&format!("Bookmarks exported to {}", path.display())),
+            Err(e) => {
+                warn!("Bookmark export failed: {}", e);
+                dialogs::show_message(&state.window, MessageType::Error, &e);
+            }
+        }
+    }
+
+    /// Bookmarks bar clicks load in the current tab; middle clicks open a
+    /// background tab in the current tab's container
+    fn connect_bookmarks_bar(state: &Rc<WindowState>) {
+        let bar = match &state.bookmarks_bar {
+            Some(bar) => bar,
+            None => return,
+        };
+        let st = state.clone();
+        bar.connect_open(move |url, new_tab| {
+            let tab = match st.active_tab() {
+                Some(tab) => tab,
+                None => return,
+            };
+            if new_tab {
+                let opened = Self::open_tab(&st, None, None, true, tab.container.as_ref());
+                opened.transition.set(Transition::Bookmark);
+                Self::navigate(&st, &opened, url);
+            } else {
+                tab.transition.set(Transition::Bookmark);
+                Self::navigate(&st, &tab, url);
+            }
+        });
+    }
+
+    /// Bookmark the current page on the bookmarks bar
+    fn bookmark_page(state: &WindowState) {
+        let tab = match state.active_tab() {
+            Some(tab) => tab,
+            None => return,
+        };
+        let url = tab.display_url();
+        if url.is_empty() || url.starts_with("marshall://") {
+            return;
+        }
+        let title = tab.webview.title().map(|t| t.to_string()).unwrap_or_default();
+
+        let mut manager = BookmarkManager::global().write();
+        if manager.find_by_url(&url).is_some() {
+            state.status_bar.set_status("Already bookmarked");
+            return;
+        }
+        manager.add_bookmark(if title.is_empty() { &url } else { &title }, &url, Some(TOOLBAR_FOLDER));
+        drop(manager);
+        BookmarksBar::refresh_all();
+        state.status_bar.set_status(&format!("Bookmarked {}", if title.is_empty() { &url } else { &title }));
+    }
+
+    /// Load a history entry without adding it to history again
+    fn load_history_entry(state: &WindowState, tab: &BrowserTab, uri: &str) {
+        if uri.starts_with("marshall://") {
+            Self::load_internal_page(&state.config, &tab.webview, uri);
+        } else {
+            tab.traversing.set(true);
+            Self::prepare_load(&state.config, &tab.webview, tab.container.as_ref(), uri);
+            tab.webview.load_uri(uri);
+        }
+    }
+
+    /// Update back/forward buttons and the history the session saves
+    fn history_changed(state: &WindowState, tab: &BrowserTab) {
+        let history = tab.history.borrow();
+        state.tab_manager.update_tab_history(&tab.id, &history.entries, history.index());
+        drop(history);
+
+        if state.is_active(&tab.id) {
+            state.toolbar.set_can_go_back(tab.can_go_back());
+            state.toolbar.set_can_go_forward(tab.can_go_forward());
+        }
+    }
+
+    /// Suggestions for what is typed in the URL bar. Local sources answer
+    /// at once; search engine suggestions follow when enabled.
+    fn suggest(state: &Rc<WindowState>, query: &str) {
+        Self::show_suggestions(state, query, &[], true);
+
+        // What is typed in a private window stays on this machine
+        let remote = state.config.general.search_suggestions
+            && !state.private
+            && !query.contains("://")
+            && !query.starts_with("marshall:");
+        if !remote {
+            return;
+        }
+
+        // Wait for a pause in typing before asking
+        let st = state.clone();
+        let query = query.to_string();
+        glib::timeout_add_local_once(SEARCH_SUGGESTION_DELAY, move || {
+            if st.toolbar.typed_url() != query {
+                return;
+            }
+            let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+            let fetch_query = query.clone();
+            std::thread::spawn(move || {
+                let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                    Ok(runtime) => runtime,
+                    Err(e) => {
+                        warn!("Search suggestions unavailable: {}", e);
+                        return;
+                    }
+                };
+                match runtime.block_on(engine::fetch_search_suggestions(&fetch_query)) {
+                    Ok(phrases) => {
+                        let _ = sender.send(phrases);
+                    }
+                    Err(e) => warn!("{}", e),
+                }
+            });
+
+            let st = st.clone();
+            receiver.attach(None, move |phrases: Vec<String>| {
+                // show_suggestions drops the answer if the text moved on
+                Self::show_suggestions(&st, &query, &phrases, false);
+                glib::Continue(false)
+            });
+        });
+    }
+
+    /// Merge the local sources with `search` phrases into the URL bar popup
+    fn show_suggestions(state: &WindowState, query: &str, search: &[String], complete: bool) {
+        if state.toolbar.typed_url() != query {
+            return;
+        }
+        let history = HistoryManager::global().search(query, MAX_HISTORY_SUGGESTIONS);
+        let bookmarks: Vec<Bookmark> = BookmarkManager::global()
+            .read()
+            .search_bookmarks(query)
+            .into_iter()
+            .take(MAX_BOOKMARK_SUGGESTIONS)
+            .cloned()
+            .collect();
+        let open_tabs: Vec<OpenTab> = state
+            .tabs
+            .borrow()
+            .iter()
+            .filter(|tab| !state.is_active(&tab.id))
+            .map(|tab| OpenTab {
+                id: tab.id.clone(),
+                title: tab.webview.title().map(|t| t.to_string()).filter(|t| !t.is_empty()).unwrap_or_else(|| tab.display_url()),
+                url: tab.display_url(),
+            })
+            .collect();
+
+        let sources = SuggestionSources {
+            open_tabs: &open_tabs,
+            bookmarks: &bookmarks,
+            history: &history,
+            search,
+        };
+        let suggestions = engine::suggest(query, &sources, MAX_SUGGESTIONS);
+        let completion = if complete { engine::inline_completion(query, &history) } else { None };
+        state.toolbar.show_url_suggestions(query, suggestions, completion.as_deref());
+    }
+
+    /// Load what the user typed: a marshall:// page, a URL or a search
+    fn navigate(state: &Rc<WindowState>, tab: &BrowserTab, url: &str) {
+        // Check for marshall:// internal URLs
+        if url.starts_with("marshall://") || url == "marshall:home" || url.is_empty() {
+            if url.is_empty() || url == "marshall:home" {
+                Self::handle_internal_url(state, tab, "marshall://home");
+            } else {
+                Self::handle_internal_url(state, tab, url);
+            }
+            return;
+        }
+
+        let url = if url.starts_with("http://") || url.starts_with("https://") {
+            url.to_string()
+        } else if url.contains('.') && !url.contains(' ') {
+            format!("https://{}", url)
+        } else {
+            // Search using DuckDuckGo with dark mode params
+            format!("https://duckduckgo.com/?q={}&kae=d&k1=-1&kaj=m&kam=osm&kp=-2", urlencoding::encode(url))
+        };
+        Self::prepare_load(&state.config, &tab.webview, tab.container.as_ref(), &url);
+        tab.webview.load_uri(&url);
+    }
+
     /// Load internal page content without history tracking (for back/forward navigation)
-    fn load_internal_page(webview: &WebView, uri: &str) {
+    fn load_internal_page(config: &Config, webview: &WebView, uri: &str) {
         // Use None as base URI to avoid triggering decide_policy
         match uri {
             "marshall://home" | "marshall://home/" => {
@@ -195,11 +1062,54 @@ impl BrowserWindow {
                 webview.load_html(&html, None);
             }
             "marshall://settings" | "marshall://settings/" => {
-                let html = homepage::generate_settings_page();
+                let html = homepage::generate_settings_page(&leaktest::network_checks(&network::Network::current()));
                 webview.load_html(&html, None);
             }
             "marshall://privacy" | "marshall://privacy/" => {
-                let html = homepage::generate_privacy_page();
+                let lists = FilterListManager::open(&config.adblock)
+                    .map(|manager| manager.lists().to_vec())
+                    .unwrap_or_default();
+                let html = homepage::generate_privacy_page(&lists, &SiteProfiles::global().all());
+                webview.load_html(&html, None);
+            }
+            "marshall://downloads" | "marshall://downloads/" => {
+                let private = webview.context().is_some_and(|c| c.is_ephemeral());
+                let manager = DownloadManager::global().read();
+                let html = homepage::generate_downloads_page(&manager.all(private), private);
+                webview.load_html(&html, None);
+            }
+            "marshall://containers" | "marshall://containers/" => {
+                let html = homepage::generate_containers_page(&Containers::global().all());
+                webview.load_html(&html, None);
+            }
+            _ if uri.starts_with("marshall://leaktest") => {
+                let network = network::Network::current();
+                let user_agent = Self::user_agent(webview).unwrap_or_default();
+                let mut promises = LeakPromises::new(&config.privacy, &config.security, &network.config, &user_agent);
+                promises.real_screen = webview
+                    .window()
+                    .and_then(|window| webview.display().monitor_at_window(&window))
+                    .map(|monitor| {
+                        let geometry = monitor.geometry();
+                        (geometry.width() as u32, geometry.height() as u32)
+                    });
+                // "Run again" passes the last canvas hash along
+                promises.previous_canvas = uri
+                    .split_once("canvas=")
+                    .map(|(_, hash)| hash.to_string())
+                    .filter(|hash| !hash.is_empty() && hash.len() <= 16 && hash.chars().all(|c| c.is_ascii_hexdigit()));
+                let html = homepage::generate_leaktest_page(&promises, &leaktest::network_checks(&network));
+                webview.load_html(&html, None);
+            }
+            _ if uri.starts_with("marshall://blocked") => {
+                let query = uri.split_once('?').map(|(_, q)| q).unwrap_or("");
+                let url = url::form_urlencoded::parse(query.as_bytes())
+                    .find(|(key, _)| key == "url")
+                    .map(|(_, url)| url.into_owned());
+                let html = match url {
+                    Some(url) => homepage::generate_blocked_page(&ThreatIntel::global().read().analyze_url(&url)),
+                    None => homepage::generate_homepage(),
+                };
                 webview.load_html(&html, None);
             }
             "marshall://assistant" | "marshall://assistant/" => {
@@ -215,9 +1125,38 @@ impl BrowserWindow {
                 webview.load_html(&html, None);
             }
             "marshall://osint" | "marshall://osint/" => {
-                let html = homepage::generate_osint_page();
+                let html = homepage::generate_osint_page(&config.security.monitored_domains);
                 webview.load_html(&html, None);
             }
+            _ if uri.starts_with("marshall://osint/typosquat") => {
+                let query = uri.split_once('?').map(|(_, q)| q).unwrap_or("");
+                let target = url::form_urlencoded::parse(query.as_bytes())
+                    .find(|(key, _)| key == "domain")
+                    .map(|(_, domain)| domain.trim().to_string())
+                    .filter(|domain| !domain.is_empty());
+                let permutations = target.as_deref().map(typosquat::permutations);
+                match (target, permutations) {
+                    // Lookalikes are resolved the way the network settings
+                    // say, through the proxy when there is one
+                    (Some(target), Some(Ok(permutations))) => match network::Network::current().upstream() {
+                        Ok(_) => {
+                            let html = homepage::generate_typosquat_page(&target, &permutations, None);
+                            webview.load_html(&html, None);
+                            Self::check_lookalikes(webview, &target, permutations);
+                        }
+                        Err(e) => {
+                            let notice = format!("Lookalikes can't be checked until the network settings are fixed: {}", e);
+                            let html = homepage::generate_typosquat_page(&target, &permutations, Some(&notice));
+                            webview.load_html(&html, None);
+                        }
+                    },
+                    (Some(target), Some(Err(e))) => {
+                        let html = homepage::generate_typosquat_page(&target, &[], Some(&e));
+                        webview.load_html(&html, None);
+                    }
+                    _ => Self::load_internal_page(config, webview, "marshall://osint"),
+                }
+            }
             _ if uri.starts_with("marshall://osint/") => {
                 let domain = uri.strip_prefix("marshall://osint/").unwrap_or("").trim_end_matches('/');
                 if !domain.is_empty() {
@@ -225,8 +1164,7 @@ impl BrowserWindow {
                     let html = homepage::generate_osint_results(&decoded);
                     webview.load_html(&html, None);
                 } else {
-                    let html = homepage::generate_osint_page();
-                    webview.load_html(&html, None);
+                    Self::load_internal_page(config, webview, "marshall://osint");
                 }
             }
             _ if uri.starts_with("marshall://link/") => {
@@ -240,6 +1178,39 @@ impl BrowserWindow {
         }
     }
 
+    /// Resolve and WHOIS the lookalikes on a typosquat report in the
+    /// background, filling each into the page as it is checked. Answers for
+    /// a report that was navigated away from are dropped by the page check.
+    fn check_lookalikes(webview: &WebView, domain: &str, permutations: Vec<Permutation>) {
+        let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+        std::thread::spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    warn!("Lookalike checks unavailable: {}", e);
+                    return;
+                }
+            };
+            let network = network::Network::current();
+            runtime.block_on(typosquat::check_all(permutations, &network, |lookalike| {
+                let _ = sender.send(Some(lookalike));
+            }));
+            let _ = sender.send(None);
+        });
+
+        let webview = webview.clone();
+        let report = serde_json::to_string(domain).unwrap_or_default();
+        receiver.attach(None, move |lookalike: Option<Lookalike>| {
+            let call = match &lookalike {
+                Some(lookalike) => format!("lookalikeChecked({})", serde_json::to_string(lookalike).unwrap_or_default()),
+                None => "lookalikesDone()".to_string(),
+            };
+            let script = format!("if (window.typosquatDomain === {}) {};", report, call);
+            webview.run_javascript(&script, None::<&gio::Cancellable>, |_result| {});
+            glib::Continue(lookalike.is_some())
+        });
+    }
+
     /// Inject Marshall userscript into the page
     fn inject_marshall_script(webview: &WebView) {
         let userscript = homepage::generate_userscript();
@@ -279,32 +1250,252 @@ impl BrowserWindow {
             cookie_manager.set_accept_policy(policy);
         }
 
+        // Favicons for the tab labels
+        context.set_favicon_database_directory(None);
+        WebViewManager::apply_network_proxy(&context, &network::isolation_key());
+
         context
     }
 
-    fn create_webview(context: &WebContext, config: &Config) -> WebView {
+    /// The ephemeral context for a new private window. Private windows share
+    /// one context, like a private session in other browsers.
+    fn acquire_ephemeral_context(config: &Config) -> WebContext {
+        PRIVATE_CONTEXT.with(|slot| {
+            let mut slot = slot.borrow_mut();
+            let (context, windows) = slot.get_or_insert_with(|| {
+                info!("Starting private browsing session");
+                (WebViewManager::from_config(config).create_ephemeral_context(), 0)
+            });
+            *windows += 1;
+            context.clone()
+        })
+    }
+
+    /// Called when a private window closes; the last one wipes the session
+    fn release_ephemeral_context() {
+        PRIVATE_CONTEXT.with(|slot| {
+            let mut slot = slot.borrow_mut();
+            if let Some((context, windows)) = slot.as_mut() {
+                *windows -= 1;
+                if *windows == 0 {
+                    WebViewManager::clear_website_data(context);
+                    downloads::end_private_session();
+                    *slot = None;
+                    info!("Private browsing session wiped");
+                }
+            }
+        });
+    }
+
+    /// The context of a container, created on first use. Falls back to the
+    /// window's own context if the container's data directory is unusable.
+    fn container_context(state: &WindowState, container: &Container) -> WebContext {
+        CONTAINER_CONTEXTS.with(|contexts| {
+            let mut contexts = contexts.borrow_mut();
+            if let Some(context) = contexts.get(&container.id) {
+                return context.clone();
+            }
+            match WebViewManager::from_config(&state.config).create_container_context(container) {
+                Ok(context) => {
+                    let icons = container.data_dir().ok().map(|dir| dir.join("icondatabase"));
+                    context.set_favicon_database_directory(icons.as_deref().and_then(|dir| dir.to_str()));
+                    downloads::watch(&context, false, download_dir(&state.config.general));
+                    contexts.insert(container.id.clone(), context.clone());
+                    context
+                }
+                Err(e) => {
+                    warn!("Container {} unavailable, using the default context: {}", container.name, e);
+                    state.context.clone()
+                }
+            }
+        })
+    }
+
+    /// The context of a tab outside containers. In tor mode every tab gets
+    /// an ephemeral one, so its streams share no circuit with other tabs.
+    /// Proxy settings belong to a context's data manager, and two managers
+    /// must never open the same directories, so its site data isn't kept.
+    fn tab_context(state: &WindowState) -> WebContext {
+        if cfg!(feature = "tor") && state.config.network.tor_enabled {
+            let context = WebViewManager::from_config(&state.config).create_ephemeral_context();
+            context.set_favicon_database_directory(None);
+            downloads::watch(&context, state.private, download_dir(&state.config.general));
+            return context;
+        }
+        state.context.clone()
+    }
+
+    /// Remove a container once nothing uses its data: its tabs are closed in
+    /// every window and its context forgotten, then its website data is
+    /// cleared and only after that are its directories deleted
+    fn remove_container(id: &str) {
+        for st in Self::open_windows() {
+            let tabs: Vec<String> = st.tabs.borrow().iter()
+                .filter(|tab| tab.container.as_ref().map(|c| c.id.as_str()) == Some(id))
+                .map(|tab| tab.id.clone())
+                .collect();
+            for tab in tabs {
+                Self::close_tab(&st, &tab);
+            }
+        }
+
+        let id = id.to_string();
+        let remove = move || {
+            if let Err(e) = Containers::global().remove(&id) {
+                warn!("Container not removed: {}", e);
+            }
+        };
+        match CONTAINER_CONTEXTS.with(|contexts| contexts.borrow_mut().remove(&id)) {
+            Some(context) => WebViewManager::clear_website_data_then(&context, remove),
+            None => remove(),
+        }
+    }
+
+    /// Apply edited proxy settings to the containers already in use
+    fn refresh_container_proxies() {
+        CONTAINER_CONTEXTS.with(|contexts| {
+            for (id, context) in contexts.borrow().iter() {
+                if let Some(container) = Containers::global().get(id) {
+                    WebViewManager::apply_container_proxy(context, &container);
+                }
+            }
+        });
+    }
+
+    /// Compile the blocking rules into a WebKit content filter. The compiled
+    /// list is cached on disk by WebKit, keyed by identifier.
+    fn compile_content_filter(state: &Rc<WindowState>) {
+        let store_dir = match Config::cache_dir() {
+            Ok(dir) => dir.join("content-filters"),
+            Err(e) => {
+                warn!("No cache directory for content filters: {}", e);
+                return;
+            }
+        };
+
+        let st = state.clone();
+        let rules = state.shields.content_rules_json();
+        ContentFilter::compile(&store_dir, "marshall-shields", &rules, move |result| {
+            match result {
+                Ok(filter) => {
+                    info!("Content filter compiled");
+                    *st.content_filter.borrow_mut() = Some(filter);
+                    for tab in st.tabs.borrow().iter() {
+                        Self::apply_shields(&tab.webview, &st.shields, &st.content_filter);
+                    }
+                }
+                Err(e) => warn!("Failed to compile content filter: {}", e),
+            }
+        });
+    }
+
+    /// Refresh remote filter lists in the background. One scheduler serves
+    /// every window, so only one thread writes the cache; the ad blocker is
+    /// rebuilt off the main thread and swapped into each open window when a
+    /// list changes.
+    fn schedule_filter_list_updates(state: &Rc<WindowState>) {
+        if FILTER_LIST_SCHEDULER.with(|started| started.replace(true)) {
+            return;
+        }
+
+        let manager = match FilterListManager::open(&state.config.adblock) {
+            Ok(manager) => manager,
+            Err(e) => {
+                warn!("Filter list updates disabled: {}", e);
+                return;
+            }
+        };
+
+        let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+        let adblock_config = state.config.adblock.clone();
+        let categories = state.config.privacy.block_categories.clone();
+        manager.spawn_scheduler(FILTER_LIST_CHECK_INTERVAL, move || {
+            let _ = sender.send(Arc::new(AdBlocker::from_config(&adblock_config, &categories)));
+        });
+
+        receiver.attach(None, move |adblocker: Arc<AdBlocker>| {
+            for st in Self::open_windows() {
+                st.shields.set_adblocker(adblocker.clone());
+                Self::compile_content_filter(&st);
+            }
+            glib::Continue(true)
+        });
+    }
+
+    /// The windows still open
+    fn open_windows() -> Vec<Rc<WindowState>> {
+        OPEN_WINDOWS.with(|windows| {
+            let mut windows = windows.borrow_mut();
+            windows.retain(|window| window.strong_count() > 0);
+            windows.iter().filter_map(Weak::upgrade).collect()
+        })
+    }
+
+    /// Apply or lift blocking for the page being loaded, depending on whether
+    /// its host is whitelisted.
+    fn apply_shields(webview: &WebView, shields: &Shields, content_filter: &RefCell<Option<ContentFilter>>) {
+        let content_manager = match webview.user_content_manager() {
+            Some(manager) => manager,
+            None => return,
+        };
+        let host = webview.uri().map(|uri| host_of(&uri)).unwrap_or_default();
+
+        if let Some(filter) = content_filter.borrow().as_ref() {
+            if shields.is_enabled_for(&host) {
+                filter.attach(&content_manager);
+            } else {
+                filter.detach(&content_manager);
+            }
+        }
+
+        Self::apply_cosmetic_filters(webview, shields, &host);
+    }
+
+    /// Replace the element-hiding stylesheet with the one for the page being loaded.
+    /// User style sheets are attached when the document is created, so this must run
+    /// before the load commits.
+    fn apply_cosmetic_filters(webview: &WebView, shields: &Shields, host: &str) {
+        let content_manager = match webview.user_content_manager() {
+            Some(manager) => manager,
+            None => return,
+        };
+        content_manager.remove_all_style_sheets();
+
+        if let Some(css) = shields.cosmetic_stylesheet(host) {
+            let allow_list = [format!("*://{}/*", host)];
+            let allow_list: Vec<&str> = allow_list.iter().map(String::as_str).collect();
+            let stylesheet = UserStyleSheet::new(
+                &css,
+                UserContentInjectedFrames::AllFrames,
+                UserStyleLevel::User,
+                &allow_list,
+                &[],
+            );
+            content_manager.add_style_sheet(&stylesheet);
+        }
+    }
+
+
+    /// Create a view for a new tab. Views opened by a page share its web
+    /// process so `window.opener` keeps working.
+    fn create_webview(context: &WebContext, config: &Config, related: Option<&WebView>, container: Option<&Container>) -> WebView {
         use webkit2gtk::SettingsExt;
         
-        let webview = WebView::with_context(context);
+        // Each view gets its own content manager for per-site style sheets
+        let content_manager = UserContentManager::new();
+        let mut builder = WebView::builder()
+            .web_context(context)
+            .user_content_manager(&content_manager);
+        if let Some(related) = related {
+            builder = builder.related_view(related);
+        }
+        let webview = builder.build();
         
         // Get settings
         let settings: webkit2gtk::Settings = WebViewExt::settings(&webview).unwrap();
         
-        // JavaScript - MUST be enabled for userscript injection
-        settings.set_enable_javascript(true);
-        
-        // Privacy settings - disable features that can fingerprint
-        if config.privacy.block_fingerprinting {
-            settings.set_enable_webgl(false);
-            settings.set_enable_webaudio(false);
-        } else {
-            settings.set_enable_webgl(config.general.enable_webgl);
-        }
-        
-        // User agent - use Marshall branded user agent
-        settings.set_user_agent(Some(
-            "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Marshall/2.0 Chrome/120.0.0.0 Safari/537.36"
-        ));
+        // Hardened settings and the persona's user agent
+        Self::apply_site_settings(&webview, config, None, container);
         
         // Developer features
         #[cfg(feature = "developer")]
@@ -315,10 +1506,11 @@ impl BrowserWindow {
         // Appearance
         settings.set_default_font_family(&config.appearance.font_family);
         settings.set_default_font_size(config.appearance.font_size);
-        
-        // Security
-        settings.set_enable_dns_prefetching(false);
-        settings.set_enable_page_cache(false);
+
+        // WebRTC reaches peers over UDP, which Tor doesn't carry
+        if network::tor_mode() && settings.find_property("enable-webrtc").is_some() {
+            settings.set_property("enable-webrtc", false);
+        }
 
         // Set zoom level
         webview.set_zoom_level(config.appearance.zoom_level);
@@ -326,105 +1518,403 @@ impl BrowserWindow {
         webview
     }
 
-    fn connect_toolbar_signals(toolbar: &Toolbar, webview: &WebView, _config: &Config, history: Rc<RefCell<InternalHistory>>) {
+    /// Configure the view for the page about to load: global settings first,
+    /// then the tab's container, then the overrides from the site's profile
+    fn apply_site_settings(webview: &WebView, config: &Config, profile: Option<&SiteProfile>, container: Option<&Container>) {
+        use webkit2gtk::{UserScript, UserScriptInjectionTime};
+
+        let privacy = Self::site_privacy(config, profile);
+
+        // User agent and languages follow the session's or container's
+        // persona, so they agree with what its scripts report and stay the
+        // same whether or not fingerprint protection is on for the site
+        let persona = Persona::for_container(&privacy, container);
+        WebViewManager::from_config(config).harden_settings(webview, &persona, profile);
+
+        // WebKit has no referrer policy setting; a <meta name="referrer"> added
+        // at document start applies it to everything the page requests
+        if let Some(content_manager) = webview.user_content_manager() {
+            content_manager.remove_all_scripts();
+            let policy = privacy.referrer_policy.as_str();
+            if crate::privacy::REFERRER_POLICIES.contains(&policy) {
+                let source = format!(
+                    "(function(){{var m=document.createElement('meta');m.name='referrer';m.content='{}';\
+                     (document.head||document.documentElement).appendChild(m);}})();",
+                    policy
+                );
+                let script = UserScript::new(
+                    &source,
+                    UserContentInjectedFrames::TopFrame,
+                    UserScriptInjectionTime::Start,
+                    &[],
+                    &[],
+                );
+                content_manager.add_script(&script);
+            }
+
+            // Hardening runs in every frame before the page's own scripts,
+            // except on origins whose profile turns fingerprint protection off
+            let security = config.security.in_force(&privacy, &config.network);
+            let source = SecurityManager::new(security).page_script(&persona);
+            if !source.is_empty() {
+                let exempt = SiteProfiles::global().fingerprint_exempt_patterns();
+                let exempt: Vec<&str> = exempt.iter().map(String::as_str).collect();
+                let script = UserScript::new(
+                    &source,
+                    UserContentInjectedFrames::AllFrames,
+                    UserScriptInjectionTime::Start,
+                    &[],
+                    &exempt,
+                );
+                content_manager.add_script(&script);
+            }
+        }
+    }
+
+    /// Configure a tab's view for the site at `uri` before the request for
+    /// it is sent, so the site's profile, user agent included, applies to
+    /// the page itself and not just what it loads
+    fn prepare_load(config: &Config, webview: &WebView, container: Option<&Container>, uri: &str) {
+        let profile = SiteProfiles::global().for_url(uri);
+        Self::apply_site_settings(webview, config, profile.as_ref(), container);
+    }
+
+    /// `config.privacy` with the overrides of a site's profile
+    fn site_privacy(config: &Config, profile: Option<&SiteProfile>) -> PrivacyConfig {
+        let mut privacy = config.privacy.clone();
+        if let Some(profile) = profile {
+            if let Some(protect) = profile.fingerprint_protection {
+                privacy.block_fingerprinting = protect;
+            }
+            if let Some(cookies) = profile.cookies {
+                privacy.block_third_party_cookies = cookies != CookieSetting::All;
+            }
+            if let Some(policy) = &profile.referrer_policy {
+                privacy.referrer_policy = policy.clone();
+            }
+        }
+        privacy
+    }
+
+    /// Set the cookie policy for the site of the active tab. WebKit keeps the
+    /// policy on the context, which every tab using it shares, and has no
+    /// per-site hook; so the policy is window-wide, follows whichever tab is
+    /// active, and background tabs live under it too.
+    fn apply_cookie_policy(webview: &WebView, config: &Config) {
+        use webkit2gtk::{CookieAcceptPolicy, CookieManagerExt};
+
+        let uri = webview.uri().map(|u| u.to_string()).unwrap_or_default();
+        let profile = SiteProfiles::global().for_url(&uri);
+        let privacy = Self::site_privacy(config, profile.as_ref());
+        if let Some(cookie_manager) = webview.context().and_then(|c| c.cookie_manager()) {
+            let policy = match profile.and_then(|p| p.cookies) {
+                Some(CookieSetting::None) => CookieAcceptPolicy::Never,
+                _ if privacy.block_third_party_cookies => CookieAcceptPolicy::NoThirdParty,
+                _ => CookieAcceptPolicy::Always,
+            };
+            cookie_manager.set_accept_policy(policy);
+        }
+    }
+
+    fn user_agent(webview: &WebView) -> Option<String> {
+        use webkit2gtk::SettingsExt;
+        WebViewExt::settings(webview).and_then(|s| s.user_agent()).map(|ua| ua.to_string())
+    }
+
+
+    /// Site icon as a pixbuf for the tab label
+    fn favicon_pixbuf(surface: &cairo::Surface) -> Option<Pixbuf> {
+        let image = cairo::ImageSurface::try_from(surface.clone()).ok()?;
+        gdk::pixbuf_get_from_surface(&image, 0, 0, image.width(), image.height())
+    }
+
+    /// Toolbar buttons act on whichever tab is active when they are pressed
+    fn connect_toolbar_signals(state: &Rc<WindowState>) {
+        let toolbar = &state.toolbar;
+
         // Back button - handle both internal and external navigation
-        let wv = webview.clone();
-        let history_back = history.clone();
-        let toolbar_back = toolbar.clone();
+        let st = state.clone();
         toolbar.connect_back(move || {
-            // First try internal history (for marshall:// pages)
-            let uri = history_back.borrow_mut().go_back();
-            if let Some(uri) = uri {
-                // Update buttons
-                let h = history_back.borrow();
-                toolbar_back.set_can_go_back(h.can_go_back());
-                toolbar_back.set_can_go_forward(h.can_go_forward());
-                drop(h);
-                // Load page without adding to history
-                Self::load_internal_page(&wv, &uri);
-            } else {
-                // Try WebKit's history (for external pages)
-                wv.go_back();
+            if let Some(tab) = st.active_tab() {
+                // First try our own history
+                let uri = tab.history.borrow_mut().go_back();
+                if let Some(uri) = uri {
+                    Self::history_changed(&st, &tab);
+                    Self::load_history_entry(&st, &tab, &uri);
+                } else {
+                    // Try WebKit's history (for external pages)
+                    tab.webview.go_back();
+                }
             }
         });
 
         // Forward button - handle both internal and external navigation
-        let wv = webview.clone();
-        let history_fwd = history.clone();
-        let toolbar_fwd = toolbar.clone();
+        let st = state.clone();
         toolbar.connect_forward(move || {
-            // First try internal history
-            let uri = history_fwd.borrow_mut().go_forward();
-            if let Some(uri) = uri {
-                // Update buttons
-                let h = history_fwd.borrow();
-                toolbar_fwd.set_can_go_back(h.can_go_back());
-                toolbar_fwd.set_can_go_forward(h.can_go_forward());
-                drop(h);
-                // Load page without adding to history
-                Self::load_internal_page(&wv, &uri);
-            } else {
-                // Try WebKit's history
-                wv.go_forward();
+            if let Some(tab) = st.active_tab() {
+                // First try our own history
+                let uri = tab.history.borrow_mut().go_forward();
+                if let Some(uri) = uri {
+                    Self::history_changed(&st, &tab);
+                    Self::load_history_entry(&st, &tab, &uri);
+                } else {
+                    // Try WebKit's history
+                    tab.webview.go_forward();
+                }
             }
         });
 
         // Reload button
-        let wv = webview.clone();
+        let st = state.clone();
         toolbar.connect_reload(move || {
-            wv.reload();
+            if let Some(tab) = st.active_tab() {
+                tab.webview.reload();
+            }
         });
 
         // Home button - load Marshall homepage with history
-        let wv = webview.clone();
-        let history_home = history.clone();
-        let toolbar_home = toolbar.clone();
+        let st = state.clone();
         toolbar.connect_home(move || {
-            Self::handle_internal_url(&wv, "marshall://home", &history_home, &toolbar_home);
+            if let Some(tab) = st.active_tab() {
+                Self::handle_internal_url(&st, &tab, "marshall://home");
+            }
         });
 
         // Menu button - show menu page with history
-        let wv_menu = webview.clone();
-        let history_menu = history.clone();
-        let toolbar_menu = toolbar.clone();
+        let st = state.clone();
         toolbar.connect_menu(move || {
-            Self::handle_internal_url(&wv_menu, "marshall://menu", &history_menu, &toolbar_menu);
+            if let Some(tab) = st.active_tab() {
+                Self::handle_internal_url(&st, &tab, "marshall://menu");
+            }
+        });
+
+        // URL bar - suggestions while typing
+        let st = state.clone();
+        toolbar.connect_url_suggest(move |query| Self::suggest(&st, query));
+
+        let st = state.clone();
+        toolbar.connect_switch_to_tab(move |id| {
+            if let Some(tab) = st.tab(id) {
+                st.tab_bar.select(&tab.webview);
+            }
         });
 
         // URL bar - navigate on enter
-        let wv = webview.clone();
-        let history_nav = history.clone();
-        let toolbar_nav = toolbar.clone();
+        let st = state.clone();
         toolbar.connect_navigate(move |url| {
-            // Check for marshall:// internal URLs
-            if url.starts_with("marshall://") || url == "marshall:home" || url.is_empty() {
-                if url.is_empty() || url == "marshall:home" {
-                    Self::handle_internal_url(&wv, "marshall://home", &history_nav, &toolbar_nav);
-                } else {
-                    Self::handle_internal_url(&wv, url, &history_nav, &toolbar_nav);
+            match st.active_tab() {
+                Some(tab) => {
+                    tab.transition.set(Transition::Typed);
+                    Self::navigate(&st, &tab, url);
+                }
+                None => {
+                    let tab = Self::open_tab(&st, Some(url), None, false, None);
+                    tab.transition.set(Transition::Typed);
                 }
-                return;
             }
-            
-            let url = if url.starts_with("http://") || url.starts_with("https://") {
-                url.to_string()
-            } else if url.contains('.') && !url.contains(' ') {
-                format!("https://{}", url)
-            } else {
-                // Search using DuckDuckGo with dark mode params
-                format!("https://duckduckgo.com/?q={}&kae=d&k1=-1&kaj=m&kam=osm&kp=-2", urlencoding::encode(url))
+        });
+    }
+
+    fn connect_shield_signals(state: &Rc<WindowState>) {
+        // Shield button toggles protection for the current site
+        let st = state.clone();
+        state.toolbar.connect_shield(move || {
+            let tab = match st.active_tab() {
+                Some(tab) => tab,
+                None => return,
             };
-            wv.load_uri(&url);
+            let host = tab.webview.uri().map(|uri| host_of(&uri)).unwrap_or_default();
+            if host.is_empty() {
+                return;
+            }
+
+            let enabled = st.shields.toggle_host(&host);
+            st.toolbar.set_shield_active(enabled);
+            st.status_bar.set_status(&format!(
+                "Protection {} for {}",
+                if enabled { "enabled" } else { "disabled" },
+                host
+            ));
+
+            // Persist the whitelist so the choice survives restarts
+            match Config::load() {
+                Ok(mut config) => {
+                    config.adblock.whitelist = st.shields.whitelist();
+                    if let Err(e) = config.save() {
+                        warn!("Failed to save whitelist: {}", e);
+                    }
+                }
+                Err(e) => warn!("Failed to load config: {}", e),
+            }
+
+            Self::apply_shields(&tab.webview, &st.shields, &st.content_filter);
+            tab.webview.reload();
+        });
+    }
+
+    fn connect_tab_signals(state: &Rc<WindowState>) {
+        // New tab button
+        let st = state.clone();
+        state.tab_bar.connect_new_tab(move || {
+            Self::open_tab(&st, Some("marshall://home"), None, false, None);
+        });
+
+        // Toolbar and status bar follow the active tab
+        let st = state.clone();
+        state.tab_bar.connect_switch(move |page| {
+            if let Some(tab) = st.tab_for_page(page) {
+                st.tab_manager.set_active_tab(&tab.id);
+                Self::apply_cookie_policy(&tab.webview, &st.config);
+                Self::sync_toolbar(&st, &tab);
+            }
+        });
+
+        // Keep the tab order in step when tabs are dragged
+        let st = state.clone();
+        state.tab_bar.connect_reordered(move |page, index| {
+            if let Some(tab) = st.tab_for_page(page) {
+                st.tab_manager.move_tab(&tab.id, index as usize);
+            }
+        });
+    }
+
+    /// Right-click menu of a tab label: move the tab into another container
+    fn show_tab_menu(state: &Rc<WindowState>, id: &str, event: &gdk::EventButton) {
+        let tab = match state.tab(id) {
+            Some(tab) => tab,
+            None => return,
+        };
+        let menu = gtk::Menu::new();
+
+        if !state.private {
+            let current = tab.container.as_ref().map(|c| c.id.clone());
+            let choices = std::iter::once(None).chain(Containers::global().all().into_iter().map(Some));
+            for container in choices {
+                let label = match &container {
+                    Some(c) => format!("Open in {}", c.name),
+                    None => "Open in No Container".to_string(),
+                };
+                let item = gtk::MenuItem::with_label(&label);
+                item.set_sensitive(container.as_ref().map(|c| &c.id) != current.as_ref());
+                let st = state.clone();
+                let id = tab.id.clone();
+                item.connect_activate(move |_| Self::move_to_container(&st, &id, container.as_ref()));
+                menu.append(&item);
+            }
+            menu.append(&gtk::SeparatorMenuItem::new());
+
+            let manage = gtk::MenuItem::with_label("Manage Containers…");
+            let st = state.clone();
+            manage.connect_activate(move |_| {
+                Self::open_tab(&st, Some("marshall://containers"), None, false, None);
+            });
+            menu.append(&manage);
+        }
+
+        let close = gtk::MenuItem::with_label("Close Tab");
+        let st = state.clone();
+        let id = tab.id.clone();
+        close.connect_activate(move |_| Self::close_tab(&st, &id));
+        menu.append(&close);
+
+        menu.show_all();
+        menu.popup_at_pointer(Some(&**event));
+    }
+
+    /// Reopen a tab's page in `container` and close the original; a view
+    /// can't change context once created
+    fn move_to_container(state: &Rc<WindowState>, id: &str, container: Option<&Container>) {
+        let tab = match state.tab(id) {
+            Some(tab) => tab,
+            None => return,
+        };
+        state.tab_bar.select(&tab.webview);
+        let moved = Self::open_tab(state, Some(&tab.display_url()), None, true, container);
+        state.tab_bar.select(&moved.webview);
+        Self::close_tab(state, id);
+    }
+
+    /// Wire the tab shortcuts from the keybindings config
+    fn connect_keybindings(state: &Rc<WindowState>) {
+        let accel_group = AccelGroup::new();
+        state.window.add_accel_group(&accel_group);
+        let keys = &state.config.keybindings;
+
+        let st = state.clone();
+        Self::bind_accelerator(&accel_group, &keys.new_tab, move || {
+            Self::open_tab(&st, Some("marshall://home"), None, false, None);
+        });
+
+        let st = state.clone();
+        Self::bind_accelerator(&accel_group, &keys.close_tab, move || {
+            if let Some(tab) = st.active_tab() {
+                Self::close_tab(&st, &tab.id);
+            }
+        });
+
+        let st = state.clone();
+        Self::bind_accelerator(&accel_group, &keys.private_window, move || {
+            if let Some(app) = st.window.application() {
+                Self::new_private(&app, &st.config).present();
+            }
+        });
+
+        let st = state.clone();
+        Self::bind_accelerator(&accel_group, &keys.bookmark_page, move || Self::bookmark_page(&st));
+    }
+
+    fn bind_accelerator<F: Fn() + 'static>(accel_group: &AccelGroup, accelerator: &str, f: F) {
+        let (key, modifiers) = gtk::accelerator_parse(accelerator);
+        if key == 0 {
+            warn!("Ignoring invalid keybinding: {}", accelerator);
+            return;
+        }
+        accel_group.connect_accel_group(key, modifiers, AccelFlags::VISIBLE, move |_, _, _, _| {
+            f();
+            true
         });
     }
 
-    fn connect_webview_signals(webview: &WebView, toolbar: &Toolbar, status_bar: &StatusBar, history: Rc<RefCell<InternalHistory>>) {
-        // Intercept navigation to marshall:// URLs from link clicks/JS
-        let history_policy = history.clone();
-        let toolbar_policy = toolbar.clone();
+    /// Signals of one tab's view. Handlers look the tab up by id so a closed
+    /// tab's view can be freed; toolbar and status bar updates only apply
+    /// while the tab is active.
+    fn connect_webview_signals(state: &Rc<WindowState>, tab: &Rc<BrowserTab>) {
+        let webview = &tab.webview;
+
+        // Intercept navigation to marshall:// URLs from link clicks/JS, and
+        // open middle- or Ctrl-clicked links in a background tab
+        let st = state.clone();
+        let id = tab.id.clone();
         webview.connect_decide_policy(move |wv, decision, decision_type| {
             if decision_type == PolicyDecisionType::NavigationAction {
                 if let Ok(nav_decision) = decision.clone().downcast::<NavigationPolicyDecision>() {
+                    if let Some(action) = nav_decision.navigation_action() {
+                        // Remember how the page was reached; loads we start
+                        // ourselves report Other and keep what we set
+                        if let Some(tab) = st.tab(&id) {
+                            match action.navigation_type() {
+                                NavigationType::LinkClicked | NavigationType::BackForward => tab.transition.set(Transition::Link),
+                                NavigationType::FormSubmitted | NavigationType::FormResubmitted => tab.transition.set(Transition::Form),
+                                NavigationType::Reload => tab.transition.set(Transition::Reload),
+                                _ => {}
+                            }
+                        }
+                        let new_tab = action.mouse_button() == 2
+                            || action.modifiers() & ModifierType::CONTROL_MASK.bits() != 0;
+                        if new_tab && action.navigation_type() == NavigationType::LinkClicked {
+                            if let Some(uri) = action.request().and_then(|r| r.uri()) {
+                                decision.ignore();
+                                let st = st.clone();
+                                let id = id.clone();
+                                glib::idle_add_local_once(move || {
+                                    // Links stay in the container they were clicked in
+                                    let container = st.tab(&id).and_then(|t| t.container.clone());
+                                    Self::open_tab(&st, Some(uri.as_str()), None, true, container.as_ref());
+                                });
+                                return true;
+                            }
+                        }
+                    }
                     if let Some(request) = nav_decision.request() {
                         if let Some(uri) = request.uri() {
                             let uri_str = uri.as_str();
@@ -439,15 +1929,44 @@ impl BrowserWindow {
                                 }
                                 
                                 decision.ignore();
-                                let wv_clone = wv.clone();
+                                let st = st.clone();
+                                let id = id.clone();
                                 let uri_owned = uri_str.to_string();
-                                let history_clone = history_policy.clone();
-                                let toolbar_clone = toolbar_policy.clone();
                                 glib::idle_add_local_once(move || {
-                                    Self::handle_internal_url(&wv_clone, &uri_owned, &history_clone, &toolbar_clone);
+                                    if let Some(tab) = st.tab(&id) {
+                                        Self::handle_internal_url(&st, &tab, &uri_owned);
+                                    }
                                 });
                                 return true;
                             }
+
+                            // Dangerous sites get marshall://blocked instead.
+                            // WebKit doesn't say which frame is navigating, so
+                            // a dangerous frame takes its page with it.
+                            if uri_str.starts_with("http://") || uri_str.starts_with("https://") {
+                                let analysis = ThreatIntel::global().read().check_navigation(uri_str);
+                                if let Some(analysis) = analysis {
+                                    warn!("Blocked {} as {} (score {})", analysis.domain, analysis.level, analysis.score);
+                                    decision.ignore();
+                                    let st = st.clone();
+                                    let id = id.clone();
+                                    let blocked = format!("marshall://blocked?url={}", urlencoding::encode(uri_str));
+                                    glib::idle_add_local_once(move || {
+                                        if let Some(tab) = st.tab(&id) {
+                                            Self::handle_internal_url(&st, &tab, &blocked);
+                                        }
+                                    });
+                                    return true;
+                                }
+
+                                // Pages navigate from a view at rest, while
+                                // frames mostly load along with their page;
+                                // WebKit doesn't say which frame it is
+                                if !wv.is_loading() {
+                                    let container = st.tab(&id).and_then(|t| t.container.clone());
+                                    Self::prepare_load(&st.config, wv, container.as_ref(), uri_str);
+                                }
+                            }
                         }
                     }
                 }
@@ -455,79 +1974,204 @@ impl BrowserWindow {
             false // Let WebKit handle other decisions
         });
 
-        // URL changed - show marshall://home for internal homepage
-        let toolbar_clone = toolbar.clone();
+        // target=_blank and window.open - WebKit loads the request into the
+        // view we return
+        let st = state.clone();
+        let container = tab.container.clone();
+        webview.connect_create(move |wv, _action| {
+            let tab = Self::open_tab(&st, None, Some(wv), false, container.as_ref());
+            Some(tab.webview.clone().upcast())
+        });
+
+        // window.close() from the page
+        let st = state.clone();
+        let id = tab.id.clone();
+        webview.connect_close(move |_| {
+            Self::close_tab(&st, &id);
+        });
+
+        // URL changed
+        let st = state.clone();
+        let id = tab.id.clone();
         webview.connect_uri_notify(move |wv| {
-            if let Some(uri) = wv.uri() {
-                // Show marshall://home for internal pages and blank pages
-                if uri.starts_with("marshall://") || uri == "about:blank" || uri.is_empty() {
-                    toolbar_clone.set_url("marshall://home");
-                } else {
-                    toolbar_clone.set_url(&uri);
+            if let Some(tab) = st.tab(&id) {
+                // Same-document navigations (pushState, fragments) never commit a load
+                if !wv.is_loading() {
+                    Self::record_page(&st, &tab);
+                }
+                let url = tab.display_url();
+                st.tab_manager.update_tab_url(&id, &url);
+                if st.is_active(&id) {
+                    st.toolbar.set_url(&url);
                 }
             }
         });
 
         // Title changed
+        let st = state.clone();
+        let id = tab.id.clone();
         webview.connect_title_notify(move |wv| {
-            if let Some(title) = wv.title() {
-                let _ = title;
+            let title = wv
+                .title()
+                .map(|t| t.to_string())
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(|| "New Tab".to_string());
+            st.tab_manager.update_tab_title(&id, &title);
+            if let Some(tab) = st.tab(&id) {
+                tab.label.set_title(&title);
+                let has_title = wv.title().is_some_and(|t| !t.is_empty());
+                if let Some(uri) = wv.uri().filter(|u| has_title && !st.private && u.starts_with("http")) {
+                    if let Err(e) = HistoryManager::global().set_title(&uri, &title) {
+                        warn!("{}", e);
+                    }
+                }
+                if st.is_active(&id) {
+                    Self::sync_toolbar(&st, &tab);
+                }
+            }
+        });
+
+        // Favicon changed
+        let st = state.clone();
+        let id = tab.id.clone();
+        webview.connect_favicon_notify(move |wv| {
+            let icon = wv.favicon().and_then(|surface| Self::favicon_pixbuf(&surface));
+            if let Some(tab) = st.tab(&id) {
+                tab.label.set_favicon(icon.as_ref());
             }
+            let favicon_uri = wv
+                .uri()
+                .and_then(|uri| wv.context()?.favicon_database()?.favicon_uri(&uri));
+            st.tab_manager.update_tab_favicon(&id, favicon_uri.as_deref());
         });
 
         // Can go back/forward changed - WebKit history for external pages
         // Note: Internal marshall:// history is managed separately
-        let toolbar_clone = toolbar.clone();
-        let history_wb = history.clone();
-        webview.connect_notify_local(Some("can-go-back"), move |wv, _| {
-            // Combine WebKit's history with our internal history
-            let internal_can_back = history_wb.borrow().can_go_back();
-            toolbar_clone.set_can_go_back(wv.can_go_back() || internal_can_back);
+        let st = state.clone();
+        let id = tab.id.clone();
+        webview.connect_notify_local(Some("can-go-back"), move |_, _| {
+            if let Some(tab) = st.tab(&id).filter(|_| st.is_active(&id)) {
+                st.toolbar.set_can_go_back(tab.can_go_back());
+            }
         });
 
-        let toolbar_clone = toolbar.clone();
-        let history_wf = history.clone();
-        webview.connect_notify_local(Some("can-go-forward"), move |wv, _| {
-            let internal_can_fwd = history_wf.borrow().can_go_forward();
-            toolbar_clone.set_can_go_forward(wv.can_go_forward() || internal_can_fwd);
+        let st = state.clone();
+        let id = tab.id.clone();
+        webview.connect_notify_local(Some("can-go-forward"), move |_, _| {
+            if let Some(tab) = st.tab(&id).filter(|_| st.is_active(&id)) {
+                st.toolbar.set_can_go_forward(tab.can_go_forward());
+            }
         });
 
         // Loading progress
-        let status_bar_clone = status_bar.clone();
+        let st = state.clone();
+        let id = tab.id.clone();
         webview.connect_estimated_load_progress_notify(move |wv| {
-            let progress = wv.estimated_load_progress();
-            status_bar_clone.set_progress(progress);
+            if st.is_active(&id) {
+                st.status_bar.set_progress(wv.estimated_load_progress());
+            }
+        });
+
+        // Count blocked subresources. The content filter does the blocking;
+        // this mirrors its decision so the page's count can be shown.
+        let st = state.clone();
+        let id = tab.id.clone();
+        webview.connect_resource_load_started(move |wv, _resource, request| {
+            let page = wv.uri().map(|u| u.to_string()).unwrap_or_default();
+            let uri = match request.uri() {
+                Some(uri) if uri.as_str() != page => uri,
+                _ => return,
+            };
+            if st.shields.should_block(&uri, &page, "") {
+                if let Some(tab) = st.tab(&id) {
+                    tab.blocked.set(tab.blocked.get() + 1);
+                    if st.is_active(&id) {
+                        st.status_bar.set_blocked_count(tab.blocked.get());
+                        st.toolbar.set_blocked_count(tab.blocked.get());
+                    }
+                }
+            }
+        });
+
+        // A history entry that fails to load must not swallow the next page
+        let st = state.clone();
+        let id = tab.id.clone();
+        webview.connect_load_failed(move |_, _, _, _| {
+            if let Some(tab) = st.tab(&id) {
+                tab.traversing.set(false);
+            }
+            false
         });
 
         // Load finished - inject Marshall script on DuckDuckGo
-        let status_bar_clone2 = status_bar.clone();
+        let st = state.clone();
+        let id = tab.id.clone();
         webview.connect_load_changed(move |wv, event| {
+            let active = st.is_active(&id);
             match event {
                 LoadEvent::Started => {
-                    status_bar_clone2.set_status("Loading...");
+                    let uri = wv.uri().map(|u| u.to_string()).unwrap_or_default();
+                    let profile = SiteProfiles::global().for_url(&uri);
+                    let container = st.tab(&id).and_then(|t| t.container.clone());
+                    Self::apply_site_settings(wv, &st.config, profile.as_ref(), container.as_ref());
+                    if active {
+                        Self::apply_cookie_policy(wv, &st.config);
+                    }
+                    Self::apply_shields(wv, &st.shields, &st.content_filter);
+                    st.tab_manager.set_tab_loading(&id, true);
+                    if let Some(tab) = st.tab(&id) {
+                        tab.blocked.set(0);
+                    }
+                    if active {
+                        let host = wv.uri().map(|uri| host_of(&uri)).unwrap_or_default();
+                        st.toolbar.set_shield_active(st.shields.is_enabled_for(&host));
+                        st.status_bar.set_blocked_count(0);
+                        st.toolbar.set_blocked_count(0);
+                        st.status_bar.set_status("Loading...");
+                    }
+                }
+                LoadEvent::Redirected => {
+                    Self::apply_shields(wv, &st.shields, &st.content_filter);
                 }
                 LoadEvent::Committed => {
+                    if let Some(tab) = st.tab(&id) {
+                        Self::record_page(&st, &tab);
+                    }
+                    let warning = Self::threat_warning(&wv.uri().map(|u| u.to_string()).unwrap_or_default());
+                    if let Some(warning) = &warning {
+                        info!("{}", warning);
+                    }
+                    if active {
+                        st.toolbar.set_threat_warning(warning.as_deref());
+                    }
                     if let Some(uri) = wv.uri() {
                         if uri.contains("duckduckgo.com") || uri.contains("duck.ai") {
                             // Inject early CSS to hide branding immediately
                             Self::inject_marshall_script(wv);
-                            status_bar_clone2.set_status("🔍 Dr Marshall Active");
-                        } else {
-                            status_bar_clone2.set_status("Receiving data...");
+                            if active {
+                                st.status_bar.set_status("🔍 Dr Marshall Active");
+                            }
+                        } else if active {
+                            st.status_bar.set_status("Receiving data...");
                         }
                     }
                 }
                 LoadEvent::Finished => {
+                    st.tab_manager.set_tab_loading(&id, false);
                     if let Some(uri) = wv.uri() {
                         if uri.contains("duckduckgo.com") || uri.contains("duck.ai") {
                             // Inject again after page fully loads
                             Self::inject_marshall_script(wv);
-                            status_bar_clone2.set_status("✓ Dr Marshall Complete");
-                        } else {
-                            status_bar_clone2.set_status("Done");
+                            if active {
+                                st.status_bar.set_status("✓ Dr Marshall Complete");
+                            }
+                        } else if active {
+                            st.status_bar.set_status("Done");
                         }
                     }
-                    status_bar_clone2.set_progress(0.0);
+                    if active {
+                        st.status_bar.set_progress(0.0);
+                    }
                 }
                 _ => {}
             }
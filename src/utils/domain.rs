@@ -0,0 +1,135 @@
+// Copyright (c) 2026 bad-antics
+// Licensed under the MIT License. See LICENSE file in the project root.
+// https://github.com/bad-antics/marshall
+
+//! Domain names by the Public Suffix List
+//!
+//! The list, bundled as `public_suffix_list.dat`, says where registrations
+//! happen: `co.uk` and `github.io` are suffixes like `com`, so the
+//! registrable domain of `www.example.co.uk` is `example.co.uk`. The
+//! `icann_` variants ignore the list's private section, for registry
+//! questions such as which WHOIS server to ask.
+
+use std::net::IpAddr;
+
+use once_cell::sync::Lazy;
+use publicsuffix::{IcannList, List, Psl};
+
+const PUBLIC_SUFFIX_LIST: &str = include_str!("public_suffix_list.dat");
+
+static LIST: Lazy<List> = Lazy::new(|| PUBLIC_SUFFIX_LIST.parse().expect("bundled public suffix list"));
+static ICANN_LIST: Lazy<IcannList> = Lazy::new(|| PUBLIC_SUFFIX_LIST.parse().expect("bundled public suffix list"));
+
+/// The lowercase host of a URL, or of a bare `host[:port][/path]`
+pub fn host(url: &str) -> Option<String> {
+    let url = url.trim();
+    let parsed = url::Url::parse(url)
+        .ok()
+        .filter(|u| u.host_str().is_some())
+        .or_else(|| url::Url::parse(&format!("http://{}", url)).ok())?;
+    let host = parsed.host_str()?.trim_matches(['[', ']']).trim_end_matches('.');
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
+/// `host` as an IPv4 or IPv6 address, brackets allowed
+pub fn ip_literal(host: &str) -> Option<IpAddr> {
+    host.trim().trim_matches(['[', ']']).parse().ok()
+}
+
+pub fn is_ip_literal(host: &str) -> bool {
+    ip_literal(host).is_some()
+}
+
+/// The public suffix of `host`, e.g. `co.uk`. Hosts under no listed suffix
+/// get their last label; addresses have none.
+pub fn effective_tld(host: &str) -> Option<String> {
+    suffix_in(&*LIST, host)
+}
+
+/// The registrable domain of `host`, e.g. `example.co.uk`; `None` for
+/// addresses and bare suffixes
+pub fn registrable_domain(host: &str) -> Option<String> {
+    domain_in(&*LIST, host)
+}
+
+/// What `host` has in front of its registrable domain, e.g. `www`
+pub fn subdomain(host: &str) -> Option<String> {
+    let host = normalize(host);
+    let domain = registrable_domain(&host)?;
+    host.strip_suffix(&domain)
+        .and_then(|rest| rest.strip_suffix('.'))
+        .filter(|rest| !rest.is_empty())
+        .map(str::to_string)
+}
+
+/// `effective_tld` by the ICANN section alone
+pub fn icann_tld(host: &str) -> Option<String> {
+    suffix_in(&*ICANN_LIST, host)
+}
+
+/// `registrable_domain` by the ICANN section alone, e.g. `github.io` for
+/// `user.github.io`
+pub fn icann_registrable_domain(host: &str) -> Option<String> {
+    domain_in(&*ICANN_LIST, host)
+}
+
+/// Whether two hosts belong to the same site: one registrable domain, or
+/// the same address
+pub fn same_site(a: &str, b: &str) -> bool {
+    let site = |host: &str| registrable_domain(host).unwrap_or_else(|| normalize(host));
+    site(a) == site(b)
+}
+
+fn normalize(host: &str) -> String {
+    host.trim().trim_start_matches('.').trim_end_matches('.').to_ascii_lowercase()
+}
+
+fn suffix_in(list: &impl Psl, host: &str) -> Option<String> {
+    let host = normalize(host);
+    if host.is_empty() || is_ip_literal(&host) {
+        return None;
+    }
+    let suffix = list.suffix(host.as_bytes())?;
+    Some(String::from_utf8_lossy(suffix.as_bytes()).to_string())
+}
+
+fn domain_in(list: &impl Psl, host: &str) -> Option<String> {
+    let host = normalize(host);
+    if host.is_empty() || is_ip_literal(&host) {
+        return None;
+    }
+    let domain = list.domain(host.as_bytes())?;
+    Some(String::from_utf8_lossy(domain.as_bytes()).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_hosts_by_the_suffix_list() {
+        assert_eq!(effective_tld("www.example.co.uk").as_deref(), Some("co.uk"));
+        assert_eq!(registrable_domain("www.example.co.uk").as_deref(), Some("example.co.uk"));
+        assert_eq!(subdomain("a.b.example.co.uk").as_deref(), Some("a.b"));
+        assert_eq!(subdomain("example.com"), None);
+        assert_eq!(registrable_domain("co.uk"), None);
+
+        // Private suffixes are sites of their own, except to registries
+        assert_eq!(registrable_domain("user.github.io").as_deref(), Some("user.github.io"));
+        assert_eq!(icann_registrable_domain("user.github.io").as_deref(), Some("github.io"));
+        assert_eq!(icann_tld("user.github.io").as_deref(), Some("io"));
+        assert!(!same_site("alice.github.io", "bob.github.io"));
+        assert!(same_site("cdn.example.com", "EXAMPLE.com"));
+    }
+
+    #[test]
+    fn takes_hosts_and_addresses_from_urls() {
+        assert_eq!(host("https://WWW.Example.com:8443/path").as_deref(), Some("www.example.com"));
+        assert_eq!(host("example.com/path").as_deref(), Some("example.com"));
+        assert_eq!(host("localhost:8080").as_deref(), Some("localhost"));
+        assert_eq!(host("http://[::1]:8080/").as_deref(), Some("::1"));
+        assert!(is_ip_literal("[::1]") && is_ip_literal("192.168.1.1"));
+        assert_eq!(effective_tld("192.168.1.1"), None);
+        assert!(same_site("10.0.0.1", "10.0.0.1") && !same_site("10.0.0.1", "10.0.0.2"));
+    }
+}
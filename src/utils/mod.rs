@@ -3,3 +3,5 @@
 // https://github.com/bad-antics/marshall
 
 //! Utility functions
+
+pub mod domain;